@@ -0,0 +1,160 @@
+use std::fmt::Display;
+use crate::chunk::Chunk;
+use crate::chunk_type::ChunkType;
+use crate::png::Png;
+use crate::locale::text;
+
+// Por debajo de este largo la entropía no dice mucho: con pocos bytes casi todos son distintos
+pub const MIN_ANALYZED_LEN: usize = 64;
+// Bits por byte a partir de los cuales los datos parecen comprimidos o cifrados
+pub const HIGH_ENTROPY: f64 = 7.2;
+
+// Entropía de Shannon en bits por byte, entre 0 (un solo valor) y 8 (bytes al azar)
+pub fn entropy(data: &[u8]) -> f64 {
+    if data.is_empty() {
+        return 0.0;
+    }
+    let mut counts = [0usize; 256];
+    for byte in data {
+        counts[*byte as usize] += 1;
+    }
+    let len = data.len() as f64;
+    counts
+        .iter()
+        .filter(|count| **count > 0)
+        .map(|count| {
+            let p = *count as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+// Largo máximo habitual de los chunks auxiliares estándar; los que ya van comprimidos por la
+// especificación (zTXt, iCCP, iTXt comprimido) pueden tener entropía alta sin que sea raro
+fn typical_length(chunk_type: &ChunkType) -> Option<(usize, bool)> {
+    let typical = match *chunk_type {
+        ChunkType::gAMA => (4, false),
+        ChunkType::cHRM => (32, false),
+        ChunkType::sRGB => (1, false),
+        ChunkType::sBIT => (4, false),
+        ChunkType::bKGD => (6, false),
+        ChunkType::pHYs => (9, false),
+        ChunkType::tIME => (7, false),
+        ChunkType::tRNS => (256, false),
+        ChunkType::hIST => (512, false),
+        ChunkType::cICP => (4, false),
+        ChunkType::cLLI => (8, false),
+        ChunkType::mDCV => (24, false),
+        ChunkType::tEXt => (2048, false),
+        ChunkType::zTXt => (8192, true),
+        ChunkType::iTXt => (8192, true),
+        ChunkType::iCCP => (65536, true),
+        ChunkType::eXIf => (65536, false),
+        _ => return None,
+    };
+    Some(typical)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Suspicion {
+    // datos que parecen comprimidos o cifrados donde no tendría por qué haberlos
+    HighEntropy,
+    // bastante más grande que lo normal para ese tipo
+    Oversized,
+    // tipo auxiliar que ningún lector conoce, con datos que no son texto
+    UnknownBinary,
+}
+
+impl Display for Suspicion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Suspicion::HighEntropy => text(
+                "entropía alta: parece comprimido o cifrado",
+                "high entropy: looks compressed or encrypted"
+            ),
+            Suspicion::Oversized => text("más grande de lo habitual para el tipo", "larger than usual for the type"),
+            Suspicion::UnknownBinary => text("tipo desconocido con datos binarios", "unknown type with binary data"),
+        };
+        write!(f, "{}", text)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChunkAnalysis {
+    pub index: usize,
+    pub chunk_type: ChunkType,
+    pub length: usize,
+    pub entropy: f64,
+    pub suspicions: Vec<Suspicion>,
+}
+
+impl ChunkAnalysis {
+    pub fn is_suspicious(&self) -> bool {
+        !self.suspicions.is_empty()
+    }
+}
+
+// Los chunks críticos se miden pero no se marcan: IDAT siempre está comprimido
+pub fn analyze_chunk(index: usize, chunk: &Chunk) -> ChunkAnalysis {
+    let data = chunk.data();
+    let chunk_type = *chunk.chunk_type();
+    let entropy = entropy(data);
+    let mut suspicions = Vec::new();
+    if !chunk_type.is_critical() {
+        let typical = typical_length(&chunk_type);
+        let compressed_by_spec = typical.is_some_and(|(_, compressed)| compressed);
+        if data.len() >= MIN_ANALYZED_LEN && entropy >= HIGH_ENTROPY && !compressed_by_spec {
+            suspicions.push(Suspicion::HighEntropy);
+        }
+        if typical.is_some_and(|(max, _)| data.len() > max) {
+            suspicions.push(Suspicion::Oversized);
+        }
+        if typical.is_none() && !chunk_type.is_registered() && std::str::from_utf8(data).is_err() {
+            suspicions.push(Suspicion::UnknownBinary);
+        }
+    }
+    ChunkAnalysis { index, chunk_type, length: data.len(), entropy, suspicions }
+}
+
+impl Png {
+    pub fn analyze(&self) -> Vec<ChunkAnalysis> {
+        self.chunks().iter().enumerate().map(|(index, chunk)| analyze_chunk(index, chunk)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_entropy() {
+        assert_eq!(entropy(&[]), 0.0);
+        assert_eq!(entropy(&[7; 100]), 0.0);
+        let all: Vec<u8> = (0..=255).collect();
+        assert!((entropy(&all) - 8.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_flags_random_private_chunk() {
+        // pseudoaleatorio pero fijo, para que el test no dependa de la suerte
+        let mut state = 0x2545f491u32;
+        let noise: Vec<u8> = (0..1024)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect();
+        let hidden = analyze_chunk(0, &Chunk::new(ChunkType::from_str("ruSt").unwrap(), noise.clone()).unwrap());
+        assert_eq!(hidden.suspicions, vec![Suspicion::HighEntropy, Suspicion::UnknownBinary]);
+
+        let idat = analyze_chunk(1, &Chunk::new(ChunkType::IDAT, noise).unwrap());
+        assert!(!idat.is_suspicious());
+        let text = analyze_chunk(2, &Chunk::new(ChunkType::tEXt, b"Comment\0hola".to_vec()).unwrap());
+        assert!(!text.is_suspicious());
+        let gama = analyze_chunk(3, &Chunk::new(ChunkType::gAMA, vec![0; 40]).unwrap());
+        assert_eq!(gama.suspicions, vec![Suspicion::Oversized]);
+    }
+}
@@ -0,0 +1,150 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use crate::embedded_file::EmbeddedFile;
+use crate::{tr, PngmeError, Result};
+use crate::locale::text;
+
+// Varios archivos en un mismo mensaje: firma "PNGa" + versión + cantidad de entradas (u32) y,
+// por cada una, su largo (u64) seguido de la entrada con el formato de EmbeddedFile.
+// El nombre de cada entrada es su camino relativo, separado con '/'
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Archive {
+    pub entries: Vec<EmbeddedFile>,
+}
+
+impl Archive {
+    pub const MAGIC: [u8; 4] = *b"PNGa";
+    pub const VERSION: u8 = 1;
+
+    // Agrega un archivo, o todo lo que hay dentro de un directorio, bajo su propio nombre
+    pub fn add_path(&mut self, path: &Path) -> Result<()> {
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| PngmeError::InvalidPayload(tr!(
+                "{} no tiene un nombre de archivo válido",
+                "{} does not have a valid file name",
+                path.display()
+            )))?;
+        self.add_under(path, name)
+    }
+
+    fn add_under(&mut self, path: &Path, name: &str) -> Result<()> {
+        if path.is_dir() {
+            let mut children = fs::read_dir(path)?.collect::<std::io::Result<Vec<_>>>()?;
+            children.sort_by_key(|child| child.file_name());
+            for child in children {
+                let child_name = child.file_name();
+                let child_name = child_name.to_str().ok_or_else(|| {
+                    PngmeError::InvalidPayload(tr!(
+                        "{} no tiene un nombre válido",
+                        "{} does not have a valid name",
+                        child.path().display()
+                    ))
+                })?;
+                self.add_under(&child.path(), &format!("{}/{}", name, child_name))?;
+            }
+            return Ok(());
+        }
+        let mut file = EmbeddedFile::read(path)?;
+        file.name = name.to_string();
+        if self.entries.iter().any(|entry| entry.name == file.name) {
+            return Err(PngmeError::InvalidPayload(tr!("{} aparece dos veces", "{} appears twice", file.name)));
+        }
+        self.entries.push(file);
+        Ok(())
+    }
+
+    pub fn is_archive(bytes: &[u8]) -> bool {
+        bytes.len() > 4 && bytes[..4] == Archive::MAGIC
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let count = u32::try_from(self.entries.len())
+            .map_err(|_| {
+                let reason = text("demasiados archivos para un solo mensaje", "too many files for a single message");
+                PngmeError::InvalidPayload(reason.to_string())
+            })?;
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&Archive::MAGIC);
+        bytes.push(Archive::VERSION);
+        bytes.extend_from_slice(&count.to_be_bytes());
+        for entry in &self.entries {
+            let entry = entry.to_bytes()?;
+            bytes.extend_from_slice(&(entry.len() as u64).to_be_bytes());
+            bytes.extend_from_slice(&entry);
+        }
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Archive> {
+        if !Archive::is_archive(bytes) {
+            let reason = text("el mensaje no es un paquete de archivos", "the message is not a file package");
+            return Err(PngmeError::InvalidPayload(reason.to_string()));
+        }
+        if bytes[4] != Archive::VERSION {
+            return Err(PngmeError::InvalidPayload(tr!(
+                "versión de paquete desconocida: {}",
+                "unknown package version: {}",
+                bytes[4]
+            )));
+        }
+        let incomplete = || {
+            let reason = text("el paquete de archivos está incompleto", "the file package is incomplete");
+            PngmeError::InvalidPayload(reason.to_string())
+        };
+        let count_bytes = bytes.get(5..9).ok_or_else(incomplete)?;
+        let count = u32::from_be_bytes(count_bytes.try_into().expect("largo verificado"));
+
+        let mut rest = &bytes[9..];
+        let mut entries = Vec::new();
+        for _ in 0..count {
+            let len_bytes = rest.get(..8).ok_or_else(incomplete)?;
+            let len = u64::from_be_bytes(len_bytes.try_into().expect("largo verificado"));
+            let len = usize::try_from(len).ok().filter(|len| *len <= rest.len() - 8).ok_or_else(incomplete)?;
+            entries.push(EmbeddedFile::from_bytes(&rest[8..8 + len])?);
+            rest = &rest[8 + len..];
+        }
+        if !rest.is_empty() {
+            return Err(PngmeError::InvalidPayload(tr!(
+                "sobran {} bytes después del paquete",
+                "{} extra bytes after the package",
+                rest.len()
+            )));
+        }
+        Ok(Archive { entries })
+    }
+}
+
+// Camino relativo donde se restaura una entrada; None si saldría del directorio de destino
+pub fn safe_path(name: &str) -> Option<PathBuf> {
+    let path: PathBuf = name.split(['/', '\\']).filter(|part| !part.is_empty()).collect();
+    let normal = path.components().all(|component| matches!(component, Component::Normal(_)));
+    (normal && path.components().next().is_some()).then_some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, data: &[u8]) -> EmbeddedFile {
+        EmbeddedFile { name: name.to_string(), modified: None, mime: "text/plain".to_string(), data: data.to_vec() }
+    }
+
+    #[test]
+    fn test_archive_round_trip() {
+        let archive = Archive { entries: vec![entry("a.txt", b"uno"), entry("dir/b.json", b"{}"), entry("vacio", b"")] };
+        let bytes = archive.to_bytes().unwrap();
+        assert!(Archive::is_archive(&bytes));
+        assert_eq!(Archive::from_bytes(&bytes).unwrap(), archive);
+        assert!(Archive::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_safe_path() {
+        assert_eq!(safe_path("dir/b.json"), Some(PathBuf::from("dir").join("b.json")));
+        assert_eq!(safe_path("/etc/passwd"), Some(PathBuf::from("etc").join("passwd")));
+        assert_eq!(safe_path("dir/../../x"), None);
+        assert_eq!(safe_path(""), None);
+    }
+}
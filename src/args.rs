@@ -0,0 +1,1343 @@
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use clap::{Arg, Args, Command, CommandFactory, Parser, Subcommand};
+use pngme::chunk_type::ChunkType;
+use pngme::compression::Compression;
+use pngme::hash::from_hex;
+use pngme::lint::Rule;
+use pngme::locale::{self, text, Lang};
+use pngme::tr;
+use pngme::strip::StripPreset;
+use crate::color::ColorChoice;
+
+#[derive(Debug, Parser)]
+#[command(name = "pngme", version, about = "Esconde mensajes dentro de archivos PNG")]
+pub struct Cli {
+    /// No muestra barras de progreso
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+    /// Muestra qué va haciendo: -v información, -vv detalle, -vvv cada chunk
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+    /// Colores en la salida: auto (sólo en una terminal y sin NO_COLOR), always o never
+    #[arg(long, global = true, value_name = "CUANDO", value_parser = parse_color)]
+    pub color: Option<ColorChoice>,
+    /// Archivo de configuración a usar en lugar de ~/.config/pngme/config.toml
+    #[arg(long, global = true, value_name = "ARCHIVO")]
+    pub config: Option<PathBuf>,
+    /// Usa los valores de [profile.NOMBRE] de la configuración en lugar de los generales
+    #[arg(long, global = true, value_name = "NOMBRE")]
+    pub profile: Option<String>,
+    /// Formato de los mensajes de -v: text o json
+    #[arg(long, global = true, value_name = "FORMATO", default_value = "text", value_parser = parse_log_format)]
+    pub log_format: LogFormat,
+    /// Idioma de los mensajes: es o en; por defecto el de LC_ALL, LC_MESSAGES o LANG. Va antes del
+    /// comando, porque encode tiene su propio --lang para el iTXt
+    #[arg(long, value_name = "IDIOMA", value_parser = parse_lang)]
+    pub lang: Option<Lang>,
+    #[command(subcommand)]
+    pub command: PngMeArgs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+pub fn parse_color(name: &str) -> Result<ColorChoice, String> {
+    match name {
+        "auto" => Ok(ColorChoice::Auto),
+        "always" => Ok(ColorChoice::Always),
+        "never" => Ok(ColorChoice::Never),
+        _ => Err(tr!(
+            "valor desconocido: {} (auto, always o never)",
+            "unknown value: {} (auto, always or never)",
+            name
+        )),
+    }
+}
+
+pub fn parse_lang(name: &str) -> Result<Lang, String> {
+    match name {
+        "es" => Ok(Lang::Es),
+        "en" => Ok(Lang::En),
+        _ => Err(tr!("idioma desconocido: {} (es o en)", "unknown language: {} (es or en)", name)),
+    }
+}
+
+// La ayuda en inglés: descripción de cada comando (con su camino, para los anidados como chunk-type)
+const ENGLISH_COMMANDS: &[(&str, &str)] = &[
+    ("encode", "Adds a message in a new chunk"),
+    ("decode", "Shows the message stored in a chunk"),
+    ("encode-file", "Hides any file along with its name, date and MIME type"),
+    ("extract", "Restores a file stored with encode-file under its original name"),
+    ("pack", "Stores several files and directories in a single message"),
+    ("unpack", "Extracts the files stored with pack, all of them or only the given ones"),
+    ("ls", "Lists the files stored with pack"),
+    ("remove", "Removes the first chunk of the given type, or the chosen ones"),
+    ("edit", "Opens the data of a chunk in $EDITOR and saves it with the length and CRC recomputed"),
+    ("export-chunk", "Writes the raw data of one or every chunk to .bin files"),
+    ("import-chunk", "Builds a chunk from the bytes of a file and inserts it where the specification allows"),
+    ("dump", "Shows the data of a chunk as hex and ASCII with offsets from the start of the file"),
+    ("patch", "Overwrites bytes in the data of a chunk and recomputes its length and CRC"),
+    ("crc", "Computes the CRC-32 of a chunk type and its data, or checks the CRC of a raw chunk"),
+    ("chunk-type", "Explains chunk types and generates new private ones"),
+    ("chunk-type describe", "Shows the properties of each letter, whether the type is registered and if it can hold own data"),
+    ("chunk-type generate", "Generates a random ancillary private type that does not clash with any registered type"),
+    ("print", "Lists the chunks in the file"),
+    ("list", "Lists the chunks with index, type, length, CRC and offset"),
+    ("info", "Shows a summary of the file"),
+    ("capacity", "Estimates how much message fits in the file without it growing too much"),
+    ("analyze", "Measures the entropy of each chunk and flags the ones that could hide data"),
+    ("scan", "Looks for unknown private chunks, duplicates, data after IEND and huge texts"),
+    ("watch", "Watches a directory and runs a command on every new or modified PNG"),
+    ("verify", "Checks signature, CRC and structure (exit 0 = ok, 5 = warnings (was 1), 2 = errors)"),
+    ("lint", "Checks chunk order and multiplicity against the specification"),
+    ("fix-order", "Moves chunks to valid positions according to the specification"),
+    ("strip", "Removes ancillary metadata chunks before publishing an image"),
+    ("diff", "Compares two PNGs chunk by chunk"),
+    ("copy-chunks", "Copies ancillary chunks from one PNG to another"),
+    ("hash", "Computes the SHA-256 of the file or only of the image contents"),
+    ("shell", "Opens an interactive session to explore and edit the file without reading it again on every command"),
+    ("tui", "Browses the chunks in a terminal interface with a hex view of their data"),
+    ("sign", "Signs the contents with an Ed25519 key and adds a siGN chunk"),
+    ("verify-sig", "Verifies the siGN chunk with an Ed25519 public key"),
+    ("keyring-set", "Stores a password or key in the system credential store"),
+    ("keyring-delete", "Removes a key from the system credential store"),
+    ("repair", "Recomputes wrong CRCs and writes a repaired copy"),
+    ("recover", "Rescues the complete chunks of a truncated or damaged file"),
+    ("export", "Exports the chunk list to JSON"),
+    ("import", "Builds a PNG from an exported JSON, recomputing the CRCs"),
+];
+
+// id, nombre del valor (vacío si no lleva) y ayuda
+const ENGLISH_GLOBALS: &[(&str, &str, &str)] = &[
+    ("quiet", "", "Hides progress bars"),
+    ("verbose", "", "Shows what is going on: -v information, -vv detail, -vvv every chunk"),
+    ("color", "WHEN", "Colors in the output: auto (only on a terminal and without NO_COLOR), always or never"),
+    ("config", "FILE", "Configuration file to use instead of ~/.config/pngme/config.toml"),
+    ("profile", "NAME", "Uses the values of [profile.NAME] in the configuration instead of the general ones"),
+    ("log_format", "FORMAT", "Format of the -v messages: text or json"),
+    ("lang", "LANG", "Message language: es or en; by default the one in LC_ALL, LC_MESSAGES or LANG"),
+];
+
+// Ayuda en inglés de las opciones de cada comando: comando ("" si se comparte, como las de OutputArgs
+// o SecretArgs; el del comando tiene prioridad), id, nombre del valor (vacío si no cambia) y ayuda
+const ENGLISH_ARGS: &[(&str, &str, &str, &str)] = &[
+    ("", "output", "", "Output file, or \"-\" for stdout"),
+    ("", "in_place", "", "Overwrites the original file"),
+    ("", "force", "", "Overwrites the -o file if it already exists; in encode, also replaces the previous message of the \
+        same type"),
+    ("", "dry_run", "", "Shows which chunks would change and the final size, without writing anything"),
+    ("", "backup", "SUFFIX", "With --in-place, first saves a copy of the original with this suffix (.bak by default)"),
+    ("", "password", "", "Password"),
+    ("", "keyfile", "FILE", "File with the key (raw or base64) to use instead of the password"),
+    ("", "password_env", "", "Reads the password from PNGME_PASSWORD even on a terminal"),
+    ("", "key_id", "", "Name of a key stored in the system credential store"),
+    ("", "keep_unsafe_to_copy", "", "When a critical chunk changes, keeps the ancillary chunks that are not safe to \
+     copy, which are removed otherwise"),
+    ("", "recursive", "", "Walks directories and processes every .png it finds"),
+    ("", "jobs", "", "Number of threads; one per core by default"),
+    ("", "exclude", "PATTERN", "Leaves out the files that match this pattern (can be repeated)"),
+    ("", "nth", "", "Which of the chunks of that type, starting at 1"),
+    ("", "json", "", "JSON output"),
+    ("", "file_path", "", "Input PNG; - to read it from stdin"),
+    ("", "files", "", "Files, directories or glob patterns like 'renders/**/*.png'"),
+    ("", "chunk_type", "", "Chunk type"),
+    ("encode", "chunk_type", "", "Chunk type, or the keyword with --text/--ztxt/--itxt; without it, chunk-type from \
+     the configuration is used"),
+    ("encode", "message", "", "Message to store, unless it is read with -m"),
+    ("encode", "output_file", "", "Output file (same as -o)"),
+    ("encode", "replace", "", "Same as --force: replaces the previous message of the same type (or keyword), if there \
+        is one"),
+    ("encode", "append", "", "Adds the message even if there is another one of the same type, without removing it"),
+    ("encode", "allow_unsafe_type", "", "Uses the type even if it is registered, critical or has an invalid reserved \
+     bit; a warning is still shown"),
+    ("encode", "message_file", "FILE", "Reads the message from a file instead of taking it as an argument"),
+    ("encode", "spread", "FILES", "Splits the message (from -m) across several PNGs, which are overwritten: requires \
+     --in-place"),
+    ("encode", "threshold", "", "With --spread, splits with Shamir's scheme: any K files are enough to recover it"),
+    ("encode", "shares", "", "Number of Shamir shares; it must match the number of --spread files"),
+    ("encode", "text", "", "Stores the message in a standard tEXt chunk (keyword\\0text)"),
+    ("encode", "ztxt", "", "Stores the message compressed in a standard zTXt chunk"),
+    ("encode", "itxt", "", "Stores the message as international UTF-8 text in an iTXt chunk"),
+    ("encode", "lang", "", "Language tag of the iTXt chunk (for example \"es\" or \"pt-BR\")"),
+    ("encode", "translated_keyword", "", "Keyword translated into the language of the iTXt chunk"),
+    ("encode", "itxt_compress", "", "Compresses the text of the iTXt chunk"),
+    ("encode", "compress", "METHOD", "Compresses the message before storing it: deflate (default), zstd or \
+     zstd:LEVEL; decode detects it"),
+    ("encode", "max_chunk_size", "", "Splits the message into several chunks of at most this many data bytes"),
+    ("encode", "position", "POSITION", "Where to add the chunk: after-TYPE, before-TYPE or index:N; at the end by \
+     default"),
+    ("encode", "encrypt", "", "Encrypts the message with XChaCha20-Poly1305 and a key derived from the password"),
+    ("encode", "hmac", "", "Stores the message unencrypted along with an HMAC-SHA256 that decode verifies"),
+    ("encode", "kdf_memory", "", "Argon2id memory in KiB (65536 by default)"),
+    ("encode", "kdf_iterations", "", "Argon2id passes (3 by default)"),
+    ("encode", "kdf_parallelism", "", "Argon2id threads (1 by default)"),
+    ("encode", "recipient", "", "Encrypts for an age recipient (age1...); can be repeated"),
+    ("decode", "chunk_type", "", "Without it, chunk-type from the configuration is used and, if there is none, every \
+     chunk is tried as with --all"),
+    ("decode", "all", "", "Shows every readable message in the file, with the type and position of each chunk"),
+    ("decode", "gather", "FILES", "Joins a message split with encode --spread across these files"),
+    ("decode", "output", "", "Saves the message to this file. Without it, text is shown and a binary message is saved \
+     to a file named after the chunk with the extension of its type"),
+    ("decode", "decrypt", "", "Decrypts a message stored with --encrypt"),
+    ("decode", "identity", "FILE", "age identity file to decrypt a message encrypted with --recipient"),
+    ("encode-file", "input", "", "File to hide"),
+    ("encode-file", "chunk_type", "", "Type of the chunk where it is stored"),
+    ("encode-file", "compress", "METHOD", "Compresses the file before storing it: deflate (default), zstd or \
+     zstd:LEVEL"),
+    ("encode-file", "encrypt", "", "Encrypts the file with XChaCha20-Poly1305 and a key derived from the password"),
+    ("extract", "chunk_type", "", "Type of the chunk where it was stored"),
+    ("extract", "output", "", "Where to write the file; by default its original name in output-dir from the \
+     configuration or in the current directory"),
+    ("pack", "inputs", "", "Files or directories to store; directories are walked entirely"),
+    ("pack", "chunk_type", "", "Type of the chunk where it is stored"),
+    ("pack", "compress", "METHOD", "Compresses the package before storing it: deflate (default), zstd or zstd:LEVEL"),
+    ("pack", "encrypt", "", "Encrypts the package with XChaCha20-Poly1305 and a key derived from the password"),
+    ("unpack", "entries", "", "Entries to extract, with the path shown by ls; all of them by default"),
+    ("unpack", "chunk_type", "", "Type of the chunk where it was stored"),
+    ("unpack", "directory", "", "Directory to extract into; by default output-dir from the configuration or the \
+     current one"),
+    ("ls", "chunk_type", "", "Type of the chunk where it was stored"),
+    ("remove", "chunk_type", "", "Without it, chunk-type from the configuration is used"),
+    ("remove", "all", "", "Removes every chunk of that type"),
+    ("remove", "choose", "", "Shows the chunks of that type and asks which one to remove"),
+    ("remove", "i_know_what_im_doing", "", "Along with --force, allows removing IHDR, PLTE, IDAT or IEND even if the \
+     image breaks"),
+    ("edit", "chunk_type", "", "The first one of this type is edited"),
+    ("edit", "hex", "", "Edits a hex dump instead of the raw bytes, for binary data"),
+    ("export-chunk", "chunk_type", "TYPE", "Chunk type; with --all, only the ones of this type are exported"),
+    ("export-chunk", "out", "", "Output file; by default TYPE-N.bin in output-dir from the configuration or in the \
+     current directory"),
+    ("export-chunk", "all", "", "Exports every chunk, each one to TYPE-N.bin, plus a manifest.tsv with the index"),
+    ("export-chunk", "dir", "", "Directory for --all; by default output-dir from the configuration or the current one"),
+    ("export-chunk", "force", "", "Overwrites the files that already exist"),
+    ("import-chunk", "chunk_type", "TYPE", ""),
+    ("import-chunk", "from", "FILE", "File with the chunk data, as is; - to read it from stdin"),
+    ("import-chunk", "replace", "", "Replaces the data of the first chunk of that type, in the same place (so it does \
+     not go with --position)"),
+    ("import-chunk", "append", "", "Adds another chunk even if there is already one of the same type"),
+    ("import-chunk", "position", "POSITION", "Where to add the chunk: after-TYPE, before-TYPE or index:N; by default \
+     where the specification allows it"),
+    ("patch", "offset", "", "Position inside the chunk data, in decimal or in hex with 0x"),
+    ("patch", "bytes", "HEX", "Bytes to write, in hex (e.g. deadbeef); if they go past the end, the data grows"),
+    ("crc", "chunk_type", "TYPE", ""),
+    ("crc", "data_file", "FILE", "File with the data; read from stdin by default"),
+    ("crc", "chunk", "FILE", "File with a whole chunk (length, type, data and CRC) whose CRC is checked; - for stdin"),
+    ("chunk-type describe", "chunk_type", "", "Type to explain"),
+    ("chunk-type generate", "unsafe_to_copy", "", "Generates a type that is not safe to copy: editors that change the \
+     image drop it"),
+    ("chunk-type generate", "count", "", "How many types to generate"),
+    ("print", "max_bytes", "", "Maximum number of data bytes to show per chunk"),
+    ("list", "chunk_type", "", "Shows only the chunks of this type"),
+    ("list", "ancillary_only", "", "Shows only the ancillary chunks"),
+    ("list", "private_only", "", "Shows only the private chunks"),
+    ("capacity", "max_growth", "PERCENT", "How much the file can grow, as a percentage of its current size"),
+    ("analyze", "suspicious_only", "", "Shows only the suspicious chunks"),
+    ("scan", "max_text", "", "Length from which a text chunk is flagged as abnormal"),
+    ("scan", "json", "", "JSON output, with a summary per kind of finding"),
+    ("watch", "dir", "", "Directory to watch"),
+    ("watch", "on_new", "COMMAND", "pngme command to run, like \"encode {} ruSt -m meta.json\"; {} is the file and, \
+     if it does not appear, the file goes right after the command name"),
+    ("watch", "recursive", "", "Also watches the subdirectories"),
+    ("lint", "allow", "", "Codes of the rules to ignore, separated by commas (e.g. P011,P014)"),
+    ("lint", "allow_file", "", "File with one code per line; .pngmelint by default if it exists"),
+    ("strip", "files", "", "Files, directories or glob patterns like 'renders/**/*_final.png'"),
+    ("strip", "types", "", "Types to remove, separated by commas; tEXt,zTXt,iTXt,tIME,eXIf by default"),
+    ("strip", "all", "", "Removes every ancillary chunk"),
+    ("strip", "preset", "", "Predefined set: social, archive or minimal"),
+    ("strip", "keep", "", "Types that are always kept, separated by commas"),
+    ("diff", "old", "", "Original PNG"),
+    ("diff", "new", "", "Modified PNG"),
+    ("copy-chunks", "source", "", "PNG to copy the chunks from"),
+    ("copy-chunks", "target", "", "PNG to copy them into"),
+    ("copy-chunks", "types", "", "Types to copy, separated by commas; every ancillary chunk by default"),
+    ("hash", "content", "", "Ignores ancillary chunks and how the IDAT chunks are split"),
+    ("tui", "output", "", "Where `s` saves; the same file by default"),
+    ("sign", "key", "", "Ed25519 private key in PEM (PKCS#8), or the 32-byte seed raw or in base64"),
+    ("sign", "types", "", "Types to cover, separated by commas; every chunk by default"),
+    ("verify-sig", "pubkey", "", "Ed25519 public key in PEM, or its 32 bytes raw or in base64"),
+    ("keyring-set", "id", "", "Name it is stored under, to use it later with --key-id"),
+    ("keyring-set", "password", "", "Password to store; without it or --keyfile it is asked for on the terminal"),
+    ("keyring-set", "keyfile", "FILE", "File with the key to store (raw or base64)"),
+    ("keyring-delete", "id", "", "Name it was stored under"),
+    ("repair", "output", "", "Output file; by default <file>.repaired.png, or stdout if the PNG comes from stdin"),
+    ("repair", "force", "", "Overwrites the output file if it already exists"),
+    ("repair", "add_iend", "", "Adds IEND at the end if it is missing"),
+    ("repair", "dry_run", "", "Shows what would be repaired and the final size, without writing anything"),
+    ("recover", "output", "", "Output file; by default <file>.recovered.png, or stdout if the PNG comes from stdin"),
+    ("recover", "force", "", "Overwrites the output file if it already exists"),
+    ("export", "json_path", "", "Output JSON file; written to stdout if not given"),
+    ("export", "force", "", "Overwrites the JSON file if it already exists"),
+    ("import", "json_path", "", "JSON written by export"),
+    ("import", "output_file", "", "Output PNG"),
+    ("import", "force", "", "Overwrites the output PNG if it already exists"),
+];
+
+// La definición de la línea de comandos, con la ayuda en el idioma elegido
+pub fn command() -> Command {
+    let command = Cli::command();
+    if locale::lang() != Lang::En {
+        return command;
+    }
+    command
+        .about("Hides messages inside PNG files")
+        .mut_subcommands(|sub| {
+            let path = sub.get_name().to_string();
+            english_command(path, sub)
+        })
+        .mut_args(|arg| match ENGLISH_GLOBALS.iter().find(|(id, _, _)| *id == arg.get_id().as_str()) {
+            Some((_, "", help)) => arg.help(*help),
+            Some((_, value_name, help)) => arg.value_name(*value_name).help(*help),
+            None => arg,
+        })
+}
+
+fn english_command(path: String, command: Command) -> Command {
+    let command = match ENGLISH_COMMANDS.iter().find(|(name, _)| *name == path) {
+        Some((_, about)) => command.about(*about),
+        None => command,
+    };
+    command.mut_args(|arg| english_arg(&path, arg)).mut_subcommands(|sub| {
+        let nested = format!("{} {}", path, sub.get_name());
+        english_command(nested, sub)
+    })
+}
+
+fn english_arg(command: &str, arg: Arg) -> Arg {
+    let id = arg.get_id().as_str();
+    let find = |command: &str| ENGLISH_ARGS.iter().find(|(name, arg_id, _, _)| *name == command && *arg_id == id);
+    let Some((_, _, value_name, help)) = find(command).or_else(|| find("")) else {
+        return arg;
+    };
+    let arg = if value_name.is_empty() { arg } else { arg.value_name(*value_name) };
+    if help.is_empty() { arg } else { arg.help(*help) }
+}
+
+fn parse_log_format(name: &str) -> Result<LogFormat, String> {
+    match name {
+        "text" => Ok(LogFormat::Text),
+        "json" => Ok(LogFormat::Json),
+        _ => Err(tr!("formato desconocido: {} (text o json)", "unknown format: {} (text or json)", name)),
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum PngMeArgs {
+    /// Agrega un mensaje en un chunk nuevo
+    Encode(Box<EncodeArgs>),
+    /// Muestra el mensaje guardado en un chunk
+    Decode(DecodeArgs),
+    /// Esconde un archivo cualquiera junto con su nombre, fecha y tipo MIME
+    EncodeFile(EncodeFileArgs),
+    /// Restaura un archivo guardado con encode-file, con su nombre original
+    Extract(ExtractArgs),
+    /// Guarda varios archivos y directorios en un mismo mensaje
+    Pack(PackArgs),
+    /// Extrae los archivos guardados con pack, todos o sólo los indicados
+    Unpack(UnpackArgs),
+    /// Lista los archivos guardados con pack
+    Ls(LsArgs),
+    /// Elimina el primer chunk del tipo indicado, u otro o todos con --nth, --choose o --all
+    Remove(RemoveArgs),
+    /// Abre los datos de un chunk en $EDITOR y los guarda con el length y el CRC recalculados
+    Edit(EditArgs),
+    /// Escribe los datos crudos de un chunk, o de todos, en archivos .bin
+    ExportChunk(ExportChunkArgs),
+    /// Arma un chunk con los bytes de un archivo y lo inserta donde lo permite la especificación
+    ImportChunk(ImportChunkArgs),
+    /// Muestra los datos de un chunk en hexa y ASCII con los offsets desde el principio del archivo
+    Dump(DumpArgs),
+    /// Sobrescribe bytes en los datos de un chunk y recalcula su length y su CRC
+    Patch(PatchArgs),
+    /// Calcula el CRC-32 de un tipo y sus datos, o verifica el CRC de un chunk crudo
+    Crc(CrcArgs),
+    /// Explica los tipos de chunk y genera tipos privados nuevos
+    #[command(subcommand)]
+    ChunkType(ChunkTypeCommand),
+    /// Lista los chunks del archivo
+    Print(PrintArgs),
+    /// Lista los chunks con índice, tipo, length, CRC y offset
+    List(ListArgs),
+    /// Muestra un resumen del archivo
+    Info(InfoArgs),
+    /// Estima cuánto mensaje entra en el archivo sin que crezca demasiado
+    Capacity(CapacityArgs),
+    /// Mide la entropía de cada chunk y marca los que podrían esconder datos
+    Analyze(AnalyzeArgs),
+    /// Busca chunks privados desconocidos, duplicados, datos después de IEND y textos enormes
+    Scan(ScanArgs),
+    /// Vigila un directorio y aplica un comando a cada PNG nuevo o modificado
+    #[cfg(feature = "watch")]
+    Watch(WatchArgs),
+    /// Verifica firma, CRC y estructura (exit 0 = bien, 5 = avisos (antes 1), 2 = errores)
+    Verify(VerifyArgs),
+    /// Revisa el orden y la multiplicidad de los chunks según la especificación
+    Lint(LintArgs),
+    /// Reordena los chunks a posiciones válidas según la especificación
+    FixOrder(FixOrderArgs),
+    /// Borra chunks auxiliares con metadatos antes de publicar una imagen
+    Strip(StripArgs),
+    /// Compara dos PNG chunk por chunk
+    Diff(DiffArgs),
+    /// Copia chunks auxiliares de un PNG a otro
+    CopyChunks(CopyChunksArgs),
+    /// Calcula el SHA-256 del archivo o sólo del contenido de la imagen
+    Hash(HashArgs),
+    /// Abre una sesión interactiva para explorar y editar el archivo sin volver a leerlo en cada comando
+    Shell(ShellArgs),
+    /// Recorre los chunks en una interfaz de terminal con un volcado hexadecimal de sus datos
+    #[cfg(feature = "tui")]
+    Tui(TuiArgs),
+    /// Firma el contenido con una clave Ed25519 y agrega un chunk siGN
+    #[cfg(feature = "crypto")]
+    Sign(SignArgs),
+    /// Verifica el chunk siGN con una clave pública Ed25519
+    #[cfg(feature = "crypto")]
+    VerifySig(VerifySigArgs),
+    /// Guarda una contraseña o clave en el almacén de credenciales del sistema
+    #[cfg(feature = "keyring")]
+    KeyringSet(KeyringSetArgs),
+    /// Borra una clave del almacén de credenciales del sistema
+    #[cfg(feature = "keyring")]
+    KeyringDelete(KeyringDeleteArgs),
+    /// Recalcula los CRC incorrectos y escribe una copia reparada
+    Repair(RepairArgs),
+    /// Rescata los chunks completos de un archivo truncado o dañado
+    Recover(RecoverArgs),
+    /// Exporta la lista de chunks a JSON
+    #[cfg(feature = "serde")]
+    Export(ExportArgs),
+    /// Arma un PNG a partir de un JSON exportado, recalculando los CRC
+    #[cfg(feature = "serde")]
+    Import(ImportArgs),
+}
+
+#[derive(Debug, Args)]
+#[cfg_attr(feature = "crypto", command(group(clap::ArgGroup::new("protection").args(["encrypt", "hmac"]))))]
+pub struct EncodeArgs {
+    /// PNG de entrada; - para leerlo de stdin
+    #[arg(required_unless_present = "spread", value_parser = parse_input)]
+    pub file_path: Option<PathBuf>,
+    /// Tipo del chunk, o el keyword si se usa --text/--ztxt/--itxt; sin esto se usa chunk-type de la
+    /// configuración
+    #[arg(value_name = "CHUNK_TYPE|KEYWORD")]
+    pub chunk_type: Option<String>,
+    /// Mensaje a guardar, si no se lee con -m
+    pub message: Option<String>,
+    /// Archivo de salida (lo mismo que -o)
+    #[arg(conflicts_with_all = ["output", "in_place"])]
+    pub output_file: Option<PathBuf>,
+    #[command(flatten)]
+    pub output: OutputArgs,
+    /// Lo mismo que --force: reemplaza el mensaje anterior del mismo tipo (o keyword), si lo hay
+    #[arg(long)]
+    pub replace: bool,
+    /// Agrega el mensaje aunque ya haya otro del mismo tipo, sin borrar el anterior
+    #[arg(long, conflicts_with = "replace")]
+    pub append: bool,
+    /// Usa el tipo aunque sea registrado, crítico o tenga el bit reservado inválido; igual se avisa
+    #[arg(long)]
+    pub allow_unsafe_type: bool,
+    /// Lee el mensaje de un archivo en lugar de tomarlo como argumento
+    #[arg(
+        short = 'm',
+        long,
+        value_name = "ARCHIVO",
+        value_parser = parse_input,
+        conflicts_with_all = ["message", "text", "ztxt", "itxt"]
+    )]
+    pub message_file: Option<PathBuf>,
+    /// Reparte el mensaje (de -m) entre varios PNG, que se sobrescriben: exige --in-place
+    #[arg(
+        long,
+        value_name = "ARCHIVOS",
+        num_args = 1..,
+        requires = "message_file",
+        requires = "in_place",
+        conflicts_with_all = ["file_path", "chunk_type", "output_file", "output", "max_chunk_size"]
+    )]
+    pub spread: Vec<PathBuf>,
+    /// Con --spread, reparte con el esquema de Shamir: alcanzan K archivos cualesquiera para recuperarlo
+    #[cfg(feature = "crypto")]
+    #[arg(long, value_name = "K", requires = "spread")]
+    pub threshold: Option<u8>,
+    /// Cantidad de partes de Shamir; tiene que coincidir con la cantidad de archivos de --spread
+    #[cfg(feature = "crypto")]
+    #[arg(long, value_name = "N", requires = "threshold")]
+    pub shares: Option<usize>,
+    /// Guarda el mensaje en un chunk tEXt estándar (keyword\0texto)
+    #[arg(long, conflicts_with_all = ["ztxt", "itxt"])]
+    pub text: bool,
+    /// Guarda el mensaje comprimido en un chunk zTXt estándar
+    #[arg(long, conflicts_with = "itxt")]
+    pub ztxt: bool,
+    /// Guarda el mensaje como texto internacional UTF-8 en un chunk iTXt
+    #[arg(long)]
+    pub itxt: bool,
+    /// Etiqueta de idioma del chunk iTXt (por ejemplo "es" o "pt-BR")
+    #[arg(long, requires = "itxt", default_value = "")]
+    pub lang: String,
+    /// Keyword traducido al idioma del chunk iTXt
+    #[arg(long, requires = "itxt", default_value = "")]
+    pub translated_keyword: String,
+    /// Comprime el texto del chunk iTXt
+    #[arg(long, requires = "itxt")]
+    pub itxt_compress: bool,
+    /// Comprime el mensaje antes de guardarlo: deflate (por defecto), zstd o zstd:NIVEL; decode lo detecta solo
+    #[arg(
+        long,
+        value_name = "METODO",
+        num_args = 0..=1,
+        default_missing_value = "deflate",
+        value_parser = parse_compression,
+        conflicts_with_all = ["text", "ztxt", "itxt"]
+    )]
+    pub compress: Option<Compression>,
+    /// Reparte el mensaje en varios chunks de a lo sumo esta cantidad de bytes de datos
+    #[arg(long, value_name = "BYTES", conflicts_with_all = ["text", "ztxt", "itxt"])]
+    pub max_chunk_size: Option<usize>,
+    /// Dónde agregar el chunk: after-TIPO, before-TIPO o index:N; por defecto al final
+    #[arg(long, value_name = "POSICION", value_parser = parse_position, conflicts_with = "spread")]
+    pub position: Option<Position>,
+    /// Cifra el mensaje con XChaCha20-Poly1305 y una clave derivada de la contraseña
+    #[cfg(feature = "crypto")]
+    #[arg(long, conflicts_with_all = ["text", "ztxt", "itxt"])]
+    pub encrypt: bool,
+    /// Guarda el mensaje sin cifrar junto con un HMAC-SHA256 que decode verifica
+    #[cfg(feature = "crypto")]
+    #[arg(long, conflicts_with_all = ["encrypt", "text", "ztxt", "itxt"])]
+    pub hmac: bool,
+    #[cfg(feature = "crypto")]
+    #[command(flatten)]
+    pub secret: SecretArgs,
+    /// Memoria de Argon2id en KiB (por defecto 65536)
+    #[cfg(feature = "crypto")]
+    #[arg(long, value_name = "KIB", requires = "protection")]
+    pub kdf_memory: Option<u32>,
+    /// Pasadas de Argon2id (por defecto 3)
+    #[cfg(feature = "crypto")]
+    #[arg(long, value_name = "N", requires = "protection")]
+    pub kdf_iterations: Option<u32>,
+    /// Hilos de Argon2id (por defecto 1)
+    #[cfg(feature = "crypto")]
+    #[arg(long, value_name = "N", requires = "protection")]
+    pub kdf_parallelism: Option<u8>,
+    /// Cifra para un destinatario age (age1...); se puede repetir
+    #[cfg(feature = "age")]
+    #[arg(long, value_name = "AGE1...", conflicts_with_all = ["encrypt", "text", "ztxt", "itxt"])]
+    pub recipient: Vec<String>,
+}
+
+pub fn parse_compression(name: &str) -> Result<Compression, String> {
+    let unknown = || tr!("método de compresión desconocido: {}", "unknown compression method: {}", name);
+    Compression::from_name(name).ok_or_else(unknown)
+}
+
+#[derive(Debug, Args)]
+pub struct DecodeArgs {
+    /// PNG de entrada; - para leerlo de stdin
+    #[arg(required_unless_present = "gather", value_parser = parse_input)]
+    pub file_path: Option<PathBuf>,
+    /// Sin esto se usa chunk-type de la configuración y, si no hay, se prueban todos como con --all
+    pub chunk_type: Option<String>,
+    /// Muestra todos los mensajes legibles del archivo, con el tipo y la posición de cada chunk
+    #[arg(long, conflicts_with_all = ["chunk_type", "gather", "output"])]
+    pub all: bool,
+    /// Junta un mensaje repartido con encode --spread entre estos archivos
+    #[arg(long, value_name = "ARCHIVOS", num_args = 1.., conflicts_with_all = ["file_path", "chunk_type"])]
+    pub gather: Vec<PathBuf>,
+    /// Guarda el mensaje en este archivo. Sin esto el texto se muestra y un mensaje binario se guarda
+    /// en un archivo con el nombre del chunk y la extensión de su tipo
+    #[arg(short, long, visible_alias = "out")]
+    pub output: Option<PathBuf>,
+    /// Descifra un mensaje guardado con --encrypt
+    #[cfg(feature = "crypto")]
+    #[arg(long)]
+    pub decrypt: bool,
+    #[cfg(feature = "crypto")]
+    #[command(flatten)]
+    pub secret: SecretArgs,
+    /// Archivo de identidades age para descifrar un mensaje cifrado con --recipient
+    #[cfg(feature = "age")]
+    #[arg(long, value_name = "ARCHIVO", conflicts_with = "decrypt", value_parser = parse_input)]
+    pub identity: Option<PathBuf>,
+}
+
+// De dónde sale la clave de --encrypt, --decrypt y --hmac; se puede indicar sólo una. Sin ninguna
+// se pide por la terminal o, si stdin no es una terminal, se lee de PNGME_PASSWORD
+#[cfg(feature = "crypto")]
+#[derive(Debug, Default, Args)]
+#[group(id = "secret", multiple = false)]
+pub struct SecretArgs {
+    /// Contraseña
+    #[arg(long)]
+    pub password: Option<String>,
+    /// Archivo con la clave (en crudo o base64) a usar en lugar de la contraseña
+    #[arg(long, value_name = "ARCHIVO", value_parser = parse_input)]
+    pub keyfile: Option<PathBuf>,
+    /// Lee la contraseña de PNGME_PASSWORD aunque se esté en una terminal
+    #[arg(long)]
+    pub password_env: bool,
+    /// Nombre de una clave guardada en el almacén de credenciales del sistema
+    #[cfg(feature = "keyring")]
+    #[arg(long, value_name = "ID")]
+    pub key_id: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct EncodeFileArgs {
+    /// PNG de entrada; - para leerlo de stdin
+    #[arg(value_parser = parse_input)]
+    pub file_path: PathBuf,
+    /// Archivo a esconder
+    #[arg(value_parser = parse_input)]
+    pub input: PathBuf,
+    /// Tipo del chunk donde se guarda
+    #[arg(long, default_value = "fiLe", value_parser = ChunkType::from_str)]
+    pub chunk_type: ChunkType,
+    /// Comprime el archivo antes de guardarlo: deflate (por defecto), zstd o zstd:NIVEL
+    #[arg(long, value_name = "METODO", num_args = 0..=1, default_missing_value = "deflate", value_parser = parse_compression)]
+    pub compress: Option<Compression>,
+    /// Cifra el archivo con XChaCha20-Poly1305 y una clave derivada de la contraseña
+    #[cfg(feature = "crypto")]
+    #[arg(long)]
+    pub encrypt: bool,
+    #[cfg(feature = "crypto")]
+    #[command(flatten)]
+    pub secret: SecretArgs,
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct ExtractArgs {
+    /// PNG de entrada; - para leerlo de stdin
+    #[arg(value_parser = parse_input)]
+    pub file_path: PathBuf,
+    /// Tipo del chunk donde se guardó
+    #[arg(long, default_value = "fiLe")]
+    pub chunk_type: String,
+    /// Dónde escribir el archivo; por defecto se usa su nombre original en output-dir de la
+    /// configuración o en el directorio actual
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+    #[cfg(feature = "crypto")]
+    #[command(flatten)]
+    pub secret: SecretArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct PackArgs {
+    /// PNG de entrada; - para leerlo de stdin
+    #[arg(value_parser = parse_input)]
+    pub file_path: PathBuf,
+    /// Archivos o directorios a guardar; los directorios se recorren enteros
+    #[arg(required = true)]
+    pub inputs: Vec<PathBuf>,
+    /// Tipo del chunk donde se guarda
+    #[arg(long, default_value = "paCk", value_parser = ChunkType::from_str)]
+    pub chunk_type: ChunkType,
+    /// Comprime el paquete antes de guardarlo: deflate (por defecto), zstd o zstd:NIVEL
+    #[arg(long, value_name = "METODO", num_args = 0..=1, default_missing_value = "deflate", value_parser = parse_compression)]
+    pub compress: Option<Compression>,
+    /// Cifra el paquete con XChaCha20-Poly1305 y una clave derivada de la contraseña
+    #[cfg(feature = "crypto")]
+    #[arg(long)]
+    pub encrypt: bool,
+    #[cfg(feature = "crypto")]
+    #[command(flatten)]
+    pub secret: SecretArgs,
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct UnpackArgs {
+    /// PNG de entrada; - para leerlo de stdin
+    #[arg(value_parser = parse_input)]
+    pub file_path: PathBuf,
+    /// Entradas a extraer, con el camino que muestra ls; por defecto todas
+    pub entries: Vec<String>,
+    /// Tipo del chunk donde se guardó
+    #[arg(long, default_value = "paCk")]
+    pub chunk_type: String,
+    /// Directorio donde se extrae; por defecto output-dir de la configuración o el actual
+    #[arg(short = 'C', long)]
+    pub directory: Option<PathBuf>,
+    #[cfg(feature = "crypto")]
+    #[command(flatten)]
+    pub secret: SecretArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct LsArgs {
+    /// PNG de entrada; - para leerlo de stdin
+    #[arg(value_parser = parse_input)]
+    pub file_path: PathBuf,
+    /// Tipo del chunk donde se guardó
+    #[arg(long, default_value = "paCk")]
+    pub chunk_type: String,
+    #[cfg(feature = "crypto")]
+    #[command(flatten)]
+    pub secret: SecretArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct RemoveArgs {
+    /// PNG de entrada; - para leerlo de stdin
+    #[arg(value_parser = parse_input)]
+    pub file_path: PathBuf,
+    /// Sin esto se usa chunk-type de la configuración
+    pub chunk_type: Option<String>,
+    /// Elimina todos los chunks de ese tipo
+    #[arg(long, conflicts_with_all = ["nth", "choose"])]
+    pub all: bool,
+    /// Cuál de los chunks de ese tipo, empezando por 1
+    #[arg(long, default_value_t = 1, value_parser = parse_nth, conflicts_with = "choose")]
+    pub nth: usize,
+    /// Muestra los chunks de ese tipo y pregunta cuál eliminar
+    #[arg(long)]
+    pub choose: bool,
+    /// Junto con --force, permite eliminar IHDR, PLTE, IDAT o IEND aunque la imagen quede rota
+    #[arg(long, requires = "force")]
+    pub i_know_what_im_doing: bool,
+    /// Al cambiar un chunk crítico conserva los auxiliares que no son seguros para copiar, que si no se eliminan
+    #[arg(long)]
+    pub keep_unsafe_to_copy: bool,
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct EditArgs {
+    /// PNG de entrada; - para leerlo de stdin
+    #[arg(value_parser = parse_input)]
+    pub file_path: PathBuf,
+    /// Se edita el primero de este tipo
+    pub chunk_type: String,
+    /// Edita un volcado hexadecimal en lugar de los bytes tal cual, para datos binarios
+    #[arg(long)]
+    pub hex: bool,
+    /// Al cambiar un chunk crítico conserva los auxiliares que no son seguros para copiar, que si no se eliminan
+    #[arg(long)]
+    pub keep_unsafe_to_copy: bool,
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct ExportChunkArgs {
+    /// PNG de entrada; - para leerlo de stdin
+    #[arg(value_parser = parse_input)]
+    pub file_path: PathBuf,
+    /// Tipo del chunk; con --all, exporta sólo los de este tipo
+    #[arg(long = "type", value_name = "TIPO", required_unless_present = "all")]
+    pub chunk_type: Option<String>,
+    /// Archivo de salida; por defecto TIPO-N.bin en output-dir de la configuración o en el directorio actual
+    #[arg(long, conflicts_with = "all")]
+    pub out: Option<PathBuf>,
+    /// Exporta todos los chunks, cada uno en TIPO-N.bin, más un manifest.tsv con el índice
+    #[arg(long)]
+    pub all: bool,
+    /// Directorio para --all; por defecto output-dir de la configuración o el actual
+    #[arg(long, requires = "all")]
+    pub dir: Option<PathBuf>,
+    /// Pisa los archivos que ya existan
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct ImportChunkArgs {
+    /// PNG de entrada; - para leerlo de stdin
+    #[arg(value_parser = parse_input)]
+    pub file_path: PathBuf,
+    #[arg(long = "type", value_name = "TIPO")]
+    pub chunk_type: String,
+    /// Archivo con los datos del chunk, tal cual; - para leerlos de stdin
+    #[arg(long, value_name = "ARCHIVO", value_parser = parse_input)]
+    pub from: PathBuf,
+    /// Reemplaza los datos del primer chunk de ese tipo, en su mismo lugar (por eso no va con --position)
+    #[arg(long, conflicts_with_all = ["append", "position"])]
+    pub replace: bool,
+    /// Agrega otro chunk aunque ya haya uno del mismo tipo
+    #[arg(long)]
+    pub append: bool,
+    /// Dónde agregar el chunk: after-TIPO, before-TIPO o index:N; por defecto donde lo permite la
+    /// especificación
+    #[arg(long, value_name = "POSICION", value_parser = parse_position)]
+    pub position: Option<Position>,
+    #[command(flatten)]
+    pub output: OutputArgs,
+    /// Al cambiar un chunk crítico conserva los auxiliares que no son seguros para copiar, que si no se eliminan
+    #[arg(long)]
+    pub keep_unsafe_to_copy: bool,
+}
+
+// Dónde insertar un chunk nuevo
+#[derive(Debug, Clone)]
+pub enum Position {
+    Index(usize),
+    // antes del primero de ese tipo
+    Before(String),
+    // después del último de ese tipo
+    After(String),
+}
+
+pub fn parse_position(value: &str) -> Result<Position, String> {
+    let chunk_type = |name: &str| match name.len() {
+        4 => ChunkType::from_str(name).map(|_| name.to_string()).map_err(|err| err.to_string()),
+        _ => Err(tr!("tipo de chunk inválido: {}", "invalid chunk type: {}", name)),
+    };
+    if let Some(index) = value.strip_prefix("index:") {
+        let invalid = |_| tr!("posición inválida: {}", "invalid position: {}", value);
+        return index.parse().map(Position::Index).map_err(invalid);
+    }
+    if let Some(name) = value.strip_prefix("before-") {
+        return chunk_type(name).map(Position::Before);
+    }
+    if let Some(name) = value.strip_prefix("after-") {
+        return chunk_type(name).map(Position::After);
+    }
+    Err(tr!(
+        "posición inválida: {} (after-TIPO, before-TIPO o index:N)",
+        "invalid position: {} (after-TYPE, before-TYPE or index:N)",
+        value
+    ))
+}
+
+#[derive(Debug, Args)]
+pub struct DumpArgs {
+    /// PNG de entrada; - para leerlo de stdin
+    #[arg(value_parser = parse_input)]
+    pub file_path: PathBuf,
+    /// Tipo del chunk
+    pub chunk_type: String,
+    /// Cuál de los chunks de ese tipo, empezando por 1
+    #[arg(long, default_value_t = 1, value_parser = parse_nth)]
+    pub nth: usize,
+}
+
+#[derive(Debug, Args)]
+pub struct PatchArgs {
+    /// PNG de entrada; - para leerlo de stdin
+    #[arg(value_parser = parse_input)]
+    pub file_path: PathBuf,
+    /// Tipo del chunk
+    pub chunk_type: String,
+    /// Posición dentro de los datos del chunk, en decimal o en hexa con 0x
+    #[arg(long, value_parser = parse_offset)]
+    pub offset: usize,
+    /// Bytes a escribir, en hexa (ej. deadbeef); si pasan del final, los datos se alargan
+    // con el camino completo clap no lo toma como una lista de valores sueltos
+    #[arg(long, value_name = "HEXA", value_parser = parse_hex_bytes)]
+    pub bytes: ::std::vec::Vec<u8>,
+    /// Cuál de los chunks de ese tipo, empezando por 1
+    #[arg(long, default_value_t = 1, value_parser = parse_nth)]
+    pub nth: usize,
+    #[command(flatten)]
+    pub output: OutputArgs,
+    /// Al cambiar un chunk crítico conserva los auxiliares que no son seguros para copiar, que si no se eliminan
+    #[arg(long)]
+    pub keep_unsafe_to_copy: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct CrcArgs {
+    #[arg(long = "type", value_name = "TIPO", required_unless_present = "chunk", value_parser = ChunkType::from_str)]
+    pub chunk_type: Option<ChunkType>,
+    /// Archivo con los datos; por defecto se leen de stdin
+    #[arg(long, value_name = "ARCHIVO", conflicts_with = "chunk", value_parser = parse_input)]
+    pub data_file: Option<PathBuf>,
+    /// Archivo con un chunk entero (length, tipo, datos y CRC) cuyo CRC se verifica; - para stdin
+    #[arg(long, value_name = "ARCHIVO", conflicts_with = "chunk_type", value_parser = parse_input)]
+    pub chunk: Option<PathBuf>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ChunkTypeCommand {
+    /// Muestra las propiedades de cada letra, si el tipo está registrado y si se puede usar para datos propios
+    Describe(DescribeArgs),
+    /// Genera un tipo auxiliar y privado al azar que no choca con ningún tipo registrado
+    Generate(GenerateArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct GenerateArgs {
+    /// Genera un tipo que no es seguro para copiar: los editores que cambian la imagen lo descartan
+    #[arg(long)]
+    pub unsafe_to_copy: bool,
+    /// Cuántos tipos generar
+    #[arg(short = 'n', long, default_value_t = 1)]
+    pub count: usize,
+}
+
+#[derive(Debug, Args)]
+pub struct DescribeArgs {
+    /// Tipo a explicar
+    #[arg(value_parser = ChunkType::from_str)]
+    pub chunk_type: ChunkType,
+}
+
+pub fn parse_offset(value: &str) -> Result<usize, String> {
+    let parsed = match value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+        Some(hex) => usize::from_str_radix(hex, 16),
+        None => value.parse(),
+    };
+    parsed.map_err(|_| tr!("offset inválido: {}", "invalid offset: {}", value))
+}
+
+pub fn parse_hex_bytes(value: &str) -> Result<Vec<u8>, String> {
+    let digits: String = value.split_whitespace().collect();
+    match from_hex(&digits) {
+        Some(bytes) if !bytes.is_empty() => Ok(bytes),
+        _ => Err(tr!(
+            "bytes inválidos: {}; van en hexa, dos dígitos por byte",
+            "invalid bytes: {}; they go in hex, two digits per byte",
+            value
+        )),
+    }
+}
+
+pub fn parse_nth(value: &str) -> Result<usize, String> {
+    match value.parse() {
+        Ok(0) => Err(text("se cuenta desde 1", "counting starts at 1").to_string()),
+        Ok(nth) => Ok(nth),
+        Err(_) => Err(tr!("número inválido: {}", "invalid number: {}", value)),
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct PrintArgs {
+    /// PNG de entrada; - para leerlo de stdin
+    #[arg(value_parser = parse_input)]
+    pub file_path: PathBuf,
+    /// Cantidad máxima de bytes de datos a mostrar por chunk
+    #[arg(long, default_value_t = pngme::chunk::Chunk::DEFAULT_PREVIEW_BYTES)]
+    pub max_bytes: usize,
+}
+
+#[derive(Debug, Args)]
+pub struct ListArgs {
+    /// Archivos, directorios o patrones glob como 'renders/**/*.png'
+    #[arg(required = true)]
+    pub files: Vec<PathBuf>,
+    #[command(flatten)]
+    pub batch: BatchArgs,
+    /// Muestra sólo los chunks de este tipo
+    #[arg(long = "type")]
+    pub chunk_type: Option<String>,
+    /// Muestra sólo los chunks auxiliares
+    #[arg(long)]
+    pub ancillary_only: bool,
+    /// Muestra sólo los chunks privados
+    #[arg(long)]
+    pub private_only: bool,
+    /// Salida en JSON
+    #[cfg(feature = "serde")]
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct InfoArgs {
+    /// Archivos, directorios o patrones glob como 'renders/**/*.png'
+    #[arg(required = true)]
+    pub files: Vec<PathBuf>,
+    #[command(flatten)]
+    pub batch: BatchArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct CapacityArgs {
+    /// PNG de entrada; - para leerlo de stdin
+    #[arg(value_parser = parse_input)]
+    pub file_path: PathBuf,
+    /// Cuánto puede crecer el archivo, en porcentaje de su tamaño actual
+    #[arg(long, value_name = "PORCENTAJE", default_value_t = 10.0, value_parser = parse_percent)]
+    pub max_growth: f64,
+}
+
+#[derive(Debug, Args)]
+pub struct AnalyzeArgs {
+    /// Archivos, directorios o patrones glob como 'renders/**/*.png'
+    #[arg(required = true)]
+    pub files: Vec<PathBuf>,
+    #[command(flatten)]
+    pub batch: BatchArgs,
+    /// Muestra sólo los chunks sospechosos
+    #[arg(long)]
+    pub suspicious_only: bool,
+    /// Salida en JSON
+    #[cfg(feature = "serde")]
+    #[arg(long)]
+    pub json: bool,
+}
+
+// Para los comandos que modifican el PNG: el original sólo se sobrescribe si se pide con --in-place
+#[derive(Debug, Args)]
+pub struct OutputArgs {
+    /// Archivo de salida, o "-" para stdout
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+    /// Sobrescribe el archivo original
+    #[arg(long, conflicts_with = "output")]
+    pub in_place: bool,
+    /// Pisa el archivo de -o si ya existe; en encode, también reemplaza el mensaje anterior del mismo tipo
+    #[arg(long)]
+    pub force: bool,
+    /// Muestra qué chunks cambiarían y el tamaño final, sin escribir nada
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Con --in-place, guarda antes una copia del original con este sufijo (por defecto .bak)
+    #[arg(
+        long,
+        value_name = "SUFIJO",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = ".bak",
+        requires = "in_place",
+        conflicts_with = "output"
+    )]
+    pub backup: Option<String>,
+}
+
+// Para los comandos que procesan muchos archivos a la vez
+#[derive(Debug, Args)]
+pub struct BatchArgs {
+    /// Recorre los directorios y procesa todos los .png que encuentre
+    #[arg(short, long)]
+    pub recursive: bool,
+    /// Cantidad de hilos; por defecto uno por núcleo
+    #[arg(short, long, value_name = "N")]
+    pub jobs: Option<usize>,
+    /// Deja afuera los archivos que coinciden con este patrón (se puede repetir)
+    #[arg(long, value_name = "PATRON", value_parser = parse_pattern)]
+    pub exclude: Vec<glob::Pattern>,
+}
+
+// Los argumentos que esperan un solo archivo también aceptan un patrón, siempre que coincida con uno solo
+fn parse_input(value: &str) -> Result<PathBuf, String> {
+    crate::batch::single(Path::new(value))
+}
+
+fn parse_pattern(value: &str) -> Result<glob::Pattern, String> {
+    glob::Pattern::new(value).map_err(|err| tr!("patrón inválido {}: {}", "invalid pattern {}: {}", value, err))
+}
+
+#[derive(Debug, Args)]
+pub struct ScanArgs {
+    /// Archivos, directorios o patrones glob como 'renders/**/*.png'
+    #[arg(required = true)]
+    pub files: Vec<PathBuf>,
+    #[command(flatten)]
+    pub batch: BatchArgs,
+    /// Largo a partir del cual un chunk de texto se marca como anormal
+    #[arg(long, value_name = "BYTES", default_value_t = pngme::scan::DEFAULT_MAX_TEXT)]
+    pub max_text: usize,
+    /// Salida en JSON, con un resumen por tipo de hallazgo
+    #[cfg(feature = "serde")]
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[cfg(feature = "watch")]
+#[derive(Debug, Args)]
+pub struct WatchArgs {
+    /// Directorio a vigilar
+    pub dir: PathBuf,
+    /// Comando de pngme a aplicar, como "encode {} ruSt -m meta.json"; {} es el archivo y,
+    /// si no aparece, el archivo va justo después del nombre del comando
+    #[arg(long, value_name = "COMANDO")]
+    pub on_new: String,
+    /// Vigila también los subdirectorios
+    #[arg(short, long)]
+    pub recursive: bool,
+}
+
+fn parse_percent(value: &str) -> Result<f64, String> {
+    let invalid = |_| tr!("porcentaje inválido: {}", "invalid percentage: {}", value);
+    let percent: f64 = value.trim_end_matches('%').parse().map_err(invalid)?;
+    if !percent.is_finite() || percent < 0.0 {
+        return Err(tr!("el porcentaje tiene que ser positivo: {}", "the percentage must be positive: {}", value));
+    }
+    Ok(percent)
+}
+
+#[derive(Debug, Args)]
+pub struct VerifyArgs {
+    /// Archivos, directorios o patrones glob como 'renders/**/*.png'
+    #[arg(required = true)]
+    pub files: Vec<PathBuf>,
+    #[command(flatten)]
+    pub batch: BatchArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct LintArgs {
+    /// Archivos, directorios o patrones glob como 'renders/**/*.png'
+    #[arg(required = true)]
+    pub files: Vec<PathBuf>,
+    #[command(flatten)]
+    pub batch: BatchArgs,
+    /// Códigos de reglas a ignorar, separados por comas (ej. P011,P014)
+    #[arg(long, value_delimiter = ',', value_parser = parse_rule)]
+    pub allow: Vec<Rule>,
+    /// Archivo con un código por línea; por defecto .pngmelint si existe
+    #[arg(long, value_parser = parse_input)]
+    pub allow_file: Option<PathBuf>,
+}
+
+pub fn parse_rule(code: &str) -> Result<Rule, String> {
+    Rule::from_code(code.trim()).ok_or_else(|| tr!("código de regla desconocido: {}", "unknown rule code: {}", code))
+}
+
+#[derive(Debug, Args)]
+pub struct FixOrderArgs {
+    /// Archivos, directorios o patrones glob como 'renders/**/*.png'
+    #[arg(required = true)]
+    pub files: Vec<PathBuf>,
+    #[command(flatten)]
+    pub batch: BatchArgs,
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct StripArgs {
+    /// Archivos, directorios o patrones glob como 'renders/**/*_final.png'
+    #[arg(required = true)]
+    pub files: Vec<PathBuf>,
+    #[command(flatten)]
+    pub batch: BatchArgs,
+    /// Tipos a borrar, separados por comas; por defecto tEXt,zTXt,iTXt,tIME,eXIf
+    #[arg(long, value_delimiter = ',', value_parser = ChunkType::from_str, conflicts_with_all = ["all", "preset"])]
+    pub types: Vec<ChunkType>,
+    /// Borra todos los chunks auxiliares
+    #[arg(long, conflicts_with = "preset")]
+    pub all: bool,
+    /// Conjunto predefinido: social, archive o minimal
+    #[arg(long, value_parser = parse_preset)]
+    pub preset: Option<StripPreset>,
+    /// Tipos que se conservan siempre, separados por comas
+    #[arg(long, value_delimiter = ',', value_parser = ChunkType::from_str)]
+    pub keep: Vec<ChunkType>,
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+pub fn parse_preset(name: &str) -> Result<StripPreset, String> {
+    let unknown = || tr!(
+        "preset desconocido: {} (social, archive o minimal)",
+        "unknown preset: {} (social, archive or minimal)",
+        name
+    );
+    StripPreset::from_name(name).ok_or_else(unknown)
+}
+
+#[derive(Debug, Args)]
+pub struct ShellArgs {
+    /// PNG de entrada; - para leerlo de stdin
+    #[arg(value_parser = parse_input)]
+    pub file_path: PathBuf,
+}
+
+#[cfg(feature = "tui")]
+#[derive(Debug, Args)]
+pub struct TuiArgs {
+    #[arg(value_parser = parse_input)]
+    pub file_path: PathBuf,
+    /// Dónde guarda `s`; por defecto el mismo archivo
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct DiffArgs {
+    /// PNG original
+    #[arg(value_parser = parse_input)]
+    pub old: PathBuf,
+    /// PNG modificado
+    #[arg(value_parser = parse_input)]
+    pub new: PathBuf,
+    /// Salida en JSON
+    #[cfg(feature = "serde")]
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct CopyChunksArgs {
+    /// PNG del que se copian los chunks
+    #[arg(value_parser = parse_input)]
+    pub source: PathBuf,
+    /// PNG al que se copian
+    #[arg(value_parser = parse_input)]
+    pub target: PathBuf,
+    /// Tipos a copiar, separados por comas; por defecto todos los auxiliares
+    #[arg(long, value_delimiter = ',', value_parser = ChunkType::from_str)]
+    pub types: Vec<ChunkType>,
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct HashArgs {
+    /// Archivos, directorios o patrones glob como 'renders/**/*.png'
+    #[arg(required = true)]
+    pub files: Vec<PathBuf>,
+    #[command(flatten)]
+    pub batch: BatchArgs,
+    /// Ignora los chunks auxiliares y cómo están divididos los IDAT
+    #[arg(long)]
+    pub content: bool,
+}
+
+#[cfg(feature = "crypto")]
+#[derive(Debug, Args)]
+pub struct SignArgs {
+    /// PNG de entrada; - para leerlo de stdin
+    #[arg(value_parser = parse_input)]
+    pub file_path: PathBuf,
+    /// Clave privada Ed25519 en PEM (PKCS#8), o la semilla de 32 bytes en crudo o base64
+    #[arg(long, visible_alias = "keyfile", value_parser = parse_input)]
+    pub key: PathBuf,
+    /// Tipos a cubrir, separados por comas; por defecto todos los chunks
+    #[arg(long, value_delimiter = ',', value_parser = ChunkType::from_str)]
+    pub types: Vec<ChunkType>,
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[cfg(feature = "crypto")]
+#[derive(Debug, Args)]
+pub struct VerifySigArgs {
+    /// PNG de entrada; - para leerlo de stdin
+    #[arg(value_parser = parse_input)]
+    pub file_path: PathBuf,
+    /// Clave pública Ed25519 en PEM, o sus 32 bytes en crudo o base64
+    #[arg(long, value_parser = parse_input)]
+    pub pubkey: PathBuf,
+}
+
+#[cfg(feature = "keyring")]
+#[derive(Debug, Args)]
+#[command(group(clap::ArgGroup::new("value").args(["password", "keyfile"])))]
+pub struct KeyringSetArgs {
+    /// Nombre con el que se guarda, para usarlo luego con --key-id
+    pub id: String,
+    /// Contraseña a guardar; sin ésta ni --keyfile se pide por la terminal
+    #[arg(long)]
+    pub password: Option<String>,
+    /// Archivo con la clave a guardar (en crudo o base64)
+    #[arg(long, value_name = "ARCHIVO", value_parser = parse_input)]
+    pub keyfile: Option<PathBuf>,
+}
+
+#[cfg(feature = "keyring")]
+#[derive(Debug, Args)]
+pub struct KeyringDeleteArgs {
+    /// Nombre con el que se guardó
+    pub id: String,
+}
+
+#[derive(Debug, Args)]
+pub struct RepairArgs {
+    /// PNG de entrada; - para leerlo de stdin
+    #[arg(value_parser = parse_input)]
+    pub file_path: PathBuf,
+    /// Archivo de salida; por defecto <archivo>.repaired.png, o stdout si el PNG viene por stdin
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+    /// Pisa el archivo de salida si ya existe
+    #[arg(long)]
+    pub force: bool,
+    /// Agrega IEND al final si falta
+    #[arg(long)]
+    pub add_iend: bool,
+    /// Muestra qué se repararía y el tamaño final, sin escribir nada
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct RecoverArgs {
+    /// PNG de entrada; - para leerlo de stdin
+    #[arg(value_parser = parse_input)]
+    pub file_path: PathBuf,
+    /// Archivo de salida; por defecto <archivo>.recovered.png, o stdout si el PNG viene por stdin
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+    /// Pisa el archivo de salida si ya existe
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Debug, Args)]
+pub struct ExportArgs {
+    /// PNG de entrada; - para leerlo de stdin
+    #[arg(value_parser = parse_input)]
+    pub file_path: PathBuf,
+    /// Archivo JSON de salida; si no se indica se escribe en stdout
+    #[arg(long = "json")]
+    pub json_path: Option<PathBuf>,
+    /// Pisa el archivo JSON si ya existe
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Debug, Args)]
+pub struct ImportArgs {
+    /// JSON guardado con export
+    #[arg(value_parser = parse_input)]
+    pub json_path: PathBuf,
+    /// PNG de salida
+    #[arg(long = "out")]
+    pub output_file: PathBuf,
+    /// Pisa el PNG de salida si ya existe
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Cada comando y cada argumento tiene que tener su ayuda en inglés; si no, --lang en la muestra en castellano
+    fn missing_english(path: &str, command: &Command, missing: &mut Vec<String>) {
+        if !path.is_empty() && !ENGLISH_COMMANDS.iter().any(|(name, _)| *name == path) {
+            missing.push(format!("comando {}", path));
+        }
+        for arg in command.get_arguments() {
+            let id = arg.get_id().as_str();
+            let found = match path {
+                "" => ENGLISH_GLOBALS.iter().any(|(global, _, _)| *global == id),
+                _ => ENGLISH_ARGS
+                    .iter()
+                    .any(|(name, arg_id, _, _)| (*name == path || name.is_empty()) && *arg_id == id),
+            };
+            if !found && id != "help" && id != "version" {
+                missing.push(format!("argumento {} de {}", id, if path.is_empty() { "pngme" } else { path }));
+            }
+        }
+        for sub in command.get_subcommands() {
+            let nested = match path {
+                "" => sub.get_name().to_string(),
+                _ => format!("{} {}", path, sub.get_name()),
+            };
+            missing_english(&nested, sub, missing);
+        }
+    }
+
+    #[test]
+    fn test_english_help_is_complete() {
+        let mut missing = Vec::new();
+        missing_english("", &Cli::command(), &mut missing);
+        assert!(missing.is_empty(), "falta la ayuda en inglés de: {}", missing.join(", "));
+    }
+}
@@ -0,0 +1,188 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use glob::{glob, Pattern};
+use rayon::prelude::*;
+use pngme::{tr, Result};
+use crate::progress;
+
+// Archivos a procesar: los que se pasaron tal cual, los que coinciden con cada patrón glob y, con
+// --recursive, los .png de cada directorio; sin los que coinciden con algún patrón de `exclude`.
+// Los patrones se expanden acá y no en la shell, así funcionan igual en Windows
+pub fn expand(paths: &[PathBuf], recursive: bool, exclude: &[Pattern]) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for path in paths {
+        if is_pattern(path) {
+            files.extend(matches(path).map_err(invalid_input)?);
+        } else if !path.is_dir() {
+            files.push(path.clone());
+        } else if recursive {
+            walk(path, &mut files)?;
+        } else {
+            return Err(invalid_input(tr!(
+                "{} es un directorio; usá --recursive",
+                "{} is a directory; use --recursive",
+                path.display()
+            )));
+        }
+    }
+    files.retain(|file| !is_excluded(file, exclude));
+    Ok(files)
+}
+
+// Para los argumentos de un solo archivo: un patrón tiene que coincidir con exactamente uno. Lo que no es un
+// patrón, como - para stdin, queda igual
+pub fn single(path: &Path) -> std::result::Result<PathBuf, String> {
+    if !is_pattern(path) {
+        return Ok(path.to_path_buf());
+    }
+    let mut files = matches(path)?;
+    if files.len() > 1 {
+        return Err(tr!(
+            "{} coincide con {} archivos y acá va uno solo",
+            "{} matches {} files but only one is expected here",
+            path.display(),
+            files.len()
+        ));
+    }
+    Ok(files.remove(0))
+}
+
+// Para las listas de archivos que no son lotes, como las de --spread, --gather o pack: cada patrón se
+// reemplaza por los archivos que coinciden, en orden, y el resto queda igual
+pub fn patterns(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for path in paths {
+        match is_pattern(path) {
+            true => files.extend(matches(path).map_err(invalid_input)?),
+            false => files.push(path.clone()),
+        }
+    }
+    Ok(files)
+}
+
+// Los archivos que coinciden con un patrón, en orden alfabético; que no coincida ninguno es un error
+fn matches(path: &Path) -> std::result::Result<Vec<PathBuf>, String> {
+    let pattern = path.to_string_lossy();
+    let entries = glob(&pattern).map_err(|err| tr!("patrón inválido {}: {}", "invalid pattern {}: {}", pattern, err))?;
+    let mut files = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|err| err.to_string())?;
+        if entry.is_file() {
+            files.push(entry);
+        }
+    }
+    if files.is_empty() {
+        return Err(tr!("ningún archivo coincide con {}", "no file matches {}", pattern));
+    }
+    Ok(files)
+}
+
+// Un camino que no existe y tiene comodines se toma como patrón
+fn is_pattern(path: &Path) -> bool {
+    !path.exists() && path.to_string_lossy().contains(['*', '?', '['])
+}
+
+// Un patrón de --exclude puede coincidir con el camino entero o sólo con el nombre del archivo
+fn is_excluded(path: &Path, exclude: &[Pattern]) -> bool {
+    let name = path.file_name().map(Path::new);
+    exclude.iter().any(|pattern| pattern.matches_path(path) || name.is_some_and(|name| pattern.matches_path(name)))
+}
+
+fn invalid_input(message: String) -> pngme::PngmeError {
+    std::io::Error::new(std::io::ErrorKind::InvalidInput, message).into()
+}
+
+fn walk(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let mut entries = fs::read_dir(dir)?.collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            walk(&path, files)?;
+        } else if path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("png")) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+// Procesa los archivos en paralelo con `jobs` hilos (por defecto uno por núcleo); los resultados
+// quedan en el mismo orden que los archivos
+pub fn run<T, F>(files: &[PathBuf], jobs: Option<usize>, f: F) -> Result<Vec<(PathBuf, Result<T>)>>
+where
+    T: Send,
+    F: Fn(&Path) -> Result<T> + Sync,
+{
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs.unwrap_or(0))
+        .build()
+        .map_err(|err| std::io::Error::other(err.to_string()))?;
+    let bar = progress::files(files.len());
+    let results = pool.install(|| {
+        files
+            .par_iter()
+            .map(|path| {
+                let _span = tracing::info_span!("file", path = %path.display()).entered();
+                let result = f(path);
+                if let Err(err) = &result {
+                    tracing::debug!(error = %err, "falló");
+                }
+                bar.inc(1);
+                (path.clone(), result)
+            })
+            .collect()
+    });
+    bar.finish_and_clear();
+    Ok(results)
+}
+
+// run y partition juntos. Con un solo archivo su error sale tal cual, con el código de salida que le
+// corresponde, igual que en los comandos que no procesan lotes
+pub fn run_each<T, F>(files: &[PathBuf], jobs: Option<usize>, f: F) -> Result<(Vec<(PathBuf, T)>, usize)>
+where
+    T: Send,
+    F: Fn(&Path) -> Result<T> + Sync,
+{
+    if let [path] = files {
+        return Ok((vec![(path.clone(), f(path)?)], 0));
+    }
+    Ok(partition(run(files, jobs, f)?))
+}
+
+// Con más de un archivo, la salida de cada uno va debajo de su nombre, separada de la anterior
+pub fn header(path: &Path, index: usize, many: bool) {
+    if many {
+        if index > 0 {
+            println!();
+        }
+        println!("{}:", path.display());
+    }
+}
+
+// Separa los archivos que fallaron: muestra su error y devuelve cuántos fueron
+pub fn partition<T>(results: Vec<(PathBuf, Result<T>)>) -> (Vec<(PathBuf, T)>, usize) {
+    let mut ok = Vec::new();
+    let mut failed = 0;
+    for (path, result) in results {
+        match result {
+            Ok(value) => ok.push((path, value)),
+            Err(err) => {
+                eprintln!("Error: {}: {}", path.display(), err);
+                failed += 1;
+            }
+        }
+    }
+    (ok, failed)
+}
+
+// Un archivo que falla no corta el resto, pero el comando termina con error
+pub fn finish(failed: usize) -> Result<()> {
+    if failed > 0 {
+        return Err(std::io::Error::other(tr!(
+            "{} archivos no se pudieron procesar",
+            "{} files could not be processed",
+            failed
+        )).into());
+    }
+    Ok(())
+}
@@ -1,24 +1,75 @@
-#![allow(unused_variables)]
-use std::{fmt::Display, io::Read};
-use crc::{Crc, CRC_32_ISO_HDLC};
+use std::{fmt::Display, io::{Read, Write}};
+use crate::chunk_ref::ChunkRef;
 use crate::chunk_type::ChunkType;
-use crate::{Error, Result};
+use crate::error::{ChunkField, Limit};
+use crate::hexdump::write_hexdump;
+use crate::locale::text;
+use crate::{tr, PngmeError, Result};
+
+// CRC de un chunk armado por partes, para no juntar tipo y datos en un Vec sólo para calcularlo:
+// CrcHasher::new().update(&tipo).update(&datos).finalize(). Es el CRC-32 de zlib; crc32fast elige en
+// tiempo de ejecución la versión con SIMD (PCLMULQDQ o las instrucciones CRC de ARM) si la CPU la tiene
+#[derive(Clone)]
+pub struct CrcHasher {
+    hasher: crc32fast::Hasher,
+}
+
+impl CrcHasher {
+    pub fn new() -> CrcHasher {
+        CrcHasher { hasher: crc32fast::Hasher::new() }
+    }
+
+    pub fn update(mut self, bytes: impl AsRef<[u8]>) -> CrcHasher {
+        self.hasher.update(bytes.as_ref());
+        self
+    }
 
-#[derive(Debug)]
-enum ChunkError {
-    ConversionError,
-    UnreadableByte,
+    pub fn finalize(self) -> u32 {
+        self.hasher.finalize()
+    }
 }
 
-// implementar esto
-impl std::error::Error for ChunkError{}
+impl Default for CrcHasher {
+    fn default() -> CrcHasher {
+        CrcHasher::new()
+    }
+}
 
-impl Display for ChunkError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ChunkError::UnreadableByte => write!(f, "Error leyendo los bytes del contenido"),
-            ChunkError::ConversionError => write!(f, "Error haciendo la conversión de un array de bytes al chunk"),
+// Límites al parsear archivos que no son de confianza, para que un length absurdo no haga reservar
+// gigas. Los valores por defecto alcanzan para cualquier imagen razonable
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+    // datos de un solo chunk
+    pub max_chunk_len: u32,
+    // el archivo entero, firma incluida
+    pub max_total_len: usize,
+}
+
+impl ParseOptions {
+    // Sin más límite que el de la especificación
+    pub const UNLIMITED: ParseOptions = ParseOptions { max_chunk_len: Chunk::MAX_LENGTH, max_total_len: usize::MAX };
+
+    // Falla si el chunk que empieza en `offset` y declara `length` bytes de datos supera algún límite
+    fn check(&self, offset: usize, length: u32) -> Result<()> {
+        if length > self.max_chunk_len {
+            return Err(PngmeError::OverLimit {
+                limit: Limit::ChunkLen,
+                offset,
+                length: length as usize,
+                max: self.max_chunk_len as usize,
+            });
+        }
+        let end = offset.saturating_add(12 + length as usize);
+        if end > self.max_total_len {
+            return Err(PngmeError::OverLimit { limit: Limit::TotalLen, offset, length: end, max: self.max_total_len });
         }
+        Ok(())
+    }
+}
+
+impl Default for ParseOptions {
+    fn default() -> ParseOptions {
+        ParseOptions { max_chunk_len: 256 << 20, max_total_len: 1 << 30 }
     }
 }
 
@@ -30,19 +81,49 @@ pub struct Chunk {
 }
 
 impl Chunk {
-    const CRC: Crc<u32> = Crc::<u32>::new(&CRC_32_ISO_HDLC);
+    // Bytes de datos que muestra Display si no se indica una precisión ({:.N})
+    pub const DEFAULT_PREVIEW_BYTES: usize = 64;
+    // Máximo valor de length permitido por la especificación
+    pub const MAX_LENGTH: u32 = (1 << 31) - 1;
+
+    // El CRC se calcula sobre el tipo y los datos por separado: los datos no se copian. Falla si los
+    // datos pasan de MAX_LENGTH, porque ese chunk no se podría escribir ni volver a leer
+    pub fn new(chunk_type: ChunkType, chunk_data: Vec<u8>) -> Result<Chunk> {
+        let length = Chunk::checked_length(&chunk_type, &chunk_data)?;
+        Ok(Chunk::from_parts(chunk_type, chunk_data, length))
+    }
+
+    // Para datos que salieron de un chunk ya leído o armado: no pueden pasar de MAX_LENGTH
+    pub(crate) fn from_parsed(chunk_type: ChunkType, chunk_data: Vec<u8>) -> Chunk {
+        let length = chunk_data.len() as u32;
+        Chunk::from_parts(chunk_type, chunk_data, length)
+    }
 
-    pub fn new(chunk_type: ChunkType, chunk_data: Vec<u8>) -> Chunk {
-        let length: u32 = chunk_data.bytes().count().try_into().unwrap();
-        let crc_sum = Chunk::get_checksum(chunk_data.clone(), chunk_type.bytes());
+    fn from_parts(chunk_type: ChunkType, chunk_data: Vec<u8>, length: u32) -> Chunk {
+        let crc = Chunk::checksum(&chunk_type, &chunk_data);
         Chunk {
             chunk_type,
             chunk_data,
             length,
-            crc: crc_sum,
+            crc,
         }
     }
 
+    fn checked_length(chunk_type: &ChunkType, chunk_data: &[u8]) -> Result<u32> {
+        match u32::try_from(chunk_data.len()) {
+            Ok(length) if length <= Chunk::MAX_LENGTH => Ok(length),
+            _ => Err(PngmeError::ChunkTooLong { chunk_type: chunk_type.to_string(), length: chunk_data.len() }),
+        }
+    }
+
+    // Cambia los datos y recalcula length y CRC; devuelve los datos anteriores. Falla, sin cambiar nada,
+    // si los datos pasan de MAX_LENGTH
+    pub fn set_data(&mut self, chunk_data: Vec<u8>) -> Result<Vec<u8>> {
+        self.length = Chunk::checked_length(&self.chunk_type, &chunk_data)?;
+        self.crc = Chunk::checksum(&self.chunk_type, &chunk_data);
+        Ok(std::mem::replace(&mut self.chunk_data, chunk_data))
+    }
+
     pub fn length(&self) -> u32 {
         self.length
     }
@@ -59,20 +140,18 @@ impl Chunk {
         self.crc
     }
 
+    pub fn data_as_str(&self) -> Result<&str> {
+        std::str::from_utf8(self.data())
+            .map_err(|err| PngmeError::InvalidUtf8 { index: err.valid_up_to() })
+    }
+
     pub fn data_as_string(&self) -> Result<String> {
-        let data = self.data().bytes();
-        let mut string = String::new();
-        for byte in data {
-            let byte = match byte {
-                Ok(val) => val,
-                Err(_) => {
-                    let err = ChunkError::UnreadableByte.into();
-                    return Err(err)
-                },
-            };
-            string.push(byte as char);            
-        }
-        return Ok(string)
+        self.data_as_str().map(str::to_owned)
+    }
+
+    // Reemplaza las secuencias inválidas por U+FFFD en lugar de fallar
+    pub fn data_as_string_lossy(&self) -> String {
+        String::from_utf8_lossy(self.data()).into_owned()
     }
 
     pub fn as_bytes(&self) -> Vec<u8> {
@@ -85,36 +164,167 @@ impl Chunk {
            .chain(self.chunk_data.iter().cloned())
            .chain(self.crc.to_be_bytes())
            .collect();
-        return byte_vec
+        byte_vec
     }
 
-    fn get_checksum(mut chunk_data: Vec<u8>, chunk_type_code: [u8; 4]) -> u32 {
-        chunk_data.extend_from_slice(&chunk_type_code);
-        let chunk_data = &chunk_data[..];
-        let sum = Chunk::CRC.checksum(chunk_data);
-        sum
+    // Escribe el chunk directamente en el writer sin armar un Vec intermedio
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(&self.length.to_be_bytes())?;
+        writer.write_all(&self.chunk_type.bytes())?;
+        writer.write_all(&self.chunk_data)?;
+        writer.write_all(&self.crc.to_be_bytes())?;
+        Ok(())
+    }
+
+    // CRC del tipo seguido de los datos, sin copiar nada
+    pub fn checksum(chunk_type: &ChunkType, data: &[u8]) -> u32 {
+        CrcHasher::new().update(chunk_type.bytes()).update(data).finalize()
+    }
+
+    // Parsea un chunk que empieza en `base` dentro del archivo, para que los errores
+    // reporten offsets absolutos
+    pub(crate) fn from_bytes_at(value: &[u8], base: usize, options: &ParseOptions) -> Result<Chunk> {
+        let chunk_ref = ChunkRef::parse_at(value, base)?;
+        // antes de copiar los datos
+        options.check(base, chunk_ref.length())?;
+        let chunk = chunk_ref.to_chunk();
+        if chunk.crc != chunk_ref.crc() {
+            return Err(PngmeError::BadCrc {
+                offset: chunk_ref.crc_offset(base),
+                expected: chunk.crc,
+                actual: chunk_ref.crc(),
+            });
+        }
+        Ok(chunk)
+    }
+
+    // Como TryFrom<&[u8]>, con otros límites
+    pub fn parse_with(value: &[u8], options: &ParseOptions) -> Result<Chunk> {
+        Chunk::from_bytes_at(value, 0, options)
+    }
+
+    pub fn from_reader<R: Read>(reader: &mut R) -> Result<Chunk> {
+        Chunk::from_reader_with(reader, &ParseOptions::default())
+    }
+
+    pub fn from_reader_with<R: Read>(reader: &mut R, options: &ParseOptions) -> Result<Chunk> {
+        match Chunk::from_reader_at(reader, 0, options)? {
+            Some(chunk) => Ok(chunk),
+            None => Err(PngmeError::UnexpectedEof { field: ChunkField::Length, offset: 0, needed: 4, available: 0 }),
+        }
+    }
+
+    // Lee un chunk del stream sin cargar nada más que su contenido. Devuelve None si el
+    // stream termina justo antes del chunk
+    pub(crate) fn from_reader_at<R: Read>(reader: &mut R, base: usize, options: &ParseOptions) -> Result<Option<Chunk>> {
+        let mut read_field = |field: ChunkField, start: usize, buf: &mut [u8]| -> Result<usize> {
+            let available = read_full(reader, buf)?;
+            if available > 0 && available < buf.len() {
+                return Err(PngmeError::UnexpectedEof { field, offset: base + start, needed: buf.len(), available });
+            }
+            Ok(available)
+        };
+
+        let mut length = [0; 4];
+        if read_field(ChunkField::Length, 0, &mut length)? == 0 {
+            return Ok(None);
+        }
+        let length = u32::from_be_bytes(length);
+        if length > Chunk::MAX_LENGTH {
+            return Err(PngmeError::LengthOutOfRange { offset: base, length });
+        }
+        options.check(base, length)?;
+
+        let mut code = [0; 4];
+        if read_field(ChunkField::Type, 4, &mut code)? == 0 {
+            return Err(PngmeError::UnexpectedEof { field: ChunkField::Type, offset: base + 4, needed: 4, available: 0 });
+        }
+        let chunk_type = ChunkType::try_from(code)
+            .map_err(|_| PngmeError::InvalidChunkType { offset: Some(base + 4), bytes: code })?;
+
+        // se lee con take para no reservar de antemano un length que el stream no tiene
+        let mut data = Vec::new();
+        reader.take(length as u64).read_to_end(&mut data)?;
+        if data.len() < length as usize {
+            return Err(PngmeError::UnexpectedEof {
+                field: ChunkField::Data,
+                offset: base + 8,
+                needed: length as usize,
+                available: data.len(),
+            });
+        }
+
+        let crc_start = 8 + length as usize;
+        let mut crc = [0; 4];
+        let available = read_full(reader, &mut crc)?;
+        if available < 4 {
+            return Err(PngmeError::UnexpectedEof { field: ChunkField::Crc, offset: base + crc_start, needed: 4, available });
+        }
+        let crc = u32::from_be_bytes(crc);
+
+        let chunk = Chunk::from_parsed(chunk_type, data);
+        if chunk.crc != crc {
+            return Err(PngmeError::BadCrc { offset: base + crc_start, expected: chunk.crc, actual: crc });
+        }
+        Ok(Some(chunk))
     }
 }
 
+// Lee hasta llenar `buf` o llegar al EOF, devolviendo cuántos bytes se leyeron
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<usize> {
+    let mut read = 0;
+    while read < buf.len() {
+        match reader.read(&mut buf[read..]) {
+            Ok(0) => break,
+            Ok(n) => read += n,
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err.into()),
+        }
+    }
+    Ok(read)
+}
+
 // Length (4 bytes, u32) -> ChunkCode (4 bytes) -> ChunkData (N bytes, Vec<u8>) -> CRC (4 bytes, u32)
 impl TryFrom<&[u8]> for Chunk {
-    type Error = ChunkError;
+    type Error = PngmeError;
     fn try_from(value: &[u8]) -> Result<Chunk> {
-        let value = value.iter();
-        let err = ChunkError::ConversionError;
-        let len: u32 = u32::from_ne_bytes(
-            value.
-        );
-        
+        Chunk::parse_with(value, &ParseOptions::default())
     }
 }
 
+// La precisión del formato limita la vista previa: `{:.16}` muestra sólo 16 bytes
 impl Display for Chunk {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ct = &self.chunk_type;
+        writeln!(f, "{}  length={}  crc={:08x}", ct, self.length, self.crc)?;
+        writeln!(
+            f,
+            "  [{}, {}, {}, {}]",
+            if ct.is_critical() { text("crítico", "critical") } else { text("auxiliar", "ancillary") },
+            if ct.is_public() { text("público", "public") } else { text("privado", "private") },
+            if ct.is_reserved_bit_valid() {
+                text("reservado válido", "valid reserved")
+            } else {
+                text("reservado inválido", "invalid reserved")
+            },
+            if ct.is_safe_to_copy() {
+                text("copia segura", "safe to copy")
+            } else {
+                text("copia insegura", "unsafe to copy")
+            },
+        )?;
+
+        let max = f.precision().unwrap_or(Chunk::DEFAULT_PREVIEW_BYTES);
+        let shown = self.chunk_data.len().min(max);
+        write_hexdump(f, &self.chunk_data[..shown], 0, "  ")?;
+        if shown < self.chunk_data.len() {
+            let more = self.chunk_data.len() - shown;
+            writeln!(f, "{}", tr!("  ... ({} bytes más)", "  ... ({} more bytes)", more))?;
+        }
+        Ok(())
     }
 }
 
-fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -142,11 +352,22 @@ mod tests {
     fn test_new_chunk() {
         let chunk_type = ChunkType::from_str("RuSt").unwrap();
         let data = "This is where your secret message will be!".as_bytes().to_vec();
-        let chunk = Chunk::new(chunk_type, data);
+        let chunk = Chunk::new(chunk_type, data).unwrap();
         assert_eq!(chunk.length(), 42);
         assert_eq!(chunk.crc(), 2882656334);
     }
 
+    #[test]
+    fn test_set_data() {
+        let mut chunk = testing_chunk();
+        let old = chunk.set_data(b"otro mensaje".to_vec()).unwrap();
+        assert_eq!(old, b"This is where your secret message will be!");
+        let expected = Chunk::new(ChunkType::from_str("RuSt").unwrap(), b"otro mensaje".to_vec()).unwrap();
+        assert_eq!(chunk.length(), 12);
+        assert_eq!(chunk.crc(), expected.crc());
+        assert_eq!(chunk.as_bytes(), expected.as_bytes());
+    }
+
     #[test]
     fn test_chunk_length() {
         let chunk = testing_chunk();
@@ -240,8 +461,145 @@ mod tests {
         let chunk: Chunk = TryFrom::try_from(chunk_data.as_ref()).unwrap();
         
         let _chunk_string = format!("{}", chunk);
-        }
+    }
 
+    fn chunk_error(bytes: &[u8]) -> PngmeError {
+        Chunk::try_from(bytes).err().unwrap()
+    }
+
+    #[test]
+    fn test_truncated_chunk_reports_field_and_offset() {
+        let chunk = testing_chunk();
+        let bytes = chunk.as_bytes();
+
+        let err = chunk_error(&bytes[..2]);
+        assert_eq!(err.field(), Some(ChunkField::Length));
+        assert_eq!(err.offset(), Some(0));
+
+        let err = chunk_error(&bytes[..20]);
+        assert_eq!(err.field(), Some(ChunkField::Data));
+        assert_eq!(err.offset(), Some(8));
+
+        let err = chunk_error(&bytes[..bytes.len() - 1]);
+        assert_eq!(err.field(), Some(ChunkField::Crc));
+        assert_eq!(err.offset(), Some(50));
+    }
+
+    #[test]
+    fn test_invalid_type_reports_offset() {
+        let mut bytes = testing_chunk().as_bytes();
+        bytes[5] = b'1';
+        let err = chunk_error(&bytes);
+        assert!(matches!(err, PngmeError::InvalidChunkType { offset: Some(4), bytes } if bytes == *b"R1St"));
+    }
+
+    #[test]
+    fn test_crc_mismatch_reports_values() {
+        let mut bytes = testing_chunk().as_bytes();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        let err = chunk_error(&bytes);
+        assert!(matches!(
+            err,
+            PngmeError::BadCrc { offset: 50, expected: 2882656334, actual } if actual == 2882656334 ^ 1
+        ));
+    }
+
+    #[test]
+    fn test_length_out_of_range() {
+        let mut bytes = testing_chunk().as_bytes();
+        bytes[0] = 0x80;
+        let err = chunk_error(&bytes);
+        assert_eq!(err.field(), Some(ChunkField::Length));
+    }
+
+    #[test]
+    fn test_chunk_from_reader() {
+        let bytes = testing_chunk().as_bytes();
+        let mut reader = std::io::Cursor::new(bytes);
+        let chunk = Chunk::from_reader(&mut reader).unwrap();
+        assert_eq!(chunk.data_as_string().unwrap(), "This is where your secret message will be!");
+        assert_eq!(chunk.crc(), 2882656334);
+    }
+
+    #[test]
+    fn test_chunk_from_truncated_reader() {
+        let bytes = testing_chunk().as_bytes();
+        let mut reader = &bytes[..30];
+        let err = Chunk::from_reader(&mut reader).err().unwrap();
+        assert_eq!(err.field(), Some(ChunkField::Data));
     }
-}
 
+    #[test]
+    fn test_chunk_over_limit() {
+        let bytes = testing_chunk().as_bytes();
+        let options = ParseOptions { max_chunk_len: 41, ..ParseOptions::default() };
+        let err = Chunk::parse_with(&bytes, &options).err().unwrap();
+        assert!(matches!(err, PngmeError::OverLimit { limit: Limit::ChunkLen, length: 42, max: 41, .. }));
+        // el length declarado alcanza: no se espera a leer los datos
+        let huge = [0x7f, 0xff, 0xff, 0xff, b'R', b'u', b'S', b't'];
+        let err = Chunk::from_reader(&mut &huge[..]).err().unwrap();
+        assert!(matches!(err, PngmeError::OverLimit { limit: Limit::ChunkLen, .. }));
+        let options = ParseOptions { max_total_len: 50, ..ParseOptions::default() };
+        let err = Chunk::parse_with(&bytes, &options).err().unwrap();
+        assert!(matches!(err, PngmeError::OverLimit { limit: Limit::TotalLen, length: 54, .. }));
+        assert!(Chunk::parse_with(&bytes, &ParseOptions::UNLIMITED).is_ok());
+    }
+
+    #[test]
+    fn test_chunk_too_long() {
+        // vec! de ceros no toca la memoria hasta que se usa, y el largo se revisa antes del CRC
+        let too_long = || vec![0; Chunk::MAX_LENGTH as usize + 1];
+        let chunk_type = ChunkType::from_str("RuSt").unwrap();
+        assert!(matches!(Chunk::new(chunk_type, too_long()), Err(PngmeError::ChunkTooLong { .. })));
+        let mut chunk = testing_chunk();
+        assert!(matches!(chunk.set_data(too_long()), Err(PngmeError::ChunkTooLong { .. })));
+        assert_eq!(chunk.length(), 42);
+    }
+
+    #[test]
+    fn test_crc_hasher_in_pieces() {
+        let chunk = testing_chunk();
+        let (first, second) = chunk.data().split_at(10);
+        let crc = CrcHasher::new().update(chunk.chunk_type().bytes()).update(first).update(second).finalize();
+        assert_eq!(crc, chunk.crc());
+        assert_eq!(CrcHasher::new().finalize(), 0);
+    }
+
+    #[test]
+    fn test_write_to_matches_as_bytes() {
+        let chunk = testing_chunk();
+        let mut out = Vec::new();
+        chunk.write_to(&mut out).unwrap();
+        assert_eq!(out, chunk.as_bytes());
+    }
+
+    #[test]
+    fn test_data_as_str_utf8() {
+        let chunk_type = ChunkType::from_str("RuSt").unwrap();
+        let chunk = Chunk::new(chunk_type, "mensaje en español: ñandú".as_bytes().to_vec()).unwrap();
+        assert_eq!(chunk.data_as_str().unwrap(), "mensaje en español: ñandú");
+    }
+
+    #[test]
+    fn test_data_as_str_invalid_utf8() {
+        let chunk_type = ChunkType::from_str("RuSt").unwrap();
+        let chunk = Chunk::new(chunk_type, vec![b'h', b'o', 0xff, b'a']).unwrap();
+        let err = chunk.data_as_str().err().unwrap();
+        assert!(matches!(err, PngmeError::InvalidUtf8 { index: 2 }));
+        assert_eq!(chunk.data_as_string_lossy(), "ho\u{fffd}a");
+    }
+
+    #[test]
+    fn test_chunk_display_preview() {
+        let chunk = testing_chunk();
+        let full = format!("{}", chunk);
+        assert!(full.starts_with("RuSt  length=42  crc=abd1d84e"));
+        assert!(full.contains("[crítico, privado, reservado válido, copia segura]"));
+        assert!(!full.contains("bytes más"));
+
+        let short = format!("{:.16}", chunk);
+        assert!(short.contains("|This is where yo|"));
+        assert!(short.contains("... (26 bytes más)"));
+    }
+}
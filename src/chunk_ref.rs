@@ -0,0 +1,181 @@
+use std::fmt::Display;
+use crate::chunk::Chunk;
+use crate::chunk_type::ChunkType;
+use crate::error::ChunkField;
+use crate::{PngmeError, Result};
+
+// Vista prestada de un chunk: no copia los datos, sólo apunta al buffer original
+#[derive(Debug, Clone, Copy)]
+pub struct ChunkRef<'a> {
+    chunk_type: ChunkType,
+    data: &'a [u8],
+    crc: u32,
+}
+
+impl<'a> ChunkRef<'a> {
+    pub fn chunk_type(&self) -> ChunkType {
+        self.chunk_type
+    }
+
+    pub fn length(&self) -> u32 {
+        self.data.len() as u32
+    }
+
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    // CRC guardado en el archivo
+    pub fn crc(&self) -> u32 {
+        self.crc
+    }
+
+    // CRC calculado a partir del tipo y los datos
+    pub fn computed_crc(&self) -> u32 {
+        Chunk::checksum(&self.chunk_type, self.data)
+    }
+
+    pub fn is_crc_valid(&self) -> bool {
+        self.crc == self.computed_crc()
+    }
+
+    // Tamaño total en el archivo: length + tipo + datos + crc
+    pub fn total_len(&self) -> usize {
+        12 + self.data.len()
+    }
+
+    pub fn to_chunk(&self) -> Chunk {
+        Chunk::from_parsed(self.chunk_type, self.data.to_vec())
+    }
+}
+
+impl<'a> From<&'a Chunk> for ChunkRef<'a> {
+    fn from(chunk: &'a Chunk) -> ChunkRef<'a> {
+        ChunkRef {
+            chunk_type: *chunk.chunk_type(),
+            data: chunk.data(),
+            crc: chunk.crc(),
+        }
+    }
+}
+
+impl<'a> ChunkRef<'a> {
+    // Lee la estructura de un chunk que empieza en `base` dentro del archivo sin verificar
+    // el CRC ni tocar los datos; los errores reportan offsets absolutos
+    pub(crate) fn parse_at(value: &'a [u8], base: usize) -> Result<ChunkRef<'a>> {
+        let take = |field: ChunkField, start: usize, needed: usize| -> Result<&'a [u8]> {
+            let available = value.len().saturating_sub(start);
+            if available < needed {
+                return Err(PngmeError::UnexpectedEof { field, offset: base + start, needed, available });
+            }
+            Ok(&value[start..start + needed])
+        };
+        let be_u32 = |bytes: &[u8]| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+
+        let length = be_u32(take(ChunkField::Length, 0, 4)?);
+        if length > Chunk::MAX_LENGTH {
+            return Err(PngmeError::LengthOutOfRange { offset: base, length });
+        }
+
+        let code = take(ChunkField::Type, 4, 4)?;
+        let code = [code[0], code[1], code[2], code[3]];
+        let chunk_type = ChunkType::try_from(code)
+            .map_err(|_| PngmeError::InvalidChunkType { offset: Some(base + 4), bytes: code })?;
+
+        let data = take(ChunkField::Data, 8, length as usize)?;
+        let crc = be_u32(take(ChunkField::Crc, 8 + length as usize, 4)?);
+
+        Ok(ChunkRef { chunk_type, data, crc })
+    }
+
+    // Offset absoluto del CRC de un chunk parseado en `base`
+    pub(crate) fn crc_offset(&self, base: usize) -> usize {
+        base + 8 + self.data.len()
+    }
+}
+
+// Lee la estructura del chunk sin verificar el CRC; para eso está is_crc_valid
+impl<'a> TryFrom<&'a [u8]> for ChunkRef<'a> {
+    type Error = PngmeError;
+    fn try_from(value: &'a [u8]) -> Result<ChunkRef<'a>> {
+        ChunkRef::parse_at(value, 0)
+    }
+}
+
+impl Display for ChunkRef<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({} bytes, crc {:08x})", self.chunk_type, self.length(), self.crc)
+    }
+}
+
+// Itera los chunks de un buffer (sin la firma del PNG) prestando los datos
+pub struct ChunkRefs<'a> {
+    rest: &'a [u8],
+}
+
+impl<'a> ChunkRefs<'a> {
+    pub fn new(bytes: &'a [u8]) -> ChunkRefs<'a> {
+        ChunkRefs { rest: bytes }
+    }
+}
+
+impl<'a> Iterator for ChunkRefs<'a> {
+    type Item = Result<ChunkRef<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.is_empty() {
+            return None;
+        }
+        match ChunkRef::try_from(self.rest) {
+            Ok(chunk) => {
+                self.rest = &self.rest[chunk.total_len()..];
+                Some(Ok(chunk))
+            }
+            Err(err) => {
+                // después de un error no hay forma segura de seguir
+                self.rest = &[];
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn testing_chunk() -> Chunk {
+        let chunk_type = ChunkType::from_str("RuSt").unwrap();
+        Chunk::new(chunk_type, b"This is where your secret message will be!".to_vec()).unwrap()
+    }
+
+    #[test]
+    fn test_chunk_ref_from_bytes() {
+        let bytes = testing_chunk().as_bytes();
+        let chunk = ChunkRef::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(chunk.chunk_type().to_string(), "RuSt");
+        assert_eq!(chunk.length(), 42);
+        assert_eq!(chunk.crc(), 2882656334);
+        assert!(chunk.is_crc_valid());
+        // los datos apuntan al buffer original
+        assert_eq!(chunk.data().as_ptr(), bytes[8..].as_ptr());
+    }
+
+    #[test]
+    fn test_chunk_ref_bad_crc() {
+        let mut bytes = testing_chunk().as_bytes();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        let chunk = ChunkRef::try_from(bytes.as_slice()).unwrap();
+        assert!(!chunk.is_crc_valid());
+    }
+
+    #[test]
+    fn test_chunk_refs_iter() {
+        let mut bytes = testing_chunk().as_bytes();
+        bytes.extend(testing_chunk().as_bytes());
+        let chunks: Vec<_> = ChunkRefs::new(&bytes).collect::<Result<_>>().unwrap();
+        assert_eq!(chunks.len(), 2);
+    }
+}
@@ -1,27 +1,171 @@
 use std::fmt::Display;
 use std::str::FromStr;
-use crate::{Error, Result};
+use crate::locale::text;
+use crate::{PngmeError, Result};
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub struct ChunkType {
     code: [u8; 4],
 }
 
-#[derive(Debug)]
-enum ChunkTypeErrors {
-    IsNotAlphabetic,
+// Dónde puede aparecer un chunk dentro del archivo según la especificación
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Placement {
+    // primer chunk (IHDR)
+    First,
+    // último chunk (IEND)
+    Last,
+    // antes de PLTE y de IDAT
+    BeforePlte,
+    // después de PLTE (si hay) y antes de IDAT
+    BetweenPlteAndIdat,
+    // antes del primer IDAT
+    BeforeIdat,
+    // IDAT: todos seguidos
+    Idat,
+    // después del último IDAT
+    AfterIdat,
+    // en cualquier lugar entre IHDR e IEND
+    Anywhere,
 }
 
-impl std::error::Error for ChunkTypeErrors{}
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ChunkConstraints {
+    pub multiple: bool,
+    pub placement: Placement,
+}
+
+impl ChunkConstraints {
+    const fn new(multiple: bool, placement: Placement) -> ChunkConstraints {
+        ChunkConstraints { multiple, placement }
+    }
+}
 
-impl Display for ChunkTypeErrors {
+// Motivos para no guardar datos propios en un tipo de chunk
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TypeProblem {
+    // estándar o extensión registrada: los lectores esperan su formato
+    Registered,
+    // un lector que no lo conoce rechaza la imagen entera
+    Critical,
+    // la tercera letra en minúscula: los lectores lo tratan como desconocido
+    ReservedBit,
+}
+
+impl Display for TypeProblem {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ChunkTypeErrors::IsNotAlphabetic => write!(f, "El caracter no se encuentra dentro de los rangos ASCII permitidos: 65-90 o 97-122"),
-        }
+        let description = match self {
+            TypeProblem::Registered => text(
+                "es un tipo registrado y los lectores van a esperar su formato",
+                "is a registered type and readers will expect its format",
+            ),
+            TypeProblem::Critical => text(
+                "es crítico: los lectores que no lo conocen rechazan la imagen",
+                "is critical: readers that do not know it reject the image",
+            ),
+            TypeProblem::ReservedBit => text(
+                "tiene el bit reservado inválido: la tercera letra tiene que ser mayúscula",
+                "has an invalid reserved bit: the third letter must be uppercase",
+            ),
+        };
+        write!(f, "{}", description)
     }
 }
 
+// Tipos definidos en la especificación PNG (incluye APNG)
+#[allow(non_upper_case_globals)]
+impl ChunkType {
+    pub const IHDR: ChunkType = ChunkType { code: *b"IHDR" };
+    pub const PLTE: ChunkType = ChunkType { code: *b"PLTE" };
+    pub const IDAT: ChunkType = ChunkType { code: *b"IDAT" };
+    pub const IEND: ChunkType = ChunkType { code: *b"IEND" };
+    pub const cHRM: ChunkType = ChunkType { code: *b"cHRM" };
+    pub const cICP: ChunkType = ChunkType { code: *b"cICP" };
+    pub const gAMA: ChunkType = ChunkType { code: *b"gAMA" };
+    pub const iCCP: ChunkType = ChunkType { code: *b"iCCP" };
+    pub const mDCV: ChunkType = ChunkType { code: *b"mDCV" };
+    pub const cLLI: ChunkType = ChunkType { code: *b"cLLI" };
+    pub const sBIT: ChunkType = ChunkType { code: *b"sBIT" };
+    pub const sRGB: ChunkType = ChunkType { code: *b"sRGB" };
+    pub const bKGD: ChunkType = ChunkType { code: *b"bKGD" };
+    pub const hIST: ChunkType = ChunkType { code: *b"hIST" };
+    pub const tRNS: ChunkType = ChunkType { code: *b"tRNS" };
+    pub const eXIf: ChunkType = ChunkType { code: *b"eXIf" };
+    pub const pHYs: ChunkType = ChunkType { code: *b"pHYs" };
+    pub const sPLT: ChunkType = ChunkType { code: *b"sPLT" };
+    pub const tIME: ChunkType = ChunkType { code: *b"tIME" };
+    pub const iTXt: ChunkType = ChunkType { code: *b"iTXt" };
+    pub const tEXt: ChunkType = ChunkType { code: *b"tEXt" };
+    pub const zTXt: ChunkType = ChunkType { code: *b"zTXt" };
+    pub const acTL: ChunkType = ChunkType { code: *b"acTL" };
+    pub const fcTL: ChunkType = ChunkType { code: *b"fcTL" };
+    pub const fdAT: ChunkType = ChunkType { code: *b"fdAT" };
+    pub const oFFs: ChunkType = ChunkType { code: *b"oFFs" };
+    pub const pCAL: ChunkType = ChunkType { code: *b"pCAL" };
+    pub const sCAL: ChunkType = ChunkType { code: *b"sCAL" };
+    pub const gIFg: ChunkType = ChunkType { code: *b"gIFg" };
+    pub const gIFt: ChunkType = ChunkType { code: *b"gIFt" };
+    pub const gIFx: ChunkType = ChunkType { code: *b"gIFx" };
+    pub const sTER: ChunkType = ChunkType { code: *b"sTER" };
+    pub const dSIG: ChunkType = ChunkType { code: *b"dSIG" };
+    pub const fRAc: ChunkType = ChunkType { code: *b"fRAc" };
+
+    pub const STANDARD: [ChunkType; 25] = [
+        ChunkType::IHDR,
+        ChunkType::PLTE,
+        ChunkType::IDAT,
+        ChunkType::IEND,
+        ChunkType::cHRM,
+        ChunkType::cICP,
+        ChunkType::gAMA,
+        ChunkType::iCCP,
+        ChunkType::mDCV,
+        ChunkType::cLLI,
+        ChunkType::sBIT,
+        ChunkType::sRGB,
+        ChunkType::bKGD,
+        ChunkType::hIST,
+        ChunkType::tRNS,
+        ChunkType::eXIf,
+        ChunkType::pHYs,
+        ChunkType::sPLT,
+        ChunkType::tIME,
+        ChunkType::iTXt,
+        ChunkType::tEXt,
+        ChunkType::zTXt,
+        ChunkType::acTL,
+        ChunkType::fcTL,
+        ChunkType::fdAT,
+    ];
+
+    // Extensiones públicas registradas fuera de la especificación principal
+    pub const REGISTERED_EXTENSIONS: [ChunkType; 9] = [
+        ChunkType::oFFs,
+        ChunkType::pCAL,
+        ChunkType::sCAL,
+        ChunkType::gIFg,
+        ChunkType::gIFt,
+        ChunkType::gIFx,
+        ChunkType::sTER,
+        ChunkType::dSIG,
+        ChunkType::fRAc,
+    ];
+}
+
+// Tipos privados que usa pngme para sus propios datos
+#[allow(non_upper_case_globals)]
+impl ChunkType {
+    // firma Ed25519: auxiliar, privado, no seguro para copiar
+    pub const siGN: ChunkType = ChunkType { code: *b"siGN" };
+    // parte de un mensaje repartido entre varios PNG: auxiliar, privado, seguro para copiar
+    pub const spRd: ChunkType = ChunkType { code: *b"spRd" };
+    // archivo escondido con encode-file: auxiliar, privado, seguro para copiar
+    pub const fiLe: ChunkType = ChunkType { code: *b"fiLe" };
+    // paquete de varios archivos de pack: auxiliar, privado, seguro para copiar
+    pub const paCk: ChunkType = ChunkType { code: *b"paCk" };
+
+    pub const PNGME: [ChunkType; 4] = [ChunkType::siGN, ChunkType::spRd, ChunkType::fiLe, ChunkType::paCk];
+}
 
 impl ChunkType {
 
@@ -60,16 +204,84 @@ impl ChunkType {
     pub fn is_valid(&self) -> bool {
         self.is_reserved_bit_valid()
     }
+
+    // Definido en la especificación PNG
+    pub fn is_standard(&self) -> bool {
+        ChunkType::STANDARD.contains(self)
+    }
+
+    // Reglas de orden y multiplicidad; None para tipos que no están registrados
+    pub fn constraints(&self) -> Option<ChunkConstraints> {
+        use Placement::*;
+        let constraints = match &self.code {
+            b"IHDR" => ChunkConstraints::new(false, First),
+            b"IEND" => ChunkConstraints::new(false, Last),
+            b"PLTE" => ChunkConstraints::new(false, BeforeIdat),
+            b"IDAT" => ChunkConstraints::new(true, Idat),
+            b"cHRM" | b"cICP" | b"gAMA" | b"iCCP" | b"mDCV" | b"cLLI" | b"sBIT" | b"sRGB" => {
+                ChunkConstraints::new(false, BeforePlte)
+            }
+            b"bKGD" | b"hIST" | b"tRNS" => ChunkConstraints::new(false, BetweenPlteAndIdat),
+            b"eXIf" | b"pHYs" | b"acTL" | b"oFFs" | b"pCAL" | b"sCAL" | b"sTER" => {
+                ChunkConstraints::new(false, BeforeIdat)
+            }
+            b"sPLT" => ChunkConstraints::new(true, BeforeIdat),
+            b"tIME" => ChunkConstraints::new(false, Anywhere),
+            b"tEXt" | b"zTXt" | b"iTXt" | b"fcTL" | b"gIFg" | b"gIFt" | b"gIFx" | b"dSIG" | b"fRAc" => {
+                ChunkConstraints::new(true, Anywhere)
+            }
+            b"fdAT" => ChunkConstraints::new(true, AfterIdat),
+            _ => return None,
+        };
+        Some(constraints)
+    }
+
+    // Estándar o extensión pública registrada: no conviene usarlo para tipos propios
+    pub fn is_registered(&self) -> bool {
+        self.is_standard() || ChunkType::REGISTERED_EXTENSIONS.contains(self)
+    }
+
+    // Por qué este tipo no sirve para un chunk propio; vacío si no hay problemas
+    pub fn custom_use_problems(&self) -> Vec<TypeProblem> {
+        let checks = [
+            (self.is_registered(), TypeProblem::Registered),
+            (self.is_critical(), TypeProblem::Critical),
+            (!self.is_reserved_bit_valid(), TypeProblem::ReservedBit),
+        ];
+        checks.into_iter().filter(|(failed, _)| *failed).map(|(_, problem)| problem).collect()
+    }
+
+    // Un tipo auxiliar y privado al azar, con el bit reservado válido, que no choca con los registrados ni
+    // con los que usa pngme. No es criptográfico: sólo evita que todos terminen usando ruSt
+    pub fn random_private(safe_to_copy: bool) -> ChunkType {
+        use std::hash::{BuildHasher, RandomState};
+        loop {
+            // cada RandomState nuevo trae otras claves, así que el hash de lo mismo cambia
+            let random = RandomState::new().hash_one(());
+            let letter = |i: u32| b'a' + ((random >> (16 * i)) % 26) as u8;
+            let last = if safe_to_copy { letter(3) } else { letter(3).to_ascii_uppercase() };
+            let chunk_type = ChunkType { code: [letter(0), letter(1), letter(2).to_ascii_uppercase(), last] };
+            if chunk_type.is_free_to_use() && !ChunkType::PNGME.contains(&chunk_type) {
+                return chunk_type;
+            }
+        }
+    }
+
+    // Se puede inventar sin pedirle nada a nadie: privado, con el bit reservado válido y sin chocar con
+    // un tipo registrado. Críticos privados también valen, pero los lectores que no los conocen
+    // rechazan la imagen entera
+    pub fn is_free_to_use(&self) -> bool {
+        !self.is_public() && self.is_reserved_bit_valid() && !self.is_registered()
+    }
 }
 
 // Implementaciones de traits de datos primitivos
 impl TryFrom<[u8; 4]> for ChunkType {
-    type Error = Error;
+    type Error = PngmeError;
     fn try_from(value: [u8; 4]) -> Result<Self> {
         for byte in value {
             if !byte.is_ascii_alphabetic(){
-                let err: Error = ChunkTypeErrors::IsNotAlphabetic.into();
-                return Err(err);
+                return Err(PngmeError::InvalidChunkType { offset: None, bytes: value });
             }
         }
         Ok(ChunkType {code: value})
@@ -77,17 +289,13 @@ impl TryFrom<[u8; 4]> for ChunkType {
 }
 
 impl FromStr for ChunkType {
-    type Err = Error;
+    type Err = PngmeError;
     fn from_str(s: &str) -> Result<Self> {
-        let s = s.as_bytes();
-        let s: [u8; 4] = s[0..4].try_into()?;
-        for byte in s {
-            if !byte.is_ascii_alphabetic(){
-                let err: Error = ChunkTypeErrors::IsNotAlphabetic.into();
-                return Err(err);
-            }
-        }
-        Ok(ChunkType {code: s})
+        let code: [u8; 4] = s
+            .as_bytes()
+            .try_into()
+            .map_err(|_| PngmeError::InvalidChunkTypeLength { length: s.len() })?;
+        ChunkType::try_from(code)
     }
 }
 
@@ -118,6 +326,16 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    pub fn test_chunk_type_from_str_wrong_length() {
+        for text in ["", "Ru", "RuS", "RuStt", "ñañ"] {
+            let err = ChunkType::from_str(text).unwrap_err();
+            assert!(matches!(err, PngmeError::InvalidChunkTypeLength { length } if length == text.len()));
+        }
+        // cuatro bytes pero no cuatro letras ASCII
+        assert!(matches!(ChunkType::from_str("ññ"), Err(PngmeError::InvalidChunkType { .. })));
+    }
+
     #[test]
     pub fn test_chunk_type_is_critical() {
         let chunk = ChunkType::from_str("RuSt").unwrap();
@@ -187,6 +405,67 @@ mod tests {
         assert_eq!(&chunk.to_string(), "RuSt");
     }
 
+    #[test]
+    pub fn test_chunk_type_constants() {
+        assert_eq!(ChunkType::IHDR, ChunkType::from_str("IHDR").unwrap());
+        assert_eq!(ChunkType::tEXt.to_string(), "tEXt");
+    }
+
+    #[test]
+    pub fn test_chunk_type_is_standard() {
+        assert!(ChunkType::IDAT.is_standard());
+        assert!(ChunkType::from_str("zTXt").unwrap().is_standard());
+        assert!(!ChunkType::oFFs.is_standard());
+        assert!(ChunkType::oFFs.is_registered());
+        assert!(!ChunkType::from_str("ruSt").unwrap().is_registered());
+    }
+
+    #[test]
+    pub fn test_chunk_type_constraints() {
+        let ihdr = ChunkType::IHDR.constraints().unwrap();
+        assert_eq!(ihdr, ChunkConstraints { multiple: false, placement: Placement::First });
+        assert_eq!(ChunkType::tRNS.constraints().unwrap().placement, Placement::BetweenPlteAndIdat);
+        assert!(ChunkType::tEXt.constraints().unwrap().multiple);
+        assert!(ChunkType::from_str("ruSt").unwrap().constraints().is_none());
+        // todos los tipos registrados tienen reglas
+        let registered = ChunkType::STANDARD.iter().chain(ChunkType::REGISTERED_EXTENSIONS.iter());
+        assert!(registered.into_iter().all(|ct| ct.constraints().is_some()));
+    }
+
+    #[test]
+    pub fn test_chunk_type_is_free_to_use() {
+        assert!(ChunkType::from_str("ruSt").unwrap().is_free_to_use());
+        assert!(ChunkType::siGN.is_free_to_use());
+        assert!(!ChunkType::tEXt.is_free_to_use());
+        // público sin registrar
+        assert!(!ChunkType::from_str("RUSt").unwrap().is_free_to_use());
+        // bit reservado inválido
+        assert!(!ChunkType::from_str("rust").unwrap().is_free_to_use());
+    }
+
+    #[test]
+    pub fn test_chunk_type_custom_use_problems() {
+        assert!(ChunkType::from_str("ruSt").unwrap().custom_use_problems().is_empty());
+        assert_eq!(ChunkType::tEXt.custom_use_problems(), [TypeProblem::Registered]);
+        assert_eq!(ChunkType::IHDR.custom_use_problems(), [TypeProblem::Registered, TypeProblem::Critical]);
+        let problems = ChunkType::from_str("Rust").unwrap().custom_use_problems();
+        assert_eq!(problems, [TypeProblem::Critical, TypeProblem::ReservedBit]);
+    }
+
+    #[test]
+    pub fn test_chunk_type_random_private() {
+        for safe_to_copy in [true, false] {
+            for _ in 0..100 {
+                let ct = ChunkType::random_private(safe_to_copy);
+                assert!(!ct.is_critical() && !ct.is_public() && ct.is_reserved_bit_valid());
+                assert_eq!(ct.is_safe_to_copy(), safe_to_copy);
+                assert!(!ct.is_registered() && !ChunkType::PNGME.contains(&ct));
+            }
+        }
+        let generated: std::collections::HashSet<ChunkType> = (0..20).map(|_| ChunkType::random_private(true)).collect();
+        assert!(generated.len() > 1);
+    }
+
     #[test]
     pub fn test_chunk_type_trait_impls() {
         let chunk_type_1: ChunkType = TryFrom::try_from([82, 117, 83, 116]).unwrap();
@@ -0,0 +1,70 @@
+use std::fmt::Display;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+use pngme::chunk_type::ChunkType;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    Critical,
+    Ancillary,
+    Private,
+    Good,
+    Warning,
+    Bad,
+    Highlight,
+}
+
+impl Style {
+    fn code(&self) -> &'static str {
+        match self {
+            Style::Critical => "1;34",
+            Style::Ancillary => "32",
+            Style::Private => "35",
+            Style::Good => "32",
+            Style::Warning => "33",
+            Style::Bad => "1;31",
+            Style::Highlight => "1;33",
+        }
+    }
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+// Con auto sólo hay color si stdout es una terminal y no está definida NO_COLOR (https://no-color.org)
+pub fn init(choice: ColorChoice) {
+    let enabled = match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+        }
+    };
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn paint(text: impl Display, style: Style) -> String {
+    if ENABLED.load(Ordering::Relaxed) {
+        format!("\x1b[{}m{}\x1b[0m", style.code(), text)
+    } else {
+        text.to_string()
+    }
+}
+
+// Críticos, auxiliares públicos y privados se distinguen de un vistazo
+pub fn chunk_type(chunk_type: &ChunkType) -> String {
+    let style = if chunk_type.is_critical() {
+        Style::Critical
+    } else if chunk_type.is_public() {
+        Style::Ancillary
+    } else {
+        Style::Private
+    };
+    paint(chunk_type, style)
+}
@@ -0,0 +1,2130 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+use std::str::FromStr;
+use pngme::chunk::Chunk;
+use pngme::chunk_ref::ChunkRef;
+use pngme::chunk_type::{ChunkType, Placement};
+use pngme::compression::Compression;
+#[cfg(feature = "crypto")]
+use pngme::crypto::{read_keyfile, KdfParams};
+use pngme::payload::Payload;
+#[cfg(feature = "age")]
+use pngme::recipients::{parse_recipient, read_identities};
+use pngme::png::Png;
+use pngme::typed::{Itxt, TextChunk, TypedChunk, Ztxt};
+use pngme::{tr, PngmeError, Result};
+use pngme::lint::{lint as lint_png, Rule};
+use pngme::locale::text;
+use pngme::diff::{diff as diff_png, DiffKind};
+use pngme::embedded_file::EmbeddedFile;
+#[cfg(feature = "serde")]
+use pngme::analysis::ChunkAnalysis;
+use pngme::scan::{scan_bytes, Finding};
+#[cfg(feature = "serde")]
+use pngme::scan::FindingKind;
+use pngme::sniff::{detect_mime, extension, is_text};
+use pngme::archive::{safe_path, Archive};
+use pngme::fragment::{capacity_for, split, FragmentHeader, MAX_FRAGMENT_DATA};
+use pngme::spread::{gather, shard};
+#[cfg(feature = "crypto")]
+use pngme::shamir::{gather_shares, share_chunks};
+use pngme::hash::{sha256, to_hex};
+use pngme::hexdump::{hexdump, parse_hexdump};
+use pngme::repair::repair_bytes;
+use pngme::secret::Secret;
+#[cfg(feature = "keyring")]
+use pngme::keystore::{delete_secret, load_secret, store_secret};
+#[cfg(feature = "crypto")]
+use pngme::signature::{read_signing_key, read_verifying_key};
+use pngme::strip::StripFilter;
+use pngme::verify::{verify_bytes, Severity};
+#[cfg(feature = "crypto")]
+use crate::args::{SecretArgs, SignArgs, VerifySigArgs};
+#[cfg(feature = "keyring")]
+use crate::args::{KeyringDeleteArgs, KeyringSetArgs};
+#[cfg(feature = "watch")]
+use crate::args::WatchArgs;
+use crate::args::{
+    parse_rule, Position, AnalyzeArgs, BatchArgs, CapacityArgs, CopyChunksArgs, CrcArgs, DecodeArgs, DescribeArgs,
+    DiffArgs, DumpArgs, EditArgs, EncodeArgs, EncodeFileArgs, ExportChunkArgs, ExtractArgs, FixOrderArgs, GenerateArgs,
+    HashArgs, ImportChunkArgs, InfoArgs, LintArgs, ListArgs, LsArgs, OutputArgs, PackArgs, PatchArgs, PrintArgs,
+    RecoverArgs, RemoveArgs, RepairArgs, ScanArgs, StripArgs, UnpackArgs, VerifyArgs,
+};
+use crate::batch;
+use crate::color::{self, paint, Style};
+use crate::config;
+use crate::progress;
+
+// Contraseña a usar cuando no hay terminal para pedirla
+#[cfg(feature = "crypto")]
+const PASSWORD_ENV: &str = "PNGME_PASSWORD";
+
+// "-" como camino es la entrada o la salida estándar, para usar pngme en medio de un pipeline
+fn is_stdio(path: &Path) -> bool {
+    path == Path::new("-")
+}
+
+// Los mensajes de estado van a stderr cuando el PNG sale por stdout, para no mezclarse con él
+macro_rules! status {
+    ($output:expr, $($arg:tt)*) => {
+        if is_stdio($output) {
+            eprintln!($($arg)*);
+        } else {
+            println!($($arg)*);
+        }
+    };
+}
+
+fn read_input(path: &Path) -> Result<Vec<u8>> {
+    let _span = tracing::debug_span!("read", path = %path.display()).entered();
+    let mut bytes = Vec::new();
+    if is_stdio(path) {
+        std::io::stdin().lock().read_to_end(&mut bytes)?;
+        return Ok(bytes);
+    }
+    let file = File::open(path)?;
+    let len = file.metadata()?.len();
+    if len < progress::LARGE_FILE {
+        return Ok(fs::read(path)?);
+    }
+    let bar = progress::bytes(len, tr!("leyendo {}", "reading {}", path.display()));
+    bytes.reserve_exact(len as usize);
+    bar.wrap_read(file).read_to_end(&mut bytes)?;
+    bar.finish_and_clear();
+    Ok(bytes)
+}
+
+pub fn read_png(path: &Path) -> Result<Png> {
+    let bytes = read_input(path)?;
+    Png::try_from(bytes.as_slice())
+}
+
+// stdout de Rust no traduce saltos de línea ni en Windows, así que los bytes salen tal cual
+fn write_output(path: &Path, data: &[u8]) -> Result<()> {
+    if !is_stdio(path) {
+        return write_atomic(path, |writer| Ok(writer.write_all(data)?));
+    }
+    let mut stdout = std::io::stdout().lock();
+    stdout.write_all(data)?;
+    stdout.flush()?;
+    Ok(())
+}
+
+// Se escribe en un temporal del mismo directorio y recién al final se renombra encima del destino:
+// si el proceso se corta a mitad de camino el archivo anterior queda intacto
+fn write_atomic(path: &Path, write: impl FnOnce(&mut BufWriter<&File>) -> Result<()>) -> Result<()> {
+    let _span = tracing::debug_span!("write_file", path = %path.display()).entered();
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let name = path
+        .file_name()
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, tr!(
+            "{} no es un archivo",
+            "{} is not a file",
+            path.display()
+        )))?;
+    let temp = dir.join(format!(".{}.{}.tmp", name.to_string_lossy(), std::process::id()));
+    let file = File::options().write(true).create_new(true).open(&temp)?;
+    let result = (|| -> Result<()> {
+        let mut writer = BufWriter::new(&file);
+        write(&mut writer)?;
+        writer.flush()?;
+        drop(writer);
+        // el reemplazo conserva los permisos del original
+        if let Ok(metadata) = fs::metadata(path) {
+            file.set_permissions(metadata.permissions())?;
+        }
+        file.sync_all()?;
+        fs::rename(&temp, path)?;
+        tracing::info!("guardado");
+        Ok(())
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+        return result;
+    }
+    // en Unix el renombre recién es definitivo cuando se sincroniza el directorio
+    #[cfg(unix)]
+    if let Ok(dir) = File::open(dir) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+// Dónde se guarda el resultado: -o, el original con --in-place, stdout si el PNG vino por stdin, o
+// el mismo nombre dentro de output-dir si la configuración lo define
+fn output_path(output: &OutputArgs, input: &Path) -> Result<PathBuf> {
+    let in_output_dir = || Some(config::get().output_dir.as_ref()?.join(input.file_name()?));
+    match output.output.clone().or_else(|| if output.in_place { None } else { in_output_dir() }) {
+        Some(path) if path.exists() && !output.force => Err(PngmeError::Usage(tr!(
+            "{} ya existe; usá --force para sobrescribirlo",
+            "{} already exists; use --force to overwrite it",
+            path.display()
+        ))),
+        Some(path) => Ok(path),
+        None if output.in_place || output.dry_run || is_stdio(input) => Ok(input.to_path_buf()),
+        None => Err(PngmeError::Usage(tr!(
+            "no se sobrescribe {} sin --in-place; usá -o para guardar en otro archivo",
+            "{} is not overwritten without --in-place; use -o to save to another file",
+            input.display()
+        ))),
+    }
+}
+
+// Para las salidas que no pasan por OutputArgs: no pisa un archivo existente sin --force
+fn refuse_existing(path: &Path, force: bool) -> Result<()> {
+    if path.exists() && !is_stdio(path) && !force {
+        return Err(PngmeError::Usage(tr!(
+            "{} ya existe; usá --force para sobrescribirlo",
+            "{} already exists; use --force to overwrite it",
+            path.display()
+        )));
+    }
+    Ok(())
+}
+
+// Con --in-place --backup, copia el original a archivo.png.bak (o al sufijo elegido) antes de pisarlo
+fn backup_original(output: &OutputArgs, input: &Path) -> Result<()> {
+    let Some(suffix) = output.backup.as_ref().or(config::get().backup.as_ref()) else {
+        return Ok(());
+    };
+    if !output.in_place || is_stdio(input) {
+        return Ok(());
+    }
+    let mut backup = input.as_os_str().to_owned();
+    backup.push(suffix);
+    fs::copy(input, backup)?;
+    Ok(())
+}
+
+// Guarda el PNG modificado donde corresponda; con --dry-run sólo muestra qué cambiaría
+fn save_png(output: &OutputArgs, input: &Path, png: &Png) -> Result<()> {
+    if output.dry_run {
+        let original = if is_stdio(input) { None } else { Some(fs::read(input)?) };
+        print!("{}", preview(input, original.as_deref(), png));
+        return Ok(());
+    }
+    backup_original(output, input)?;
+    write_png(&output_path(output, input)?, png)
+}
+
+// Los chunks que cambiarían y el tamaño final, todo en un solo texto para que no se mezcle con
+// el de otros archivos procesados en paralelo
+fn preview(input: &Path, original: Option<&[u8]>, png: &Png) -> String {
+    let mut text = tr!(
+        "{} (--dry-run, no se escribió nada):\n",
+        "{} (--dry-run, nothing was written):\n",
+        input.display()
+    );
+    let size = png.as_bytes().len();
+    match original {
+        Some(bytes) => {
+            // un archivo dañado no se puede comparar chunk por chunk, pero sí por tamaño
+            if let Ok(original) = Png::try_from(bytes) {
+                for d in diff_png(&original, png) {
+                    text += &format!("  {}\n", d);
+                }
+            }
+            let growth = size as i64 - bytes.len() as i64;
+            text += &tr!(
+                "  tamaño: {} -> {} bytes ({:+})\n",
+                "  size: {} -> {} bytes ({:+})\n",
+                bytes.len(),
+                size,
+                growth
+            );
+        }
+        None => text += &tr!("  tamaño: {} bytes\n", "  size: {} bytes\n", size),
+    }
+    text
+}
+
+pub fn write_png(path: &Path, png: &Png) -> Result<()> {
+    if is_stdio(path) {
+        let mut writer = BufWriter::new(std::io::stdout().lock());
+        png.write_to(&mut writer)?;
+        writer.flush()?;
+        return Ok(());
+    }
+    let len = 8 + png.chunks().iter().map(|chunk| chunk.length() as u64 + 12).sum::<u64>();
+    if len < progress::LARGE_FILE {
+        return write_atomic(path, |writer| png.write_to(writer));
+    }
+    let bar = progress::bytes(len, tr!("escribiendo {}", "writing {}", path.display()));
+    write_atomic(path, |writer| png.write_to(&mut bar.wrap_write(writer)))?;
+    bar.finish_and_clear();
+    Ok(())
+}
+
+// Texto de un chunk de texto estándar como "keyword: texto"; None si es otro tipo de chunk
+pub fn text_message(chunk: &Chunk) -> Result<Option<String>> {
+    let text = match chunk.decode_typed()? {
+        TypedChunk::Text(text) => format!("{}: {}", text.keyword, text.text),
+        TypedChunk::Ztxt(ztxt) => format!("{}: {}", ztxt.keyword, ztxt.text()?),
+        TypedChunk::Itxt(itxt) if itxt.language_tag.is_empty() => format!("{}: {}", itxt.keyword, itxt.text()?),
+        TypedChunk::Itxt(itxt) => format!("{} [{}]: {}", itxt.keyword, itxt.language_tag, itxt.text()?),
+        _ => return Ok(None),
+    };
+    Ok(Some(text))
+}
+
+// Contraseña, contenido del archivo de clave o clave guardada en el sistema; clap impide que venga más de una
+#[cfg(feature = "crypto")]
+fn secret(args: &SecretArgs) -> Result<Option<Secret<Vec<u8>>>> {
+    #[cfg(feature = "keyring")]
+    if let Some(id) = &args.key_id {
+        return Ok(Some(load_secret(id)?));
+    }
+    if args.password_env {
+        let password = env_password().ok_or_else(|| PngmeError::InvalidKey(tr!(
+            "--password-env: la variable {} no está definida",
+            "--password-env: the {} variable is not set",
+            PASSWORD_ENV
+        )))?;
+        return Ok(Some(password));
+    }
+    match &args.keyfile {
+        Some(path) => Ok(Some(read_keyfile(path)?)),
+        None => Ok(args.password.clone().map(Secret::from)),
+    }
+}
+
+#[cfg(feature = "crypto")]
+fn env_password() -> Option<Secret<Vec<u8>>> {
+    std::env::var(PASSWORD_ENV).ok().filter(|password| !password.is_empty()).map(Secret::from)
+}
+
+// Si no se indicó ninguna clave, en una terminal se usa la clave key-id de la configuración o se pide la
+// contraseña sin mostrarla (dos veces si `confirm`). PNGME_PASSWORD sólo se lee sin terminal, como en un
+// script, o con --password-env: una variable exportada y olvidada no tiene que ganarle a la pregunta
+#[cfg(feature = "crypto")]
+fn secret_or_prompt(args: &SecretArgs, confirm: bool) -> Result<Secret<Vec<u8>>> {
+    use std::io::IsTerminal;
+
+    if let Some(secret) = secret(args)? {
+        return Ok(secret);
+    }
+    let interactive = std::io::stdin().is_terminal();
+    if !interactive {
+        if let Some(password) = env_password() {
+            return Ok(password);
+        }
+    }
+    // la clave por defecto de la configuración sólo reemplaza a la pregunta
+    #[cfg(feature = "keyring")]
+    if let Some(id) = &config::get().key_id {
+        return load_secret(id);
+    }
+    if !interactive {
+        return Err(PngmeError::InvalidKey(tr!(
+            "hace falta una contraseña: usá --password, --keyfile o la variable {}",
+            "a password is needed: use --password, --keyfile or the {} variable",
+            PASSWORD_ENV
+        )));
+    }
+    let password: Secret<Vec<u8>> = Secret::from(rpassword::prompt_password(text("Contraseña: ", "Password: "))?);
+    if password.expose().is_empty() {
+        let reason = text("la contraseña no puede estar vacía", "the password cannot be empty");
+        return Err(PngmeError::InvalidKey(reason.to_string()));
+    }
+    let repeat = || rpassword::prompt_password(text("Repetí la contraseña: ", "Repeat the password: "));
+    if confirm && Secret::<Vec<u8>>::from(repeat()?) != password {
+        let reason = text("las contraseñas no coinciden", "the passwords do not match");
+        return Err(PngmeError::InvalidKey(reason.to_string()));
+    }
+    Ok(password)
+}
+
+// La compresión pedida, o la de la configuración
+fn compression(requested: Option<Compression>) -> Compression {
+    requested.or(config::get().compress).unwrap_or(Compression::None)
+}
+
+// El tipo de chunk indicado, o chunk-type de la configuración
+fn chunk_type_or_default(chunk_type: Option<String>) -> Result<String> {
+    chunk_type.or_else(|| config::get().chunk_type.clone()).ok_or_else(|| {
+        PngmeError::Usage(tr!(
+            "falta el tipo de chunk; indicalo o definí chunk-type en la configuración",
+            "the chunk type is missing; give it or set chunk-type in the configuration"
+        ))
+    })
+}
+
+// Mensaje con la compresión y el cifrado que se pidieron
+fn build_payload(args: &EncodeArgs, message: Vec<u8>) -> Result<Payload> {
+    let payload = Payload::new(message).compressed(compression(args.compress));
+    #[cfg(feature = "crypto")]
+    let payload = if args.encrypt || args.hmac {
+        let defaults = KdfParams::default();
+        let kdf = KdfParams {
+            memory_kib: args.kdf_memory.unwrap_or(defaults.memory_kib),
+            iterations: args.kdf_iterations.unwrap_or(defaults.iterations),
+            parallelism: args.kdf_parallelism.unwrap_or(defaults.parallelism),
+        };
+        kdf.validate()?;
+        let password = secret_or_prompt(&args.secret, true)?;
+        let payload = if args.hmac { payload.authenticated(password) } else { payload.encrypted(password) };
+        payload.with_kdf(kdf)
+    } else if secret(&args.secret)?.is_some() {
+        return Err(PngmeError::Usage(tr!(
+            "la contraseña o la clave sólo se usan con --encrypt o --hmac",
+            "the password or key is only used with --encrypt or --hmac"
+        )));
+    } else {
+        payload
+    };
+    #[cfg(feature = "age")]
+    let payload = match recipients(args) {
+        [] => payload,
+        recipients => {
+            let recipients = recipients.iter().map(|r| parse_recipient(r)).collect::<Result<Vec<_>>>()?;
+            payload.encrypted_to(recipients)
+        }
+    };
+    Ok(payload)
+}
+
+// Los de --recipient, o los de la configuración; éstos no se suman a --encrypt ni a --hmac
+#[cfg(feature = "age")]
+fn recipients(args: &EncodeArgs) -> &[String] {
+    #[cfg(feature = "crypto")]
+    if args.encrypt || args.hmac {
+        return &args.recipient;
+    }
+    if args.recipient.is_empty() {
+        &config::get().recipients
+    } else {
+        &args.recipient
+    }
+}
+
+pub fn encode(mut args: EncodeArgs) -> Result<()> {
+    // la salida posicional de siempre vale como -o
+    if let Some(path) = args.output_file.take() {
+        args.output.output = Some(path);
+    }
+    if !args.spread.is_empty() {
+        return encode_spread(args);
+    }
+    // clap exige el archivo cuando no se usa --spread
+    let file_path = args.file_path.as_deref().expect("falta el archivo");
+    // con chunk-type en la configuración, `encode foto.png "hola"` lleva el mensaje donde iría el tipo
+    let no_message = args.message.is_none() && args.message_file.is_none();
+    if no_message && config::get().chunk_type.is_some() {
+        args.message = args.chunk_type.take();
+    }
+    let chunk_type = chunk_type_or_default(args.chunk_type.take())?;
+    let chunk_type = chunk_type.as_str();
+    if args.message.is_none() && args.message_file.is_none() {
+        return Err(PngmeError::Usage(tr!(
+            "falta el mensaje; pasalo como argumento o con -m",
+            "the message is missing; give it as an argument or with -m"
+        )));
+    }
+    let text = args.message.as_deref().unwrap_or_default();
+    // se valida antes de leer nada
+    output_path(&args.output, file_path)?;
+    let mut png = read_png(file_path)?;
+    // un mensaje anterior con el mismo tipo (o keyword) no se pisa ni se duplica sin pedirlo
+    let text_mode = args.text || args.ztxt || args.itxt;
+    let exists = if text_mode { png.has_text(chunk_type) } else { png.chunk_by_type(chunk_type).is_some() };
+    // --replace es otro nombre para lo que hace --force acá; con --append, --force sólo pisa el archivo de -o
+    let replace = args.replace || (args.output.force && !args.append);
+    if exists && !replace && !args.append {
+        return Err(PngmeError::Usage(tr!(
+            "{} ya tiene un mensaje en {}; usá --force para reemplazarlo o --append para agregar otro",
+            "{} already has a message in {}; use --force to replace it or --append to add another one",
+            file_path.display(),
+            chunk_type
+        )));
+    }
+    if exists && replace {
+        if text_mode {
+            png.remove_text(chunk_type);
+        } else {
+            png.strip(&StripFilter::only(&[ChunkType::from_str(chunk_type)?]));
+        }
+    }
+    let chunks = if args.text {
+        vec![TextChunk::new(chunk_type, text)?.to_chunk()?]
+    } else if args.ztxt {
+        vec![Ztxt::new(chunk_type, text)?.to_chunk()?]
+    } else if args.itxt {
+        let itxt = Itxt::new(chunk_type, &args.lang, &args.translated_keyword, text, args.itxt_compress)?;
+        vec![itxt.to_chunk()?]
+    } else {
+        let chunk_type = ChunkType::from_str(chunk_type)?;
+        check_custom_type(&chunk_type, args.allow_unsafe_type)?;
+        let message = match &args.message_file {
+            Some(path) => read_input(path)?,
+            None => text.as_bytes().to_vec(),
+        };
+        let payload = build_payload(&args, message)?;
+        let max_data = args.max_chunk_size.unwrap_or(MAX_FRAGMENT_DATA);
+        split(chunk_type, &payload.to_bytes()?, max_data)?
+    };
+    match &args.position {
+        Some(position) => {
+            insert_at_position(&mut png, position, chunks)?;
+        }
+        None => {
+            for chunk in chunks {
+                png.append_chunk(chunk)?;
+            }
+        }
+    }
+
+    save_png(&args.output, file_path, &png)?;
+    Ok(())
+}
+
+// Agrega los chunks seguidos en la posición pedida; devuelve dónde quedó el primero
+fn insert_at_position(png: &mut Png, position: &Position, chunks: Vec<Chunk>) -> Result<usize> {
+    let mut chunks = chunks.into_iter();
+    let Some(first) = chunks.next() else {
+        return Ok(0);
+    };
+    let start = match position {
+        Position::Index(index) => {
+            png.insert_chunk_at(*index, first)?;
+            *index
+        }
+        Position::Before(chunk_type) => png.insert_before(chunk_type, first)?,
+        Position::After(chunk_type) => png.insert_after(chunk_type, first)?,
+    };
+    for (offset, chunk) in chunks.enumerate() {
+        png.insert_chunk_at(start + 1 + offset, chunk)?;
+    }
+    Ok(start)
+}
+
+// Un tipo registrado, crítico o con el bit reservado inválido rompe la imagen o confunde a los lectores:
+// sin --allow-unsafe-type no se usa, y con él se avisa igual (con --log-format json el aviso sale como JSON)
+fn check_custom_type(chunk_type: &ChunkType, allow: bool) -> Result<()> {
+    let problems = chunk_type.custom_use_problems();
+    if problems.is_empty() {
+        return Ok(());
+    }
+    if !allow {
+        let reasons: Vec<String> = problems.iter().map(ToString::to_string).collect();
+        return Err(PngmeError::Usage(tr!(
+            "{} {}; elegí otro (pngme chunk-type generate) o usá --allow-unsafe-type",
+            "{} {}; pick another one (pngme chunk-type generate) or use --allow-unsafe-type",
+            chunk_type,
+            reasons.join(text(" y ", " and "))
+        )));
+    }
+    for problem in problems {
+        tracing::warn!(chunk_type = %chunk_type, ?problem, "{} {}", chunk_type, problem);
+    }
+    Ok(())
+}
+
+// Reparte el mensaje entre todos los archivos; una parte anterior en alguno de ellos se reemplaza
+fn encode_spread(args: EncodeArgs) -> Result<()> {
+    let message = read_input(args.message_file.as_deref().expect("clap exige -m con --spread"))?;
+    let bytes = build_payload(&args, message)?.to_bytes()?;
+    // se leen todos antes de escribir para no dejar el mensaje a medias si alguno falla
+    // un patrón solo ya puede nombrar varios archivos, así que la cantidad se controla después de expandirlo
+    let spread = batch::patterns(&args.spread)?;
+    if spread.len() < 2 {
+        let reason = text("--spread necesita al menos dos archivos", "--spread needs at least two files");
+        return Err(PngmeError::Usage(reason.to_string()));
+    }
+    let mut covers = spread.iter().map(|path| read_png(path)).collect::<Result<Vec<_>>>()?;
+    #[cfg(feature = "crypto")]
+    let parts = match args.threshold {
+        Some(threshold) => {
+            if args.shares.is_some_and(|shares| shares != covers.len()) {
+                return Err(PngmeError::Usage(tr!(
+                    "--shares tiene que coincidir con los {} archivos de --spread",
+                    "--shares must match the {} files of --spread",
+                    covers.len()
+                )));
+            }
+            share_chunks(&bytes, threshold, covers.len())?
+        }
+        None => shard(&bytes, covers.len())?,
+    };
+    #[cfg(not(feature = "crypto"))]
+    let parts = shard(&bytes, covers.len())?;
+    for (png, chunks) in covers.iter_mut().zip(parts) {
+        png.remove_shards();
+        for chunk in chunks {
+            png.append_chunk(chunk)?;
+        }
+    }
+    for (path, png) in spread.iter().zip(&covers) {
+        save_png(&args.output, path, png)?;
+    }
+    println!("{}", tr!(
+        "Mensaje de {} bytes repartido entre {} archivos",
+        "{}-byte message spread over {} files",
+        bytes.len(),
+        covers.len()
+    ));
+    Ok(())
+}
+
+// Abre el mensaje con la contraseña o las identidades que se indicaron
+#[cfg_attr(not(any(feature = "crypto", feature = "age")), allow(unused_variables))]
+fn open_payload(data: &[u8], args: &DecodeArgs) -> Result<Payload> {
+    #[cfg(feature = "age")]
+    if let Some(path) = &args.identity {
+        return Payload::open_with_identities(data, &read_identities(path)?);
+    }
+    #[cfg(feature = "crypto")]
+    let secret = if args.decrypt || Payload::is_authenticated(data) {
+        Some(secret_or_prompt(&args.secret, false)?)
+    } else {
+        None
+    };
+    #[cfg(not(feature = "crypto"))]
+    let secret: Option<Secret<Vec<u8>>> = None;
+    let password = secret.as_ref().map(|secret| secret.expose().as_slice());
+    if password.is_none() && Payload::is_encrypted(data) {
+        let reason = if Payload::is_recipient_encrypted(data) {
+            text(
+                "el mensaje está cifrado para destinatarios age; usá --identity <ARCHIVO>",
+                "the message is encrypted for age recipients; use --identity <FILE>"
+            )
+        } else {
+            text("el mensaje está cifrado; usá --decrypt", "the message is encrypted; use --decrypt")
+        };
+        return Err(PngmeError::Usage(reason.to_string()));
+    }
+    Payload::open(data, password)
+}
+
+pub fn decode(args: DecodeArgs) -> Result<()> {
+    if !args.gather.is_empty() {
+        return decode_gather(args);
+    }
+    // clap exige el archivo cuando no se usa --gather
+    let png = read_png(args.file_path.as_deref().expect("falta el archivo"))?;
+    if args.all || (args.chunk_type.is_none() && config::get().chunk_type.is_none()) {
+        return decode_all(&png);
+    }
+    let chunk_type = chunk_type_or_default(args.chunk_type.clone())?;
+    let chunk_type = chunk_type.as_str();
+    match png.chunk_by_type(chunk_type) {
+        Some(chunk) => match text_message(chunk)? {
+            Some(text) => println!("{}", text),
+            None => {
+                let payload = open_payload(&png.reassemble(chunk)?, &args)?;
+                write_message(payload.data(), chunk_type, args.output.as_deref())?;
+            }
+        },
+        None => return Err(png.chunk_not_found(chunk_type)),
+    }
+    Ok(())
+}
+
+// Prueba cada chunk auxiliar y muestra los que tienen un mensaje legible, para cuando no se sabe qué
+// tipo usó quien lo escondió
+fn decode_all(png: &Png) -> Result<()> {
+    let mut found = 0;
+    // un mensaje repartido en fragmentos se muestra una vez, en la posición del primero
+    let mut seen_fragments = HashSet::new();
+    for (index, chunk) in png.chunks().iter().enumerate() {
+        let ct = chunk.chunk_type();
+        if ct.is_critical() {
+            continue;
+        }
+        let message = match text_message(chunk) {
+            Ok(Some(text)) => Some(text),
+            _ if ct.is_registered() => None,
+            _ => private_message(png, chunk, &mut seen_fragments),
+        };
+        if let Some(message) = message {
+            found += 1;
+            println!("#{} {}: {}", index, color::chunk_type(ct), message);
+        }
+    }
+    if found == 0 {
+        return Err(PngmeError::NoMessageFound);
+    }
+    Ok(())
+}
+
+// El mensaje de un chunk privado si es texto; los cifrados sólo se señalan
+fn private_message(png: &Png, chunk: &Chunk, seen_fragments: &mut HashSet<(ChunkType, u32)>) -> Option<String> {
+    if let Some((header, _)) = FragmentHeader::parse(chunk.data()) {
+        if !seen_fragments.insert((*chunk.chunk_type(), header.id)) {
+            return None;
+        }
+    }
+    let data = png.reassemble(chunk).ok()?;
+    if Payload::is_encrypted(&data) || Payload::is_authenticated(&data) {
+        return Some(tr!(
+            "(protegido con contraseña; se lee con decode {})",
+            "(password protected; read it with decode {})",
+            chunk.chunk_type()
+        ));
+    }
+    let payload = Payload::from_bytes(&data).ok()?;
+    is_text(payload.data()).then(|| String::from_utf8_lossy(payload.data()).into_owned())
+}
+
+// El texto se muestra; lo binario va a un archivo con la extensión de su tipo, o tal cual si la salida
+// no es una terminal, para no llenarla de basura
+fn write_message(data: &[u8], name: &str, output: Option<&Path>) -> Result<()> {
+    use std::io::IsTerminal;
+    if let Some(path) = output {
+        return write_output(path, data);
+    }
+    // un PDF puede ser UTF-8 válido; manda la firma, y el texto es lo que no tiene ninguna
+    let mime = detect_mime(data);
+    if mime == "text/plain" || (mime == "application/json" && is_text(data)) {
+        println!("{}", String::from_utf8_lossy(data));
+        return Ok(());
+    }
+    let mut stdout = std::io::stdout();
+    if !stdout.is_terminal() {
+        stdout.write_all(data)?;
+        return Ok(());
+    }
+    let path = unused_path(name, extension(mime));
+    fs::write(&path, data)?;
+    println!("{}", tr!(
+        "Mensaje binario ({}, {} bytes) guardado en {}",
+        "Binary message ({}, {} bytes) saved to {}",
+        mime,
+        data.len(),
+        path.display()
+    ));
+    Ok(())
+}
+
+// Donde se guarda lo extraído cuando no se indica otro lugar: output-dir de la configuración o el actual
+pub fn default_dir() -> PathBuf {
+    config::get().output_dir.clone().unwrap_or_default()
+}
+
+// nombre.ext, o nombre-1.ext, nombre-2.ext... si ya existe
+fn unused_path(name: &str, extension: &str) -> PathBuf {
+    let dir = default_dir();
+    (0..)
+        .map(|n| match n {
+            0 => dir.join(format!("{}.{}", name, extension)),
+            n => dir.join(format!("{}-{}.{}", name, n, extension)),
+        })
+        .find(|path| !path.exists())
+        .expect("siempre hay un nombre libre")
+}
+
+fn decode_gather(args: DecodeArgs) -> Result<()> {
+    let mut shards = Vec::new();
+    #[cfg(feature = "crypto")]
+    let mut shares = Vec::new();
+    for path in batch::patterns(&args.gather)? {
+        let png = read_png(&path)?;
+        shards.extend(png.shards()?);
+        #[cfg(feature = "crypto")]
+        shares.extend(png.shamir_shares()?);
+    }
+    // las partes de Shamir se combinan; si no hay, se juntan las de un reparto común
+    #[cfg(feature = "crypto")]
+    let data = if shares.is_empty() { Secret::new(gather(shards)?) } else { gather_shares(shares)? };
+    #[cfg(not(feature = "crypto"))]
+    let data = Secret::new(gather(shards)?);
+    let payload = open_payload(data.expose(), &args)?;
+    write_message(payload.data(), text("mensaje", "message"), args.output.as_deref())
+}
+
+// Cifra el mensaje si se pidió --encrypt; una clave sin --encrypt es un error
+#[cfg(feature = "crypto")]
+fn encrypt_if_requested(payload: Payload, encrypt: bool, args: &SecretArgs) -> Result<Payload> {
+    if encrypt {
+        Ok(payload.encrypted(secret_or_prompt(args, true)?))
+    } else if secret(args)?.is_some() {
+        let reason = text(
+            "la contraseña o la clave sólo se usan con --encrypt",
+            "the password or key is only used with --encrypt"
+        );
+        Err(PngmeError::Usage(reason.to_string()))
+    } else {
+        Ok(payload)
+    }
+}
+
+// Mensaje guardado en el primer chunk del tipo; la clave se pide sólo si está cifrado o autenticado
+fn open_stored(png: &Png, chunk_type: &str, #[cfg(feature = "crypto")] args: &SecretArgs) -> Result<Payload> {
+    let chunk = png.chunk_by_type(chunk_type).ok_or_else(|| png.chunk_not_found(chunk_type))?;
+    let data = png.reassemble(chunk)?;
+    #[cfg(feature = "crypto")]
+    let secret = if Payload::is_encrypted(&data) || Payload::is_authenticated(&data) {
+        Some(secret_or_prompt(args, false)?)
+    } else {
+        None
+    };
+    #[cfg(not(feature = "crypto"))]
+    let secret: Option<Secret<Vec<u8>>> = None;
+    Payload::open(&data, secret.as_ref().map(|secret| secret.expose().as_slice()))
+}
+
+// Agrega el mensaje al archivo, fragmentado si hace falta, y lo escribe
+fn append_payload(path: &Path, chunk_type: ChunkType, payload: &Payload, output: &OutputArgs) -> Result<()> {
+    let mut png = read_png(path)?;
+    for chunk in split(chunk_type, &payload.to_bytes()?, MAX_FRAGMENT_DATA)? {
+        png.append_chunk(chunk)?;
+    }
+    save_png(output, path, &png)
+}
+
+// El archivo viaja dentro de un mensaje común, así que se comprime y se cifra como cualquier otro
+pub fn encode_file(args: EncodeFileArgs) -> Result<()> {
+    let output = output_path(&args.output, &args.file_path)?;
+    let file = EmbeddedFile::read(&args.input)?;
+    let payload = Payload::new(file.to_bytes()?).compressed(compression(args.compress));
+    #[cfg(feature = "crypto")]
+    let payload = encrypt_if_requested(payload, args.encrypt, &args.secret)?;
+    append_payload(&args.file_path, args.chunk_type, &payload, &args.output)?;
+    status!(&output, "{}", tr!(
+        "{} ({}, {} bytes) guardado en {}",
+        "{} ({}, {} bytes) saved in {}",
+        file.name,
+        file.mime,
+        file.data.len(),
+        args.chunk_type
+    ));
+    Ok(())
+}
+
+pub fn extract(args: ExtractArgs) -> Result<()> {
+    let png = read_png(&args.file_path)?;
+    #[cfg(feature = "crypto")]
+    let payload = open_stored(&png, &args.chunk_type, &args.secret)?;
+    #[cfg(not(feature = "crypto"))]
+    let payload = open_stored(&png, &args.chunk_type)?;
+    let file = EmbeddedFile::from_bytes(payload.data())?;
+
+    let output = match &args.output {
+        Some(path) => path.clone(),
+        None => {
+            let name = file.safe_name().ok_or_else(|| {
+                PngmeError::Usage(tr!(
+                    "el nombre guardado no sirve como archivo: {:?}; usá -o",
+                    "the stored name is not usable as a file name: {:?}; use -o",
+                    file.name
+                ))
+            })?;
+            // sin -o no se pisa nada que ya exista
+            let path = default_dir().join(name);
+            if path.exists() {
+                return Err(PngmeError::Usage(tr!(
+                    "{} ya existe; usá -o para elegir otro destino",
+                    "{} already exists; use -o to pick another destination",
+                    path.display()
+                )));
+            }
+            path
+        }
+    };
+    restore_file(&output, &file)?;
+    println!("{} ({}, {} bytes)", output.display(), file.mime, file.data.len());
+    Ok(())
+}
+
+// Escribe el contenido y le devuelve la fecha de modificación original
+fn restore_file(path: &Path, file: &EmbeddedFile) -> Result<()> {
+    fs::write(path, &file.data)?;
+    if let Some(seconds) = file.modified {
+        File::options().write(true).open(path)?.set_modified(UNIX_EPOCH + Duration::from_secs(seconds))?;
+    }
+    Ok(())
+}
+
+pub fn pack(args: PackArgs) -> Result<()> {
+    let output = output_path(&args.output, &args.file_path)?;
+    let mut archive = Archive::default();
+    for path in batch::patterns(&args.inputs)? {
+        archive.add_path(&path)?;
+    }
+    let payload = Payload::new(archive.to_bytes()?).compressed(compression(args.compress));
+    #[cfg(feature = "crypto")]
+    let payload = encrypt_if_requested(payload, args.encrypt, &args.secret)?;
+    append_payload(&args.file_path, args.chunk_type, &payload, &args.output)?;
+    let total: usize = archive.entries.iter().map(|entry| entry.data.len()).sum();
+    status!(&output, "{}", tr!(
+        "{} archivos ({} bytes) guardados en {}",
+        "{} files ({} bytes) saved in {}",
+        archive.entries.len(),
+        total,
+        args.chunk_type
+    ));
+    Ok(())
+}
+
+// Extrae todas las entradas o sólo las pedidas; nunca fuera del directorio de destino ni sobre algo que ya existe
+pub fn unpack(args: UnpackArgs) -> Result<()> {
+    let directory = args.directory.clone().unwrap_or_else(default_dir);
+    let png = read_png(&args.file_path)?;
+    #[cfg(feature = "crypto")]
+    let payload = open_stored(&png, &args.chunk_type, &args.secret)?;
+    #[cfg(not(feature = "crypto"))]
+    let payload = open_stored(&png, &args.chunk_type)?;
+    let archive = Archive::from_bytes(payload.data())?;
+    if let Some(missing) = args.entries.iter().find(|name| !archive.entries.iter().any(|entry| &entry.name == *name)) {
+        return Err(PngmeError::Usage(tr!(
+            "el paquete no tiene ninguna entrada {}",
+            "the archive has no entry {}",
+            missing
+        )));
+    }
+    let selected = archive.entries.iter().filter(|entry| args.entries.is_empty() || args.entries.contains(&entry.name));
+    for entry in selected {
+        let relative = safe_path(&entry.name)
+            .ok_or_else(|| PngmeError::InvalidPayload(tr!(
+                "camino inseguro en el paquete: {:?}",
+                "unsafe path in the archive: {:?}",
+                entry.name
+            )))?;
+        let path = directory.join(relative);
+        if path.exists() {
+            return Err(PngmeError::Usage(tr!("{} ya existe", "{} already exists", path.display())));
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        restore_file(&path, entry)?;
+        println!("{}", path.display());
+    }
+    Ok(())
+}
+
+pub fn ls(args: LsArgs) -> Result<()> {
+    let png = read_png(&args.file_path)?;
+    #[cfg(feature = "crypto")]
+    let payload = open_stored(&png, &args.chunk_type, &args.secret)?;
+    #[cfg(not(feature = "crypto"))]
+    let payload = open_stored(&png, &args.chunk_type)?;
+    for entry in Archive::from_bytes(payload.data())?.entries {
+        println!("{:>10}  {:<24}  {}", entry.data.len(), entry.mime, entry.name);
+    }
+    Ok(())
+}
+
+pub fn remove(args: RemoveArgs) -> Result<()> {
+    let output = output_path(&args.output, &args.file_path)?;
+    let chunk_type = chunk_type_or_default(args.chunk_type)?;
+    let mut png = read_png(&args.file_path)?;
+    let forced = args.output.force && args.i_know_what_im_doing;
+    // antes de --choose, para no preguntar cuál y después negarse
+    if !forced && ChunkType::from_str(&chunk_type).is_ok_and(|ct| ct.is_critical() && ct.is_standard()) {
+        eprintln!("{}", text(
+            "Para eliminarlo igual: --force --i-know-what-im-doing",
+            "To remove it anyway: --force --i-know-what-im-doing"
+        ));
+        return Err(PngmeError::CriticalChunkRemoval { chunk_type });
+    }
+    if args.all {
+        let removed =
+            if forced { png.remove_chunks_forced(&chunk_type)? } else { png.remove_chunks(&chunk_type)? };
+        if removed.is_empty() {
+            return Err(png.chunk_not_found(&chunk_type));
+        }
+        let dropped = png.critical_changed(removed[0].chunk_type(), args.keep_unsafe_to_copy);
+        save_png(&args.output, &args.file_path, &png)?;
+        // con --dry-run la vista previa ya dice qué cambiaría
+        if args.output.dry_run {
+            return Ok(());
+        }
+        for chunk in removed {
+            status!(&output, "{}", tr!("Se eliminó el chunk {}", "Removed chunk {}", chunk));
+        }
+        report_unsafe_dropped(&output, &dropped);
+        return Ok(());
+    }
+    let nth = if args.choose { choose_chunk(&png, &chunk_type)? } else { args.nth - 1 };
+    let chunk =
+        if forced { png.remove_chunk_nth_forced(&chunk_type, nth)? } else { png.remove_chunk_nth(&chunk_type, nth)? };
+    // un mensaje repartido en fragmentos se borra entero
+    let fragments = match FragmentHeader::parse(chunk.data()) {
+        Some((header, _)) => png.remove_fragments(chunk.chunk_type(), header.id),
+        None => 0,
+    };
+    let dropped = png.critical_changed(chunk.chunk_type(), args.keep_unsafe_to_copy);
+    save_png(&args.output, &args.file_path, &png)?;
+    if args.output.dry_run {
+        return Ok(());
+    }
+    status!(&output, "{}", tr!("Se eliminó el chunk {}", "Removed chunk {}", chunk));
+    if fragments > 0 {
+        status!(&output, "{}", tr!(
+            "Se eliminaron también los otros {} fragmentos del mensaje",
+            "The other {} fragments of the message were removed too",
+            fragments
+        ));
+    }
+    report_unsafe_dropped(&output, &dropped);
+    Ok(())
+}
+
+fn report_unsafe_dropped(output: &Path, dropped: &[Chunk]) {
+    for chunk in dropped {
+        status!(output, "{}", tr!(
+            "Se eliminó {}: no es seguro para copiar y puede no valer para la imagen modificada \
+             (--keep-unsafe-to-copy lo conserva)",
+            "Removed {}: it is not safe to copy and may not hold for the modified image \
+             (--keep-unsafe-to-copy keeps it)",
+            chunk.chunk_type()
+        ));
+    }
+}
+
+// Lista en stderr los chunks de ese tipo y pregunta cuál; devuelve su número entre ellos, desde 0
+fn choose_chunk(png: &Png, chunk_type: &str) -> Result<usize> {
+    use std::io::{BufRead, IsTerminal};
+
+    // un tipo mal escrito es un error, no una lista vacía
+    ChunkType::from_str(chunk_type)?;
+    let matching: Vec<(usize, &Chunk)> = png.chunks_by_type(chunk_type).collect();
+    match matching.len() {
+        0 => return Err(png.chunk_not_found(chunk_type)),
+        1 => return Ok(0),
+        _ => {}
+    }
+    if !std::io::stdin().is_terminal() {
+        let reason = text("--choose necesita una terminal; usá --nth", "--choose needs a terminal; use --nth");
+        return Err(PngmeError::Usage(reason.to_string()));
+    }
+    for (nth, (index, chunk)) in matching.iter().enumerate() {
+        let preview: String = chunk.data_as_string_lossy().chars().take(40).filter(|c| !c.is_control()).collect();
+        eprintln!("{:>3}) #{:<4} {:>8} bytes  {}", nth + 1, index, chunk.length(), preview);
+    }
+    eprint!("{}", tr!("¿Cuál eliminar? (1-{}): ", "Which one to remove? (1-{}): ", matching.len()));
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    match answer.trim().parse::<usize>() {
+        Ok(nth) if (1..=matching.len()).contains(&nth) => Ok(nth - 1),
+        _ => Err(PngmeError::Usage(tr!("respuesta inválida: {}", "invalid answer: {}", answer.trim()))),
+    }
+}
+
+pub fn edit(args: EditArgs) -> Result<()> {
+    let output = output_path(&args.output, &args.file_path)?;
+    let mut png = read_png(&args.file_path)?;
+    let chunk_type = ChunkType::from_str(&args.chunk_type)?;
+    let index = png
+        .chunks()
+        .iter()
+        .position(|chunk| *chunk.chunk_type() == chunk_type)
+        .ok_or_else(|| png.chunk_not_found(&args.chunk_type))?;
+    let old = png.chunks()[index].data();
+    let original = if args.hex { hexdump(old, 0).into_bytes() } else { old.to_vec() };
+    let edited = edit_in_editor(&original, &format!("{}.{}", chunk_type, if args.hex { "txt" } else { "bin" }))?;
+    let data = if args.hex {
+        let not_utf8 = |_| PngmeError::Usage(text("el volcado no es UTF-8", "the dump is not UTF-8").to_string());
+        parse_hexdump(&String::from_utf8(edited).map_err(not_utf8)?)?
+    } else {
+        edited
+    };
+    if data == old {
+        status!(&output, "{}", tr!("Sin cambios en {}", "No changes in {}", chunk_type));
+        return Ok(());
+    }
+    let chunk = Chunk::new(chunk_type, data)?;
+    status!(&output, "{}: {} -> {} bytes, CRC {:08x}", chunk_type, png.chunks()[index].length(), chunk.length(), chunk.crc());
+    png.replace_chunk_at(index, chunk);
+    let dropped = png.critical_changed(&chunk_type, args.keep_unsafe_to_copy);
+    save_png(&args.output, &args.file_path, &png)?;
+    report_unsafe_dropped(&output, &dropped);
+    Ok(())
+}
+
+// Escribe los datos en un archivo temporal, espera a que se cierre el editor ($VISUAL, $EDITOR o vi)
+// y devuelve lo que quedó. El archivo se borra siempre, incluso si el editor falla
+fn edit_in_editor(data: &[u8], suffix: &str) -> Result<Vec<u8>> {
+    let editor = std::env::var("VISUAL")
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .or_else(|| std::env::var("EDITOR").ok().filter(|editor| !editor.trim().is_empty()))
+        .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string());
+    let path = std::env::temp_dir().join(format!("pngme-{}-{}", std::process::id(), suffix));
+    File::options().write(true).create_new(true).open(&path)?.write_all(data)?;
+    // el editor puede traer argumentos, como "code --wait"
+    let mut words = editor.split_whitespace();
+    let status = std::process::Command::new(words.next().unwrap_or_default()).args(words).arg(&path).status();
+    let edited = fs::read(&path);
+    let _ = fs::remove_file(&path);
+    let status = status
+        .map_err(|err| PngmeError::Usage(tr!(
+            "no se pudo abrir el editor {}: {}",
+            "could not open the editor {}: {}",
+            editor,
+            err
+        )))?;
+    if !status.success() {
+        return Err(PngmeError::Usage(tr!(
+            "el editor {} terminó con error; no se guardó nada",
+            "the editor {} exited with an error; nothing was saved",
+            editor
+        )));
+    }
+    Ok(edited?)
+}
+
+pub fn export_chunk(args: ExportChunkArgs) -> Result<()> {
+    let png = read_png(&args.file_path)?;
+    let filter = args.chunk_type.as_deref().map(ChunkType::from_str).transpose()?;
+    // el offset de cada chunk en el archivo, para el manifest
+    let selected: Vec<(usize, usize, &Chunk)> = png
+        .chunks()
+        .iter()
+        .zip(png.chunk_spans())
+        .enumerate()
+        .filter(|(_, (chunk, _))| filter.is_none_or(|filter| filter == *chunk.chunk_type()))
+        .map(|(index, (chunk, span))| (index, span.offset, chunk))
+        .collect();
+    if selected.is_empty() {
+        return Err(png.chunk_not_found(args.chunk_type.as_deref().unwrap_or_default()));
+    }
+    let file_name = |index: usize, chunk: &Chunk| format!("{}-{}.bin", chunk.chunk_type(), index);
+
+    if !args.all {
+        let (index, _, chunk) = selected[0];
+        let path = match &args.out {
+            Some(path) => path.clone(),
+            None => default_dir().join(file_name(index, chunk)),
+        };
+        refuse_existing(&path, args.force)?;
+        fs::write(&path, chunk.data())?;
+        println!("{} ({} bytes)", path.display(), chunk.length());
+        return Ok(());
+    }
+
+    let dir = args.dir.clone().unwrap_or_else(default_dir);
+    let manifest_path = dir.join("manifest.tsv");
+    // se revisa todo antes de escribir nada, para no dejar una exportación a medias
+    refuse_existing(&manifest_path, args.force)?;
+    for (index, _, chunk) in &selected {
+        refuse_existing(&dir.join(file_name(*index, chunk)), args.force)?;
+    }
+    fs::create_dir_all(&dir)?;
+    let mut manifest = String::from("index\ttype\toffset\tlength\tcrc\tfile\n");
+    for (index, offset, chunk) in &selected {
+        let name = file_name(*index, chunk);
+        fs::write(dir.join(&name), chunk.data())?;
+        manifest.push_str(&format!(
+            "{}\t{}\t{}\t{}\t{:08x}\t{}\n",
+            index,
+            chunk.chunk_type(),
+            offset,
+            chunk.length(),
+            chunk.crc(),
+            name
+        ));
+    }
+    fs::write(&manifest_path, manifest)?;
+    println!("{}", tr!("{} chunks exportados en {}", "{} chunks exported to {}", selected.len(), dir.display()));
+    Ok(())
+}
+
+pub fn import_chunk(args: ImportChunkArgs) -> Result<()> {
+    let output = output_path(&args.output, &args.file_path)?;
+    let chunk_type = ChunkType::from_str(&args.chunk_type)?;
+    let mut png = read_png(&args.file_path)?;
+    let data = read_input(&args.from)?;
+    let exists = png.chunks().iter().any(|chunk| *chunk.chunk_type() == chunk_type);
+    let multiple = chunk_type.constraints().is_none_or(|constraints| constraints.multiple);
+    if exists && args.replace {
+        let length = data.len();
+        png.replace_chunk_data(&args.chunk_type, data)?;
+        let dropped = png.critical_changed(&chunk_type, args.keep_unsafe_to_copy);
+        status!(&output, "{}", tr!(
+            "Se reemplazaron los datos del chunk {} ({} bytes)",
+            "Replaced the data of chunk {} ({} bytes)",
+            chunk_type,
+            length
+        ));
+        save_png(&args.output, &args.file_path, &png)?;
+        report_unsafe_dropped(&output, &dropped);
+        return Ok(());
+    }
+    if exists && args.append && !multiple {
+        return Err(PngmeError::Usage(tr!(
+            "la especificación no permite más de un chunk {}; usá --replace",
+            "the specification allows only one {} chunk; use --replace",
+            chunk_type
+        )));
+    }
+    if exists && !args.append {
+        return Err(PngmeError::Usage(tr!(
+            "ya hay un chunk {}; usá --replace para cambiar sus datos o --append para agregar otro",
+            "there is already a {} chunk; use --replace to change its data or --append to add another one",
+            chunk_type
+        )));
+    }
+    let chunk = Chunk::new(chunk_type, data)?;
+    let length = chunk.length();
+    let index = match &args.position {
+        Some(position) => insert_at_position(&mut png, position, vec![chunk])?,
+        None => png.insert_chunk(chunk),
+    };
+    let dropped = png.critical_changed(&chunk_type, args.keep_unsafe_to_copy);
+    status!(&output, "{}", tr!(
+        "Se agregó {} en la posición {} ({} bytes)",
+        "Added {} at position {} ({} bytes)",
+        chunk_type,
+        index,
+        length
+    ));
+    save_png(&args.output, &args.file_path, &png)?;
+    report_unsafe_dropped(&output, &dropped);
+    Ok(())
+}
+
+// Posición y offset en el archivo del chunk número `nth` (desde 1) de ese tipo
+fn nth_chunk(png: &Png, chunk_type: &str, nth: usize) -> Result<(usize, usize)> {
+    let wanted = ChunkType::from_str(chunk_type)?;
+    if let Some((index, _)) = png.chunks_by_type(chunk_type).nth(nth - 1) {
+        return Ok((index, png.chunk_spans()[index].offset));
+    }
+    let found = png.chunks_by_type(chunk_type).count();
+    if found == 0 {
+        return Err(png.chunk_not_found(chunk_type));
+    }
+    Err(PngmeError::ChunkIndexOutOfRange { chunk_type: Some(wanted.to_string()), index: nth, count: found })
+}
+
+pub fn dump(args: DumpArgs) -> Result<()> {
+    let png = read_png(&args.file_path)?;
+    let (index, offset) = nth_chunk(&png, &args.chunk_type, args.nth)?;
+    let chunk = &png.chunks()[index];
+    println!("{}", tr!(
+        "{} #{}: offset {:#x}, {} bytes de datos desde {:#x}, CRC {:08x}",
+        "{} #{}: offset {:#x}, {} data bytes from {:#x}, CRC {:08x}",
+        color::chunk_type(chunk.chunk_type()),
+        index,
+        offset,
+        chunk.length(),
+        offset + 8,
+        chunk.crc()
+    ));
+    // length y tipo ocupan los primeros 8 bytes del chunk
+    print!("{}", hexdump(chunk.data(), offset + 8));
+    Ok(())
+}
+
+pub fn patch(args: PatchArgs) -> Result<()> {
+    let output = output_path(&args.output, &args.file_path)?;
+    let mut png = read_png(&args.file_path)?;
+    let (index, _) = nth_chunk(&png, &args.chunk_type, args.nth)?;
+    // el offset viene de --offset: pasarse es un error de uso, no un archivo dañado
+    let old = png.patch_chunk_at(index, args.offset, &args.bytes).map_err(|err| match err {
+        PngmeError::DataOffsetOutOfRange { .. } => PngmeError::Usage(err.to_string()),
+        err => err,
+    })?;
+    let old = to_hex(&old);
+    let patched = &png.chunks()[index];
+    let chunk_type = *patched.chunk_type();
+    status!(&output, "{}", tr!(
+        "{} #{} en {:#x}: {} -> {}; length {}, CRC {:08x}",
+        "{} #{} at {:#x}: {} -> {}; length {}, CRC {:08x}",
+        chunk_type,
+        index,
+        args.offset,
+        if old.is_empty() { text("(nada)", "(nothing)") } else { &old },
+        to_hex(&args.bytes),
+        patched.length(),
+        patched.crc()
+    ));
+    let dropped = png.critical_changed(&chunk_type, args.keep_unsafe_to_copy);
+    save_png(&args.output, &args.file_path, &png)?;
+    report_unsafe_dropped(&output, &dropped);
+    Ok(())
+}
+
+pub fn crc(args: CrcArgs) -> Result<()> {
+    let Some(path) = args.chunk else {
+        let chunk_type = args.chunk_type.ok_or_else(|| {
+            let reason = text("falta --type o --chunk", "--type or --chunk is missing");
+            PngmeError::Usage(reason.to_string())
+        })?;
+        let data = read_input(args.data_file.as_deref().unwrap_or(Path::new("-")))?;
+        let crc = Chunk::checksum(&chunk_type, &data);
+        println!("{:08x}  {}", crc, crc);
+        return Ok(());
+    };
+    let bytes = read_input(&path)?;
+    let chunk = ChunkRef::try_from(bytes.as_slice())?;
+    if bytes.len() > chunk.total_len() {
+        return Err(PngmeError::InvalidPayload(tr!(
+            "sobran {} bytes después del chunk {}",
+            "{} extra bytes after the {} chunk",
+            bytes.len() - chunk.total_len(),
+            chunk.chunk_type()
+        )));
+    }
+    println!("{}", tr!(
+        "{}, {} bytes: CRC guardado {:08x}, calculado {:08x}",
+        "{}, {} bytes: stored CRC {:08x}, computed {:08x}",
+        color::chunk_type(&chunk.chunk_type()),
+        chunk.length(),
+        chunk.crc(),
+        chunk.computed_crc()
+    ));
+    if !chunk.is_crc_valid() {
+        return Err(PngmeError::BadCrc { offset: chunk.total_len() - 4, expected: chunk.computed_crc(), actual: chunk.crc() });
+    }
+    Ok(())
+}
+
+pub fn describe_chunk_type(args: DescribeArgs) -> Result<()> {
+    let ct = args.chunk_type;
+    let letters = ct.bytes().map(|byte| byte as char);
+    println!("{}", color::chunk_type(&ct));
+    let properties = [
+        if ct.is_critical() {
+            text(
+                "crítico: un lector que no lo conoce tiene que rechazar la imagen",
+                "critical: a reader that does not know it must reject the image"
+            )
+        } else {
+            text(
+                "auxiliar: un lector que no lo conoce puede ignorarlo",
+                "ancillary: a reader that does not know it may ignore it"
+            )
+        },
+        if ct.is_public() {
+            text(
+                "público: lo define la especificación o un registro público",
+                "public: defined by the specification or a public registry"
+            )
+        } else {
+            text(
+                "privado: lo puede definir cualquiera para uso propio",
+                "private: anyone may define it for their own use"
+            )
+        },
+        if ct.is_reserved_bit_valid() {
+            text("bit reservado válido", "valid reserved bit")
+        } else {
+            text(
+                "bit reservado inválido: la especificación exige mayúscula; los lectores lo tratan como desconocido",
+                "invalid reserved bit: the specification requires uppercase; readers treat it as unknown"
+            )
+        },
+        if ct.is_safe_to_copy() {
+            text(
+                "seguro para copiar: un editor puede conservarlo aunque cambie la imagen",
+                "safe to copy: an editor may keep it even if it changes the image"
+            )
+        } else {
+            text(
+                "no es seguro para copiar: un editor que cambia la imagen tiene que descartarlo",
+                "unsafe to copy: an editor that changes the image must drop it"
+            )
+        },
+    ];
+    for (letter, property) in letters.iter().zip(properties) {
+        println!("  {}  {}", letter, property);
+    }
+    let registered = if ct.is_standard() {
+        text("sí, en la especificación PNG", "yes, in the PNG specification")
+    } else if ct.is_registered() {
+        text("sí, como extensión pública", "yes, as a public extension")
+    } else {
+        "no"
+    };
+    println!("{}", tr!("registrado: {}", "registered: {}", registered));
+    if let Some(constraints) = ct.constraints() {
+        let placement = match constraints.placement {
+            Placement::First => text("primero", "first"),
+            Placement::Last => text("último", "last"),
+            Placement::BeforePlte => text("antes de PLTE e IDAT", "before PLTE and IDAT"),
+            Placement::BetweenPlteAndIdat => text("después de PLTE y antes de IDAT", "after PLTE and before IDAT"),
+            Placement::BeforeIdat => text("antes de IDAT", "before IDAT"),
+            Placement::Idat => text("IDAT seguidos", "consecutive IDAT"),
+            Placement::AfterIdat => text("después de IDAT", "after IDAT"),
+            Placement::Anywhere => text("en cualquier lugar entre IHDR e IEND", "anywhere between IHDR and IEND"),
+        };
+        let multiple =
+            if constraints.multiple { text("puede repetirse", "may repeat") } else { text("uno solo", "only one") };
+        println!("{}", tr!("posición: {}; {}", "position: {}; {}", placement, multiple));
+    }
+    let usable = if ct.is_free_to_use() {
+        if ct.is_critical() {
+            tr!(
+                "sí, aunque al ser crítico los lectores que no lo conocen rechazan la imagen",
+                "yes, although being critical readers that do not know it reject the image"
+            )
+        } else {
+            tr!("sí", "yes")
+        }
+    } else if ct.is_registered() {
+        tr!("no, ya está registrado", "no, it is already registered")
+    } else if ct.is_public() {
+        tr!(
+            "no, los tipos públicos se registran; como auxiliar privado sería {}{}{}{}",
+            "no, public types are registered; as a private ancillary type it would be {}{}{}{}",
+            letters[0].to_ascii_lowercase(),
+            letters[1].to_ascii_lowercase(),
+            letters[2],
+            letters[3]
+        )
+    } else {
+        tr!("no, el bit reservado tiene que ser mayúscula", "no, the reserved bit must be uppercase")
+    };
+    println!("{}", tr!("se puede usar para datos propios: {}", "usable for custom data: {}", usable));
+    Ok(())
+}
+
+pub fn generate_chunk_type(args: GenerateArgs) -> Result<()> {
+    for _ in 0..args.count {
+        println!("{}", ChunkType::random_private(!args.unsafe_to_copy));
+    }
+    Ok(())
+}
+
+pub fn print_chunks(args: PrintArgs) -> Result<()> {
+    let png = read_png(&args.file_path)?;
+    print!("{:.*}", args.max_bytes, png);
+    Ok(())
+}
+
+// Índice, tipo, offset, largo (sumando los fragmentos de un mensaje), cantidad de fragmentos y CRC
+type ListRow = (usize, ChunkType, usize, usize, usize, u32);
+
+pub fn list(args: ListArgs) -> Result<()> {
+    let filter_type = args.chunk_type.as_deref().map(ChunkType::from_str).transpose()?;
+    let (listings, failed) =
+        read_each(&args.files, &args.batch, |path| Ok(list_rows(&read_png(path)?, filter_type, &args)))?;
+    let many = listings.len() + failed > 1;
+
+    #[cfg(feature = "serde")]
+    if args.json {
+        let rows = |rows: &[ListRow]| -> Vec<serde_json::Value> {
+            rows.iter()
+                .map(|(index, chunk_type, offset, length, fragments, crc)| {
+                    serde_json::json!({
+                        "index": index,
+                        "type": chunk_type.to_string(),
+                        "length": length,
+                        "crc": format!("{:08x}", crc),
+                        "offset": offset,
+                        "fragments": fragments,
+                    })
+                })
+                .collect()
+        };
+        // con varios archivos, un objeto por archivo; con uno, la lista de chunks como siempre
+        let json = if many {
+            let files: Vec<serde_json::Value> = listings
+                .iter()
+                .map(|(path, listing)| {
+                    serde_json::json!({ "file": path.display().to_string(), "chunks": rows(listing) })
+                })
+                .collect();
+            serde_json::to_string_pretty(&files)
+        } else {
+            serde_json::to_string_pretty(&listings.first().map(|(_, listing)| rows(listing)).unwrap_or_default())
+        };
+        println!("{}", json.map_err(std::io::Error::from)?);
+        return batch::finish(failed);
+    }
+
+    for (n, (path, rows)) in listings.iter().enumerate() {
+        batch::header(path, n, many);
+        println!("{:>5}  {:<4}  {:>10}  {:<8}  {:>10}", "#", text("tipo", "type"), "length", "crc", "offset");
+        for (index, chunk_type, offset, length, fragments, crc) in rows {
+            let note =
+                if *fragments > 1 { tr!("  ({} fragmentos)", "  ({} fragments)", fragments) } else { String::new() };
+            println!(
+                "{:>5}  {}  {:>10}  {:08x}  {:>10}{}",
+                index,
+                color::chunk_type(chunk_type),
+                length,
+                crc,
+                offset,
+                note
+            );
+        }
+    }
+    batch::finish(failed)
+}
+
+fn list_rows(png: &Png, filter_type: Option<ChunkType>, args: &ListArgs) -> Vec<ListRow> {
+    let mut rows = Vec::new();
+    // los fragmentos de un mensaje se muestran en una sola fila, en la posición del primero
+    let mut seen_fragments = HashSet::new();
+    for (index, (chunk, span)) in png.chunks().iter().zip(png.chunk_spans()).enumerate() {
+        let ct = chunk.chunk_type();
+        if filter_type.is_some_and(|filter| filter != *ct)
+            || (args.ancillary_only && ct.is_critical())
+            || (args.private_only && ct.is_public())
+        {
+            continue;
+        }
+        let (length, fragments) = match FragmentHeader::parse(chunk.data()) {
+            Some((header, _)) if !seen_fragments.insert((*ct, header.id)) => continue,
+            Some(_) => {
+                let fragments = png.fragments_of(chunk);
+                (fragments.iter().map(|(_, data)| data.len()).sum(), fragments.len())
+            }
+            None => (chunk.length() as usize, 1),
+        };
+        rows.push((index, *ct, span.offset, length, fragments, chunk.crc()));
+    }
+    rows
+}
+
+// Los comandos de sólo lectura aceptan varios archivos, directorios con --recursive y patrones glob. Cada
+// archivo se procesa en paralelo y los resultados quedan en el orden de los archivos; los que fallan ya se
+// mostraron y se cuentan para batch::finish
+fn read_each<T, F>(files: &[PathBuf], args: &BatchArgs, f: F) -> Result<(Vec<(PathBuf, T)>, usize)>
+where
+    T: Send,
+    F: Fn(&Path) -> Result<T> + Sync,
+{
+    let files = batch::expand(files, args.recursive, &args.exclude)?;
+    batch::run_each(&files, args.jobs, f)
+}
+
+// Cuenta el mensaje guardado tal cual en chunks nuevos; comprimir puede hacer entrar más
+pub fn capacity(args: CapacityArgs) -> Result<()> {
+    let bytes = read_input(&args.file_path)?;
+    let png = Png::try_from(bytes.as_slice())?;
+    let ancillary: Vec<&Chunk> = png.chunks().iter().filter(|chunk| !chunk.chunk_type().is_critical()).collect();
+    let ancillary_bytes: u64 = ancillary.iter().map(|chunk| chunk.length() as u64 + 12).sum();
+    let budget = (bytes.len() as f64 * args.max_growth / 100.0) as u64;
+    let max_payload = capacity_for(budget);
+
+    println!("{}", tr!("Tamaño:           {} bytes", "Size:             {} bytes", bytes.len()));
+    println!("{}", tr!(
+        "Auxiliares:       {} ({} bytes)",
+        "Ancillary:        {} ({} bytes)",
+        ancillary.len(),
+        ancillary_bytes
+    ));
+    println!("{}", tr!(
+        "Aumento máximo:   {}% ({} bytes)",
+        "Maximum growth:   {}% ({} bytes)",
+        args.max_growth,
+        budget
+    ));
+    println!("{}", tr!("Mensaje máximo:   {} bytes", "Maximum message:  {} bytes", max_payload));
+    #[cfg(feature = "crypto")]
+    let encrypted = max_payload.saturating_sub(Payload::ENCRYPTED_OVERHEAD as u64);
+    #[cfg(feature = "crypto")]
+    println!("{}", tr!("Con --encrypt:    {} bytes", "With --encrypt:   {} bytes", encrypted));
+    Ok(())
+}
+
+pub fn analyze(args: AnalyzeArgs) -> Result<()> {
+    let (reports, failed) = read_each(&args.files, &args.batch, |path| {
+        let analysis = read_png(path)?.analyze();
+        Ok(analysis.into_iter().filter(|chunk| !args.suspicious_only || chunk.is_suspicious()).collect::<Vec<_>>())
+    })?;
+    let many = reports.len() + failed > 1;
+
+    #[cfg(feature = "serde")]
+    if args.json {
+        let rows = |analysis: &[ChunkAnalysis]| -> Vec<serde_json::Value> {
+            analysis
+                .iter()
+                .map(|chunk| {
+                    serde_json::json!({
+                        "index": chunk.index,
+                        "type": chunk.chunk_type.to_string(),
+                        "length": chunk.length,
+                        "entropy": (chunk.entropy * 1000.0).round() / 1000.0,
+                        "suspicions": chunk.suspicions.iter().map(|s| s.to_string()).collect::<Vec<_>>(),
+                    })
+                })
+                .collect()
+        };
+        // con varios archivos, un objeto por archivo, como en list
+        let json = if many {
+            let files: Vec<serde_json::Value> = reports
+                .iter()
+                .map(|(path, analysis)| {
+                    serde_json::json!({ "file": path.display().to_string(), "chunks": rows(analysis) })
+                })
+                .collect();
+            serde_json::to_string_pretty(&files)
+        } else {
+            serde_json::to_string_pretty(&reports.first().map(|(_, analysis)| rows(analysis)).unwrap_or_default())
+        };
+        println!("{}", json.map_err(std::io::Error::from)?);
+        return batch::finish(failed);
+    }
+
+    for (n, (path, analysis)) in reports.iter().enumerate() {
+        batch::header(path, n, many);
+        println!("{:>5}  {:<4}  {:>10}  {:>8}", "#", text("tipo", "type"), "length", text("entropía", "entropy"));
+        for chunk in analysis {
+            let notes: Vec<String> = chunk.suspicions.iter().map(|s| s.to_string()).collect();
+            let notes = if notes.is_empty() { String::new() } else { format!("  <- {}", notes.join("; ")) };
+            println!(
+                "{:>5}  {:<4}  {:>10}  {:>8.3}{}",
+                chunk.index,
+                chunk.chunk_type,
+                chunk.length,
+                chunk.entropy,
+                notes
+            );
+        }
+        let suspicious = analysis.iter().filter(|chunk| chunk.is_suspicious()).count();
+        println!("{}", tr!("Chunks sospechosos: {}", "Suspicious chunks: {}", suspicious));
+    }
+    batch::finish(failed)
+}
+
+pub fn scan(args: ScanArgs) -> Result<()> {
+    let files = batch::expand(&args.files, args.batch.recursive, &args.batch.exclude)?;
+    let results = batch::run(&files, args.batch.jobs, |path| Ok(scan_bytes(&read_input(path)?, args.max_text)))?;
+    let (reports, failed) = batch::partition(results);
+
+    #[cfg(feature = "serde")]
+    if args.json {
+        let json: Vec<serde_json::Value> = reports
+            .iter()
+            .map(|(path, findings)| {
+                let summary: serde_json::Map<String, serde_json::Value> = FindingKind::ALL
+                    .iter()
+                    .map(|kind| {
+                        let count = findings.iter().filter(|finding| finding.kind == *kind).count();
+                        (kind.code().to_string(), count.into())
+                    })
+                    .collect();
+                let findings: Vec<serde_json::Value> = findings
+                    .iter()
+                    .map(|finding| {
+                        serde_json::json!({
+                            "kind": finding.kind.code(),
+                            "offset": finding.offset,
+                            "type": finding.chunk_type.map(|ct| ct.to_string()),
+                            "message": finding.message,
+                        })
+                    })
+                    .collect();
+                serde_json::json!({ "file": path.display().to_string(), "findings": findings, "summary": summary })
+            })
+            .collect();
+        let json = serde_json::to_string_pretty(&json).map_err(std::io::Error::from)?;
+        println!("{}", json);
+        batch::finish(failed)?;
+        return problems_found(&reports);
+    }
+
+    for (path, findings) in &reports {
+        if findings.is_empty() {
+            println!("{}: {}", path.display(), paint(text("nada sospechoso", "nothing suspicious"), Style::Good));
+            continue;
+        }
+        println!("{}: {}", path.display(), paint(tr!("{} hallazgos", "{} findings", findings.len()), Style::Warning));
+        for finding in findings {
+            let code = paint(finding.kind.code(), Style::Highlight);
+            println!("  {} [offset {}]: {}", code, finding.offset, finding.message);
+        }
+    }
+    batch::finish(failed)?;
+    problems_found(&reports)
+}
+
+fn problems_found(reports: &[(PathBuf, Vec<Finding>)]) -> Result<()> {
+    let count: usize = reports.iter().map(|(_, findings)| findings.len()).sum();
+    if count > 0 {
+        return Err(PngmeError::ProblemsFound { count, errors: false });
+    }
+    Ok(())
+}
+
+// Espera este tiempo sin cambios antes de procesar un archivo, para no leerlo a medio escribir
+#[cfg(feature = "watch")]
+const WATCH_DEBOUNCE: std::time::Duration = Duration::from_millis(500);
+
+#[cfg(feature = "watch")]
+pub fn watch(args: WatchArgs) -> Result<()> {
+    use notify::{EventKind, RecursiveMode, Watcher};
+    use std::sync::mpsc::{channel, RecvTimeoutError};
+    use std::time::Instant;
+
+    let watch_error = |err: notify::Error| PngmeError::Io(std::io::Error::other(err.to_string()));
+    let template = shell_words::split(&args.on_new)
+        .map_err(|err| PngmeError::Usage(tr!("comando inválido: {}", "invalid command: {}", err)))?;
+    if template.is_empty() || template[0] == "watch" {
+        return Err(PngmeError::Usage(tr!(
+            "comando inválido para --on-new: {:?}",
+            "invalid command for --on-new: {:?}",
+            args.on_new
+        )));
+    }
+
+    let (sender, receiver) = channel();
+    let mut watcher = notify::recommended_watcher(sender).map_err(watch_error)?;
+    let mode = if args.recursive { RecursiveMode::Recursive } else { RecursiveMode::NonRecursive };
+    watcher.watch(&args.dir, mode).map_err(watch_error)?;
+    println!("{}", tr!("Vigilando {} (Ctrl+C para terminar)", "Watching {} (Ctrl+C to stop)", args.dir.display()));
+
+    let mut pending: HashMap<PathBuf, Instant> = HashMap::new();
+    // fecha de modificación que dejó el comando, para no volver a procesar lo que él mismo escribió
+    let mut processed: HashMap<PathBuf, std::time::SystemTime> = HashMap::new();
+    loop {
+        match receiver.recv_timeout(WATCH_DEBOUNCE) {
+            Ok(Ok(event)) if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) => {
+                let pngs = event.paths.into_iter().filter(|path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("png")));
+                for path in pngs {
+                    pending.insert(path, Instant::now());
+                }
+            }
+            Ok(Ok(_)) | Err(RecvTimeoutError::Timeout) => {}
+            Ok(Err(err)) => eprintln!("Error: {}", err),
+            Err(RecvTimeoutError::Disconnected) => return Ok(()),
+        }
+
+        let ready: Vec<PathBuf> =
+            pending.iter().filter(|(_, time)| time.elapsed() >= WATCH_DEBOUNCE).map(|(path, _)| path.clone()).collect();
+        for path in ready {
+            pending.remove(&path);
+            let modified = || fs::metadata(&path).and_then(|metadata| metadata.modified()).ok();
+            if modified().is_none() || modified() == processed.get(&path).copied() {
+                continue;
+            }
+            match run_on_file(&template, &path) {
+                Ok(()) => println!("{}: {}", path.display(), text("listo", "done")),
+                Err(err) => eprintln!("Error: {}: {}", path.display(), err),
+            }
+            if let Some(time) = modified() {
+                processed.insert(path, time);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "watch")]
+fn run_on_file(template: &[String], path: &Path) -> Result<()> {
+    use clap::Parser;
+    let file = path.to_string_lossy();
+    let mut words: Vec<String> = template.iter().map(|word| word.replace("{}", &file)).collect();
+    if !template.iter().any(|word| word.contains("{}")) {
+        words.insert(1, file.into_owned());
+    }
+    let cli = crate::args::Cli::try_parse_from(std::iter::once("pngme".to_string()).chain(words))
+        .map_err(|err| PngmeError::Usage(err.to_string()))?;
+    crate::run(cli.command)
+}
+
+pub fn info(args: InfoArgs) -> Result<()> {
+    let (summaries, failed) = read_each(&args.files, &args.batch, |path| Ok(info_lines(&read_png(path)?)))?;
+    let many = summaries.len() + failed > 1;
+    for (n, (path, lines)) in summaries.iter().enumerate() {
+        batch::header(path, n, many);
+        for line in lines {
+            println!("{}", line);
+        }
+    }
+    batch::finish(failed)
+}
+
+fn info_lines(png: &Png) -> Vec<String> {
+    let has = |types: &[ChunkType]| {
+        png.chunks().iter().any(|chunk| types.contains(chunk.chunk_type()))
+    };
+    let yes_no = |value: bool| if value { text("sí", "yes") } else { "no" };
+    let mut lines = Vec::new();
+
+    match png.header_info() {
+        Ok(ihdr) => {
+            lines.push(tr!("Dimensiones:      {}x{}", "Dimensions:       {}x{}", ihdr.width, ihdr.height));
+            lines.push(match ihdr.color() {
+                Some(color) => tr!(
+                    "Tipo de color:    {} ({} bits)",
+                    "Color type:       {} ({} bits)",
+                    color,
+                    ihdr.bit_depth
+                ),
+                None => tr!(
+                    "Tipo de color:    desconocido ({})",
+                    "Color type:       unknown ({})",
+                    ihdr.color_type
+                ),
+            });
+            lines.push(tr!("Entrelazado:      {}", "Interlaced:       {}", yes_no(ihdr.is_interlaced())));
+        }
+        Err(err) => lines.push(tr!("IHDR inválido:    {}", "Invalid IHDR:     {}", err)),
+    }
+
+    let ancillary: Vec<&Chunk> = png
+        .chunks()
+        .iter()
+        .filter(|chunk| !chunk.chunk_type().is_critical())
+        .collect();
+    let ancillary_bytes: u64 = ancillary.iter().map(|chunk| chunk.length() as u64).sum();
+    let non_standard: Vec<String> = png
+        .chunks()
+        .iter()
+        .filter(|chunk| !chunk.chunk_type().is_registered())
+        .map(|chunk| chunk.chunk_type().to_string())
+        .collect();
+
+    lines.push(format!("Chunks:           {}", png.chunks().len()));
+    lines.push(tr!(
+        "Auxiliares:       {} ({} bytes)",
+        "Ancillary:        {} ({} bytes)",
+        ancillary.len(),
+        ancillary_bytes
+    ));
+    let text_chunks = has(&[ChunkType::tEXt, ChunkType::zTXt, ChunkType::iTXt]);
+    lines.push(tr!("Texto:            {}", "Text:             {}", yes_no(text_chunks)));
+    lines.push(tr!("Fecha (tIME):     {}", "Time (tIME):      {}", yes_no(has(&[ChunkType::tIME]))));
+    lines.push(tr!("Perfil ICC:       {}", "ICC profile:      {}", yes_no(has(&[ChunkType::iCCP]))));
+    let non_standard = if non_standard.is_empty() { "no".to_string() } else { non_standard.join(", ") };
+    lines.push(tr!("No estándar:      {}", "Non-standard:     {}", non_standard));
+    lines
+}
+
+// Con errores sale como un archivo que no se puede leer; si sólo hay avisos, como cualquier análisis con hallazgos
+pub fn verify(args: VerifyArgs) -> Result<()> {
+    let (reports, failed) = read_each(&args.files, &args.batch, |path| Ok(verify_bytes(&read_input(path)?)))?;
+    let many = reports.len() + failed > 1;
+    for (path, problems) in &reports {
+        for problem in problems {
+            let style = if problem.severity == Severity::Error { Style::Bad } else { Style::Warning };
+            match many {
+                true => println!("{}: {}", path.display(), paint(problem, style)),
+                false => println!("{}", paint(problem, style)),
+            }
+        }
+        if problems.is_empty() {
+            println!("{}: {}", path.display(), paint(text("sin problemas", "no problems"), Style::Good));
+        }
+    }
+    batch::finish(failed)?;
+    let problems = reports.iter().flat_map(|(_, problems)| problems);
+    match problems.clone().map(|problem| problem.severity).max() {
+        None => Ok(()),
+        Some(severity) => {
+            Err(PngmeError::ProblemsFound { count: problems.count(), errors: severity == Severity::Error })
+        }
+    }
+}
+
+// Lista de reglas permitidas: un código por línea, '#' para comentarios
+fn read_allow_file(path: &Path) -> Result<Vec<Rule>> {
+    let content = fs::read_to_string(path)?;
+    content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .map(|code| parse_rule(code).map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err).into()))
+        .collect()
+}
+
+// Falla si queda algún hallazgo que no esté permitido
+pub fn lint(args: LintArgs) -> Result<()> {
+    let mut allowed = args.allow;
+    match args.allow_file {
+        Some(path) => allowed.extend(read_allow_file(&path)?),
+        None if Path::new(".pngmelint").is_file() => allowed.extend(read_allow_file(Path::new(".pngmelint"))?),
+        None => {}
+    }
+
+    let (reports, failed) = read_each(&args.files, &args.batch, |path| {
+        let findings = lint_png(&read_png(path)?);
+        Ok(findings.into_iter().filter(|finding| !allowed.contains(&finding.rule)).collect::<Vec<_>>())
+    })?;
+    let many = reports.len() + failed > 1;
+    for (path, findings) in &reports {
+        for finding in findings {
+            match many {
+                true => println!("{}: {}", path.display(), paint(finding, Style::Warning)),
+                false => println!("{}", paint(finding, Style::Warning)),
+            }
+        }
+        if findings.is_empty() {
+            println!("{}: {}", path.display(), paint(text("sin hallazgos", "no findings"), Style::Good));
+        }
+    }
+    batch::finish(failed)?;
+    let count: usize = reports.iter().map(|(_, findings)| findings.len()).sum();
+    if count > 0 {
+        return Err(PngmeError::ProblemsFound { count, errors: false });
+    }
+    Ok(())
+}
+
+pub fn fix_order(args: FixOrderArgs) -> Result<()> {
+    let files = batch::expand(&args.files, args.batch.recursive, &args.batch.exclude)?;
+    let outputs = batch_outputs(&args.output, &files)?;
+    let (results, failed) = batch::run_each(&files, args.batch.jobs, |path| {
+        let mut png = read_png(path)?;
+        let moved = png.normalize_order();
+        if moved > 0 {
+            save_png(&args.output, path, &png)?;
+        }
+        Ok(moved)
+    })?;
+    for (path, moved) in results {
+        let output = &outputs[&path];
+        if moved == 0 {
+            status!(output, "{}", tr!("{}: el orden ya es válido", "{}: the order is already valid", path.display()));
+            continue;
+        }
+        status!(output, "{}", tr!(
+            "{} chunks cambiaron de lugar; guardado en {}",
+            "{} chunks moved; saved to {}",
+            moved,
+            output.display()
+        ));
+    }
+    batch::finish(failed)
+}
+
+// Destino de cada archivo de un lote que se modifica; -o sólo tiene sentido con uno. Sin -o ni --in-place
+// se avisa antes de procesar nada: después de escribir ya no se puede preguntar, porque el destino existe
+fn batch_outputs(output: &OutputArgs, files: &[PathBuf]) -> Result<HashMap<PathBuf, PathBuf>> {
+    if output.output.is_some() && files.len() != 1 {
+        let reason = text("-o sólo se puede usar con un archivo", "-o can only be used with one file");
+        return Err(PngmeError::Usage(reason.to_string()));
+    }
+    files.iter().map(|path| Ok((path.clone(), output_path(output, path)?))).collect()
+}
+
+pub fn strip(args: StripArgs) -> Result<()> {
+    let files = batch::expand(&args.files, args.batch.recursive, &args.batch.exclude)?;
+    let outputs = batch_outputs(&args.output, &files)?;
+    // el preset de la configuración sólo cuando no se eligió qué borrar
+    let preset = match args.preset {
+        None if args.all || !args.types.is_empty() => None,
+        preset => preset.or(config::get().strip_preset),
+    };
+    let filter = if let Some(preset) = preset {
+        preset.filter()
+    } else if args.all {
+        StripFilter::all_ancillary()
+    } else if args.types.is_empty() {
+        StripFilter::metadata()
+    } else {
+        StripFilter::only(&args.types)
+    };
+    let filter = filter.exclude(&args.keep);
+    let results = batch::run(&files, args.batch.jobs, |path| {
+        let mut png = read_png(path)?;
+        let removed = png.strip(&filter);
+        if !removed.is_empty() {
+            save_png(&args.output, path, &png)?;
+        }
+        Ok(removed)
+    })?;
+    let (results, failed) = batch::partition(results);
+    for (path, removed) in results {
+        let output = &outputs[&path];
+        if removed.is_empty() {
+            status!(output, "{}", tr!("{}: no hay chunks para borrar", "{}: no chunks to remove", path.display()));
+        }
+        for chunk in &removed {
+            status!(output, "{}", tr!(
+                "{}: se eliminó {} ({} bytes)",
+                "{}: removed {} ({} bytes)",
+                path.display(),
+                chunk.chunk_type(),
+                chunk.length()
+            ));
+        }
+    }
+    batch::finish(failed)
+}
+
+pub fn diff(args: DiffArgs) -> Result<()> {
+    let old = read_png(&args.old)?;
+    let new = read_png(&args.new)?;
+    let diffs = diff_png(&old, &new);
+
+    #[cfg(feature = "serde")]
+    if args.json {
+        let summary = |summary: Option<pngme::diff::ChunkSummary>| {
+            summary.map(|s| serde_json::json!({ "index": s.index, "length": s.length, "crc": format!("{:08x}", s.crc) }))
+        };
+        let rows: Vec<serde_json::Value> = diffs
+            .iter()
+            .map(|d| {
+                serde_json::json!({
+                    "change": d.kind.name(),
+                    "type": d.chunk_type.to_string(),
+                    "old": summary(d.old),
+                    "new": summary(d.new),
+                })
+            })
+            .collect();
+        let json = serde_json::to_string_pretty(&rows).map_err(std::io::Error::from)?;
+        println!("{}", json);
+        return Ok(());
+    }
+
+    if diffs.is_empty() {
+        println!("{}", text("sin diferencias", "no differences"));
+    }
+    for d in &diffs {
+        let style = match d.kind {
+            DiffKind::Added => Style::Good,
+            DiffKind::Removed => Style::Bad,
+            DiffKind::Modified => Style::Warning,
+        };
+        println!("{}", paint(d, style));
+    }
+    Ok(())
+}
+
+pub fn copy_chunks(args: CopyChunksArgs) -> Result<()> {
+    let output = output_path(&args.output, &args.target)?;
+    let source = read_png(&args.source)?;
+    let mut target = read_png(&args.target)?;
+    let report = target.merge_from(&source, |ct| args.types.is_empty() || args.types.contains(ct));
+    for chunk_type in &report.skipped {
+        if !chunk_type.is_critical() {
+            eprintln!("{}", tr!(
+                "Se omitió {}: no es seguro copiarlo entre imágenes distintas",
+                "Skipped {}: it is not safe to copy between different images",
+                chunk_type
+            ));
+        }
+    }
+    if report.copied.is_empty() {
+        status!(&output, "{}", text("No se copió ningún chunk", "No chunk was copied"));
+        return Ok(());
+    }
+    save_png(&args.output, &args.target, &target)?;
+    status!(&output, "{}", tr!(
+        "Se copiaron {} chunks a {}",
+        "Copied {} chunks to {}",
+        report.copied.len(),
+        output.display()
+    ));
+    Ok(())
+}
+
+// Mismo formato que sha256sum
+pub fn hash(args: HashArgs) -> Result<()> {
+    let files = batch::expand(&args.files, args.batch.recursive, &args.batch.exclude)?;
+    let results = batch::run(&files, args.batch.jobs, |path| {
+        if args.content {
+            Ok(read_png(path)?.content_hash())
+        } else {
+            Ok(sha256(&read_input(path)?))
+        }
+    })?;
+    let (digests, failed) = batch::partition(results);
+    for (path, digest) in digests {
+        println!("{}  {}", to_hex(&digest), path.display());
+    }
+    batch::finish(failed)
+}
+
+#[cfg(feature = "keyring")]
+pub fn keyring_set(args: KeyringSetArgs) -> Result<()> {
+    let secret = match (args.keyfile, args.password) {
+        (Some(path), _) => read_keyfile(&path)?,
+        (None, Some(password)) => Secret::from(password),
+        (None, None) => secret_or_prompt(&SecretArgs::default(), true)?,
+    };
+    store_secret(&args.id, secret.expose())?;
+    println!("{}", tr!("Clave guardada: {}", "Key stored: {}", args.id));
+    Ok(())
+}
+
+#[cfg(feature = "keyring")]
+pub fn keyring_delete(args: KeyringDeleteArgs) -> Result<()> {
+    delete_secret(&args.id)?;
+    println!("{}", tr!("Clave borrada: {}", "Key deleted: {}", args.id));
+    Ok(())
+}
+
+#[cfg(feature = "crypto")]
+pub fn sign(args: SignArgs) -> Result<()> {
+    let output = output_path(&args.output, &args.file_path)?;
+    let mut png = read_png(&args.file_path)?;
+    let key = read_signing_key(&args.key)?;
+    png.sign(&key, &args.types)?;
+    save_png(&args.output, &args.file_path, &png)?;
+    status!(&output, "{}", tr!("Firmado: {}", "Signed: {}", output.display()));
+    Ok(())
+}
+
+#[cfg(feature = "crypto")]
+pub fn verify_sig(args: VerifySigArgs) -> Result<()> {
+    let png = read_png(&args.file_path)?;
+    let key = read_verifying_key(&args.pubkey)?;
+    let covered = png.verify_signature(&key)?;
+    if covered.is_empty() {
+        println!("{}", tr!(
+            "{}: firma válida (cubre todos los chunks)",
+            "{}: valid signature (covers every chunk)",
+            args.file_path.display()
+        ));
+    } else {
+        let types: Vec<String> = covered.iter().map(ChunkType::to_string).collect();
+        println!("{}", tr!(
+            "{}: firma válida (cubre {})",
+            "{}: valid signature (covers {})",
+            args.file_path.display(),
+            types.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+pub fn repair(args: RepairArgs) -> Result<()> {
+    let bytes = read_input(&args.file_path)?;
+    let (png, report) = repair_bytes(&bytes, args.add_iend)?;
+    if args.dry_run {
+        print!("{}", report);
+        print!("{}", preview(&args.file_path, Some(&bytes), &png));
+        return Ok(());
+    }
+    let output = args.output.unwrap_or_else(|| default_copy_path(&args.file_path, "repaired"));
+    refuse_existing(&output, args.force)?;
+    write_png(&output, &png)?;
+    status!(&output, "{}", report.to_string().trim_end());
+    if !is_stdio(&output) {
+        println!("{}", tr!("Copia reparada en {}", "Repaired copy in {}", output.display()));
+    }
+    Ok(())
+}
+
+// <archivo>.SUFIJO.png al lado del original; si el PNG vino por stdin, la copia sale por stdout
+fn default_copy_path(input: &Path, suffix: &str) -> PathBuf {
+    if is_stdio(input) {
+        return input.to_path_buf();
+    }
+    input.with_extension(format!("{}.png", suffix))
+}
+
+pub fn recover(args: RecoverArgs) -> Result<()> {
+    let bytes = read_input(&args.file_path)?;
+    let (png, report) = Png::from_bytes_partial(&bytes)?;
+    let output = args.output.unwrap_or_else(|| default_copy_path(&args.file_path, "recovered"));
+    refuse_existing(&output, args.force)?;
+    write_png(&output, &png)?;
+    status!(&output, "{}", report.to_string().trim_end());
+    if !is_stdio(&output) {
+        println!("{}", tr!("Archivo recuperado en {}", "Recovered file in {}", output.display()));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+pub fn export(args: crate::args::ExportArgs) -> Result<()> {
+    if let Some(path) = &args.json_path {
+        refuse_existing(path, args.force)?;
+    }
+    let png = read_png(&args.file_path)?;
+    let json = serde_json::to_string_pretty(&png).map_err(std::io::Error::from)?;
+    match args.json_path {
+        Some(path) => fs::write(path, json + "\n")?,
+        None => println!("{}", json),
+    }
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+pub fn import(args: crate::args::ImportArgs) -> Result<()> {
+    refuse_existing(&args.output_file, args.force)?;
+    let json = fs::read_to_string(&args.json_path)?;
+    let png: Png = serde_json::from_str(&json).map_err(std::io::Error::from)?;
+    write_png(&args.output_file, &png)
+}
@@ -0,0 +1,184 @@
+use std::io::{Read, Write};
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression as Level;
+use crate::{tr, PngmeError, Result};
+#[cfg(not(feature = "zstd"))]
+use crate::locale::text;
+
+// Método de compresión del mensaje, tal como se guarda en el envoltorio de Payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Deflate,
+    // nivel 1-22; sólo importa al comprimir, no se guarda en el envoltorio
+    Zstd(i32),
+}
+
+impl Compression {
+    pub const ZSTD_DEFAULT_LEVEL: i32 = 3;
+
+    pub fn code(&self) -> u8 {
+        match self {
+            Compression::None => 0,
+            Compression::Deflate => 1,
+            Compression::Zstd(_) => 2,
+        }
+    }
+
+    pub fn from_code(code: u8) -> Result<Compression> {
+        match code {
+            0 => Ok(Compression::None),
+            1 => Ok(Compression::Deflate),
+            2 => Ok(Compression::Zstd(Compression::ZSTD_DEFAULT_LEVEL)),
+            _ => Err(PngmeError::InvalidPayload(tr!(
+                "método de compresión desconocido: {}",
+                "unknown compression method: {}",
+                code
+            ))),
+        }
+    }
+
+    // "deflate", "zstd" o "zstd:19"
+    pub fn from_name(name: &str) -> Option<Compression> {
+        let name = name.to_ascii_lowercase();
+        let (method, level) = match name.split_once(':') {
+            Some((method, level)) => (method, Some(level.parse::<i32>().ok()?)),
+            None => (name.as_str(), None),
+        };
+        match (method, level) {
+            ("none", None) => Some(Compression::None),
+            ("deflate" | "zlib", None) => Some(Compression::Deflate),
+            ("zstd", None) => Some(Compression::Zstd(Compression::ZSTD_DEFAULT_LEVEL)),
+            ("zstd", Some(level)) if (1..=22).contains(&level) => Some(Compression::Zstd(level)),
+            _ => None,
+        }
+    }
+
+    pub fn compress(&self, data: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Compression::None => Ok(data.to_vec()),
+            Compression::Deflate => deflate(data),
+            Compression::Zstd(level) => zstd_compress(data, *level),
+        }
+    }
+
+    // Falla con DecompressionLimit si el resultado pasa de `limit` bytes, para que un mensaje de pocos KB
+    // no agote la memoria
+    pub fn decompress(&self, data: &[u8], limit: usize) -> Result<Vec<u8>> {
+        let out = match self {
+            Compression::None => Some(data.to_vec()),
+            Compression::Deflate => inflate_limited(data, limit)?,
+            Compression::Zstd(_) => zstd_decompress(data, limit)?,
+        };
+        out.ok_or(PngmeError::DecompressionLimit { chunk_type: None, limit })
+    }
+}
+
+#[cfg(feature = "zstd")]
+fn zstd_compress(data: &[u8], level: i32) -> Result<Vec<u8>> {
+    Ok(zstd::encode_all(data, level)?)
+}
+
+// Devuelve None si se pasa de `limit`, como inflate_limited
+#[cfg(feature = "zstd")]
+fn zstd_decompress(data: &[u8], limit: usize) -> Result<Option<Vec<u8>>> {
+    let mut out = Vec::new();
+    zstd::stream::read::Decoder::new(data)?.take((limit as u64).saturating_add(1)).read_to_end(&mut out)?;
+    Ok((out.len() <= limit).then_some(out))
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_compress(_data: &[u8], _level: i32) -> Result<Vec<u8>> {
+    Err(zstd_unavailable())
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_decompress(_data: &[u8], _limit: usize) -> Result<Option<Vec<u8>>> {
+    Err(zstd_unavailable())
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_unavailable() -> PngmeError {
+    let reason = text(
+        "pngme se compiló sin soporte para zstd (feature \"zstd\")",
+        "pngme was built without zstd support (feature \"zstd\")"
+    );
+    PngmeError::InvalidPayload(reason.to_string())
+}
+
+// Flujo zlib (método de compresión 0 de la especificación PNG)
+pub fn deflate(data: &[u8]) -> Result<Vec<u8>> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Level::default());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+pub fn inflate(data: &[u8]) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    ZlibDecoder::new(data).read_to_end(&mut out)?;
+    Ok(out)
+}
+
+// Como inflate, pero deja de leer pasados `limit` bytes: unos pocos KB de zlib pueden descomprimirse a gigas.
+// Devuelve None si se pasó
+pub fn inflate_limited(data: &[u8], limit: usize) -> Result<Option<Vec<u8>>> {
+    let mut out = Vec::new();
+    ZlibDecoder::new(data).take((limit as u64).saturating_add(1)).read_to_end(&mut out)?;
+    Ok((out.len() <= limit).then_some(out))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deflate_round_trip() {
+        let data = b"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".repeat(10);
+        let compressed = deflate(&data).unwrap();
+        assert!(compressed.len() < data.len());
+        assert_eq!(inflate(&compressed).unwrap(), data);
+        assert!(inflate(b"no es zlib").is_err());
+    }
+
+    #[test]
+    fn test_inflate_limited() {
+        let compressed = deflate(&[0; 1000]).unwrap();
+        assert_eq!(inflate_limited(&compressed, 1000).unwrap().unwrap().len(), 1000);
+        assert!(inflate_limited(&compressed, 999).unwrap().is_none());
+        assert!(inflate_limited(b"no es zlib", 1000).is_err());
+        assert_eq!(inflate_limited(&compressed, usize::MAX).unwrap().unwrap().len(), 1000);
+    }
+
+    #[test]
+    fn test_decompress_bomb() {
+        // 64 MiB de ceros caben en unos 64 KB de zlib
+        let compressed = deflate(&vec![0; 64 << 20]).unwrap();
+        assert!(compressed.len() < 128 << 10);
+        let result = Compression::Deflate.decompress(&compressed, 1 << 20);
+        assert!(matches!(result, Err(PngmeError::DecompressionLimit { chunk_type: None, limit: 0x100000 })));
+        assert_eq!(Compression::Deflate.decompress(&deflate(b"hola").unwrap(), 4).unwrap(), b"hola");
+        assert!(Compression::None.decompress(b"hola", 3).is_ok());
+    }
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(Compression::from_name("Deflate"), Some(Compression::Deflate));
+        assert_eq!(Compression::from_name("zstd"), Some(Compression::Zstd(3)));
+        assert_eq!(Compression::from_name("zstd:19"), Some(Compression::Zstd(19)));
+        assert_eq!(Compression::from_name("zstd:40"), None);
+        assert_eq!(Compression::from_name("deflate:9"), None);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_zstd_round_trip() {
+        let data = b"zstd zstd zstd zstd ".repeat(50);
+        let compression = Compression::Zstd(19);
+        let compressed = compression.compress(&data).unwrap();
+        assert!(compressed.len() < data.len());
+        assert_eq!(compression.decompress(&compressed, data.len()).unwrap(), data);
+        let bomb = compression.compress(&vec![0; 64 << 20]).unwrap();
+        assert!(matches!(compression.decompress(&bomb, 1 << 20), Err(PngmeError::DecompressionLimit { .. })));
+    }
+}
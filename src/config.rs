@@ -0,0 +1,205 @@
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use pngme::compression::Compression;
+use pngme::locale::text;
+use pngme::strip::StripPreset;
+#[cfg(feature = "config")]
+use pngme::tr;
+use pngme::Result;
+#[cfg(feature = "config")]
+use std::collections::BTreeMap;
+use crate::args::{parse_color, parse_compression, parse_preset};
+use crate::color::ColorChoice;
+
+// Valores por defecto leídos de ~/.config/pngme/config.toml (o del archivo de --config) y de las
+// variables PNGME_*, que tienen prioridad sobre el archivo; lo que se pasa en la línea de comandos
+// siempre tiene prioridad sobre ambos
+#[derive(Debug, Default)]
+pub struct Config {
+    pub chunk_type: Option<String>,
+    pub output_dir: Option<PathBuf>,
+    pub compress: Option<Compression>,
+    pub color: Option<ColorChoice>,
+    #[cfg(feature = "keyring")]
+    pub key_id: Option<String>,
+    #[cfg(feature = "age")]
+    pub recipients: Vec<String>,
+    pub strip_preset: Option<StripPreset>,
+    pub backup: Option<String>,
+}
+
+// Tal como está escrito en el archivo, antes de validar los valores. Cada [profile.NOMBRE] tiene las
+// mismas claves y, elegido con --profile, reemplaza a las de afuera
+#[cfg(feature = "config")]
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+struct RawConfig {
+    chunk_type: Option<String>,
+    output_dir: Option<PathBuf>,
+    compress: Option<String>,
+    color: Option<String>,
+    key_id: Option<String>,
+    recipients: Vec<String>,
+    strip_preset: Option<String>,
+    backup: Option<String>,
+    profile: BTreeMap<String, RawConfig>,
+}
+
+#[cfg(feature = "config")]
+impl RawConfig {
+    // Lo que el perfil no define se toma de afuera
+    fn over(self, base: RawConfig) -> RawConfig {
+        RawConfig {
+            chunk_type: self.chunk_type.or(base.chunk_type),
+            output_dir: self.output_dir.or(base.output_dir),
+            compress: self.compress.or(base.compress),
+            color: self.color.or(base.color),
+            key_id: self.key_id.or(base.key_id),
+            recipients: if self.recipients.is_empty() { base.recipients } else { self.recipients },
+            strip_preset: self.strip_preset.or(base.strip_preset),
+            backup: self.backup.or(base.backup),
+            profile: BTreeMap::new(),
+        }
+    }
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+pub fn init(config: Config) {
+    let _ = CONFIG.set(config);
+}
+
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+// $XDG_CONFIG_HOME/pngme/config.toml, o ~/.config/pngme/config.toml; en Windows, dentro de %APPDATA%
+#[cfg(feature = "config")]
+fn default_path() -> Option<PathBuf> {
+    let dir = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")));
+    #[cfg(windows)]
+    let dir = dir.or_else(|| std::env::var_os("APPDATA").map(PathBuf::from));
+    dir.map(|dir| dir.join("pngme").join("config.toml"))
+}
+
+fn invalid(path: &Path, message: impl std::fmt::Display) -> pngme::PngmeError {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{}: {}", path.display(), message)).into()
+}
+
+fn no_config_for_profile() -> pngme::PngmeError {
+    let message = text("--profile necesita un archivo de configuración", "--profile needs a configuration file");
+    std::io::Error::new(std::io::ErrorKind::NotFound, message).into()
+}
+
+// La configuración del archivo con las variables de entorno encima. PNGME_CONFIG y PNGME_PROFILE
+// valen como --config y --profile
+pub fn load(explicit: Option<&Path>, profile: Option<&str>) -> Result<Config> {
+    let env_config = var("PNGME_CONFIG").map(PathBuf::from);
+    let env_profile = var("PNGME_PROFILE");
+    let mut config = load_file(explicit.or(env_config.as_deref()), profile.or(env_profile.as_deref()))?;
+    apply_env(&mut config)?;
+    Ok(config)
+}
+
+// Una variable definida y no vacía
+fn var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+fn apply_env(config: &mut Config) -> Result<()> {
+    let invalid_var = |name: &str, message: String| -> pngme::PngmeError {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("{}: {}", name, message)).into()
+    };
+    if let Some(chunk_type) = var("PNGME_CHUNK_TYPE") {
+        config.chunk_type = Some(chunk_type);
+    }
+    if let Some(dir) = var("PNGME_OUTPUT_DIR") {
+        config.output_dir = Some(PathBuf::from(dir));
+    }
+    if let Some(name) = var("PNGME_COMPRESS") {
+        config.compress = Some(parse_compression(&name).map_err(|err| invalid_var("PNGME_COMPRESS", err))?);
+    }
+    if let Some(name) = var("PNGME_COLOR") {
+        config.color = Some(parse_color(&name).map_err(|err| invalid_var("PNGME_COLOR", err))?);
+    }
+    #[cfg(feature = "keyring")]
+    if let Some(id) = var("PNGME_KEY_ID") {
+        config.key_id = Some(id);
+    }
+    #[cfg(feature = "age")]
+    if let Some(recipients) = var("PNGME_RECIPIENTS") {
+        config.recipients = recipients.split(',').map(|r| r.trim().to_string()).collect();
+    }
+    if let Some(name) = var("PNGME_STRIP_PRESET") {
+        config.strip_preset = Some(parse_preset(&name).map_err(|err| invalid_var("PNGME_STRIP_PRESET", err))?);
+    }
+    if let Some(suffix) = var("PNGME_BACKUP") {
+        config.backup = Some(suffix);
+    }
+    // PNGME_NO_BACKUP=0 es lo mismo que no definirla
+    if var("PNGME_NO_BACKUP").is_some_and(|value| value != "0") {
+        config.backup = None;
+    }
+    Ok(())
+}
+
+// El archivo de --config tiene que existir; el de siempre es opcional, salvo que se pida un perfil
+#[cfg(feature = "config")]
+fn load_file(explicit: Option<&Path>, profile: Option<&str>) -> Result<Config> {
+    let path = match explicit {
+        Some(path) => path.to_path_buf(),
+        None => match default_path() {
+            Some(path) if path.is_file() => path,
+            _ if profile.is_some() => return Err(no_config_for_profile()),
+            _ => return Ok(Config::default()),
+        },
+    };
+    let text = std::fs::read_to_string(&path).map_err(|err| invalid(&path, err))?;
+    let mut raw: RawConfig = toml::from_str(&text).map_err(|err| invalid(&path, err))?;
+    if let Some(name) = profile {
+        let selected = raw.profile.remove(name).ok_or_else(|| {
+            let names: Vec<&str> = raw.profile.keys().map(String::as_str).collect();
+            invalid(&path, tr!(
+                "no hay ningún perfil {} (definidos: {})",
+                "there is no profile {} (defined: {})",
+                name,
+                names.join(", ")
+            ))
+        })?;
+        if !selected.profile.is_empty() {
+            return Err(invalid(&path, tr!(
+                "el perfil {} no puede tener otros perfiles adentro",
+                "profile {} cannot contain other profiles",
+                name
+            )));
+        }
+        raw = selected.over(raw);
+    }
+    Ok(Config {
+        chunk_type: raw.chunk_type,
+        output_dir: raw.output_dir,
+        compress: raw.compress.map(|name| parse_compression(&name)).transpose().map_err(|err| invalid(&path, err))?,
+        color: raw.color.map(|name| parse_color(&name)).transpose().map_err(|err| invalid(&path, err))?,
+        #[cfg(feature = "keyring")]
+        key_id: raw.key_id,
+        #[cfg(feature = "age")]
+        recipients: raw.recipients,
+        strip_preset: raw.strip_preset.map(|name| parse_preset(&name)).transpose().map_err(|err| invalid(&path, err))?,
+        backup: raw.backup,
+    })
+}
+
+#[cfg(not(feature = "config"))]
+fn load_file(explicit: Option<&Path>, profile: Option<&str>) -> Result<Config> {
+    match (explicit, profile) {
+        (Some(path), _) => Err(invalid(path, text(
+            "pngme se compiló sin la feature config",
+            "pngme was built without the config feature"
+        ))),
+        (None, Some(_)) => Err(no_config_for_profile()),
+        (None, None) => Ok(Config::default()),
+    }
+}
@@ -0,0 +1,236 @@
+use std::path::Path;
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload as AeadPayload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use crate::secret::Secret;
+use crate::{tr, PngmeError, Result};
+use crate::locale::text;
+
+pub const SALT_LEN: usize = 16;
+pub const NONCE_LEN: usize = 24;
+pub const KEY_LEN: usize = 32;
+pub const TAG_LEN: usize = 32;
+
+pub fn random_bytes<const N: usize>() -> Result<[u8; N]> {
+    let mut bytes = [0; N];
+    fill_random(&mut bytes)?;
+    Ok(bytes)
+}
+
+pub fn fill_random(bytes: &mut [u8]) -> Result<()> {
+    getrandom::fill(bytes).map_err(|err| std::io::Error::other(err.to_string()))?;
+    Ok(())
+}
+
+// Material de clave de un archivo: si el contenido (sin espacios al final) es base64 válido se
+// decodifica, si no se usan los bytes tal cual
+pub fn read_keyfile(path: &Path) -> Result<Secret<Vec<u8>>> {
+    let bytes = Secret::new(std::fs::read(path)?);
+    let key = match BASE64.decode(bytes.expose().trim_ascii_end()) {
+        Ok(decoded) => Secret::new(decoded),
+        Err(_) => bytes,
+    };
+    if key.expose().is_empty() {
+        return Err(PngmeError::InvalidKey(tr!(
+            "{}: el archivo de clave está vacío",
+            "{}: the key file is empty",
+            path.display()
+        )));
+    }
+    Ok(key)
+}
+
+// Costo de Argon2id. Se guarda en el encabezado del mensaje para poder subirlo sin romper
+// los mensajes viejos; al leer se limita para que un archivo malicioso no agote la memoria
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfParams {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u8,
+}
+
+impl KdfParams {
+    pub const ENCODED_LEN: usize = 9;
+    pub const MAX_MEMORY_KIB: u32 = 1 << 20;
+    pub const MAX_ITERATIONS: u32 = 100;
+
+    // Los parámetros que usaban los mensajes cifrados antes de guardarlos en el encabezado
+    pub const LEGACY: KdfParams = KdfParams {
+        memory_kib: Params::DEFAULT_M_COST,
+        iterations: Params::DEFAULT_T_COST,
+        parallelism: 1,
+    };
+
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |reason: String| Err(PngmeError::InvalidPayload(reason));
+        if !(8..=KdfParams::MAX_MEMORY_KIB).contains(&self.memory_kib) {
+            return invalid(tr!(
+                "memoria de Argon2 fuera de rango (8-{} KiB): {}",
+                "Argon2 memory out of range (8-{} KiB): {}",
+                KdfParams::MAX_MEMORY_KIB,
+                self.memory_kib
+            ));
+        }
+        if !(1..=KdfParams::MAX_ITERATIONS).contains(&self.iterations) {
+            return invalid(tr!(
+                "iteraciones de Argon2 fuera de rango (1-{}): {}",
+                "Argon2 iterations out of range (1-{}): {}",
+                KdfParams::MAX_ITERATIONS,
+                self.iterations
+            ));
+        }
+        if !(1..=16).contains(&self.parallelism) || self.memory_kib < 8 * self.parallelism as u32 {
+            return invalid(tr!(
+                "paralelismo de Argon2 inválido: {}",
+                "invalid Argon2 parallelism: {}",
+                self.parallelism
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> [u8; KdfParams::ENCODED_LEN] {
+        let mut bytes = [0; KdfParams::ENCODED_LEN];
+        bytes[..4].copy_from_slice(&self.memory_kib.to_be_bytes());
+        bytes[4..8].copy_from_slice(&self.iterations.to_be_bytes());
+        bytes[8] = self.parallelism;
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8; KdfParams::ENCODED_LEN]) -> Result<KdfParams> {
+        let params = KdfParams {
+            memory_kib: u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            iterations: u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
+            parallelism: bytes[8],
+        };
+        params.validate()?;
+        Ok(params)
+    }
+}
+
+// 64 MiB y 3 pasadas, la segunda recomendación del RFC 9106
+impl Default for KdfParams {
+    fn default() -> KdfParams {
+        KdfParams { memory_kib: 64 * 1024, iterations: 3, parallelism: 1 }
+    }
+}
+
+// Clave de 256 bits derivada de la contraseña con Argon2id
+pub fn derive_key(password: &[u8], salt: &[u8], params: &KdfParams) -> Result<Secret<[u8; KEY_LEN]>> {
+    params.validate()?;
+    let kdf_error = |err: argon2::Error| PngmeError::InvalidPayload(tr!(
+        "no se pudo derivar la clave: {}",
+        "the key could not be derived: {}",
+        err
+    ));
+    let argon_params = Params::new(params.memory_kib, params.iterations, params.parallelism as u32, Some(KEY_LEN))
+        .map_err(kdf_error)?;
+    let mut key = Secret::new([0; KEY_LEN]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, argon_params)
+        .hash_password_into(password, salt, key.expose_mut())
+        .map_err(kdf_error)?;
+    Ok(key)
+}
+
+// XChaCha20-Poly1305; `aad` se autentica pero no se cifra
+pub fn encrypt(key: &[u8; KEY_LEN], nonce: &[u8; NONCE_LEN], aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+    let cipher = XChaCha20Poly1305::new(&Key::from(*key));
+    cipher
+        .encrypt(&XNonce::from(*nonce), AeadPayload { msg: plaintext, aad })
+        .map_err(|_| {
+            let reason = text("el mensaje es demasiado largo para cifrarlo", "the message is too long to encrypt");
+            PngmeError::InvalidPayload(reason.to_string())
+        })
+}
+
+pub fn decrypt(key: &[u8; KEY_LEN], nonce: &[u8; NONCE_LEN], aad: &[u8], ciphertext: &[u8]) -> Result<Secret<Vec<u8>>> {
+    let cipher = XChaCha20Poly1305::new(&Key::from(*key));
+    cipher
+        .decrypt(&XNonce::from(*nonce), AeadPayload { msg: ciphertext, aad })
+        .map(Secret::new)
+        .map_err(|_| PngmeError::DecryptionFailed)
+}
+
+// HMAC-SHA256 de las partes concatenadas
+pub fn hmac_sha256(key: &[u8; KEY_LEN], parts: &[&[u8]]) -> [u8; TAG_LEN] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC acepta claves de cualquier largo");
+    for part in parts {
+        mac.update(part);
+    }
+    mac.finalize().into_bytes().into()
+}
+
+// Compara la etiqueta en tiempo constante
+pub fn verify_hmac_sha256(key: &[u8; KEY_LEN], parts: &[&[u8]], tag: &[u8]) -> Result<()> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC acepta claves de cualquier largo");
+    for part in parts {
+        mac.update(part);
+    }
+    mac.verify_slice(tag).map_err(|_| PngmeError::IntegrityCheckFailed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_round_trip() {
+        let key = [7; KEY_LEN];
+        let nonce = random_bytes::<NONCE_LEN>().unwrap();
+        let ciphertext = encrypt(&key, &nonce, b"cabecera", b"secreto").unwrap();
+        assert_ne!(&ciphertext[..7], b"secreto");
+        assert_eq!(decrypt(&key, &nonce, b"cabecera", &ciphertext).unwrap().expose(), b"secreto");
+        assert!(decrypt(&key, &nonce, b"otra cabecera", &ciphertext).is_err());
+        assert!(decrypt(&[8; KEY_LEN], &nonce, b"cabecera", &ciphertext).is_err());
+    }
+
+    #[test]
+    fn test_read_keyfile() {
+        let dir = std::env::temp_dir();
+        let raw = dir.join(format!("pngme-keyfile-raw-{}", std::process::id()));
+        let encoded = dir.join(format!("pngme-keyfile-b64-{}", std::process::id()));
+        std::fs::write(&raw, [0xff, 0x00, 0x10]).unwrap();
+        std::fs::write(&encoded, "/wAQ\n").unwrap();
+        assert_eq!(read_keyfile(&raw).unwrap().expose(), &[0xff, 0x00, 0x10]);
+        assert_eq!(read_keyfile(&encoded).unwrap().expose(), &[0xff, 0x00, 0x10]);
+        std::fs::write(&raw, b"").unwrap();
+        assert!(read_keyfile(&raw).is_err());
+        std::fs::remove_file(raw).unwrap();
+        std::fs::remove_file(encoded).unwrap();
+    }
+
+    #[test]
+    fn test_hmac() {
+        let key = [7; KEY_LEN];
+        let tag = hmac_sha256(&key, &[b"cabe", b"cera"]);
+        assert_eq!(tag, hmac_sha256(&key, &[b"cabecera"]));
+        assert!(verify_hmac_sha256(&key, &[b"cabecera"], &tag).is_ok());
+        assert!(verify_hmac_sha256(&key, &[b"cabecerA"], &tag).is_err());
+        assert!(verify_hmac_sha256(&[8; KEY_LEN], &[b"cabecera"], &tag).is_err());
+        assert!(verify_hmac_sha256(&key, &[b"cabecera"], &tag[..16]).is_err());
+    }
+
+    #[test]
+    fn test_derive_key() {
+        let salt = [1; SALT_LEN];
+        let params = KdfParams { memory_kib: 1024, iterations: 1, parallelism: 1 };
+        let key = derive_key(b"clave", &salt, &params).unwrap();
+        assert_eq!(key, derive_key(b"clave", &salt, &params).unwrap());
+        assert_ne!(key, derive_key(b"otra", &salt, &params).unwrap());
+        assert_ne!(key, derive_key(b"clave", &salt, &KdfParams { iterations: 2, ..params }).unwrap());
+    }
+
+    #[test]
+    fn test_kdf_params_encoding() {
+        let params = KdfParams::default();
+        assert_eq!(KdfParams::from_bytes(&params.to_bytes()).unwrap(), params);
+        let huge = KdfParams { memory_kib: u32::MAX, ..params };
+        assert!(KdfParams::from_bytes(&huge.to_bytes()).is_err());
+        let no_iterations = KdfParams { iterations: 0, ..params };
+        assert!(KdfParams::from_bytes(&no_iterations.to_bytes()).is_err());
+    }
+}
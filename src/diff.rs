@@ -0,0 +1,139 @@
+use std::fmt::Display;
+use crate::chunk::Chunk;
+use crate::chunk_type::ChunkType;
+use crate::png::Png;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+impl DiffKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DiffKind::Added => "added",
+            DiffKind::Removed => "removed",
+            DiffKind::Modified => "modified",
+        }
+    }
+}
+
+// Posición y huella de un chunk en uno de los dos archivos
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkSummary {
+    pub index: usize,
+    pub length: u32,
+    pub crc: u32,
+}
+
+impl ChunkSummary {
+    fn new(index: usize, chunk: &Chunk) -> ChunkSummary {
+        ChunkSummary { index, length: chunk.length(), crc: chunk.crc() }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkDiff {
+    pub kind: DiffKind,
+    pub chunk_type: ChunkType,
+    pub old: Option<ChunkSummary>,
+    pub new: Option<ChunkSummary>,
+}
+
+impl Display for ChunkDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.old, self.new) {
+            (Some(old), Some(new)) => write!(
+                f,
+                "~ {}  #{} -> #{}  length {} -> {} ({:+})  crc {:08x} -> {:08x}",
+                self.chunk_type,
+                old.index,
+                new.index,
+                old.length,
+                new.length,
+                new.length as i64 - old.length as i64,
+                old.crc,
+                new.crc
+            ),
+            (Some(old), None) => write!(f, "- {}  #{}  length={}  crc={:08x}", self.chunk_type, old.index, old.length, old.crc),
+            (None, Some(new)) => write!(f, "+ {}  #{}  length={}  crc={:08x}", self.chunk_type, new.index, new.length, new.crc),
+            (None, None) => write!(f, "  {}", self.chunk_type),
+        }
+    }
+}
+
+// Compara chunk por chunk: la n-ésima aparición de un tipo en `old` se empareja con la
+// n-ésima del mismo tipo en `new`. Los cambios de posición sin cambios de contenido no se reportan
+pub fn diff(old: &Png, new: &Png) -> Vec<ChunkDiff> {
+    let mut unmatched: Vec<Option<(usize, &Chunk)>> = new.chunks().iter().enumerate().map(Some).collect();
+    let mut diffs = Vec::new();
+
+    for (index, chunk) in old.chunks().iter().enumerate() {
+        let chunk_type = *chunk.chunk_type();
+        let counterpart = unmatched
+            .iter_mut()
+            .find(|slot| slot.is_some_and(|(_, other)| *other.chunk_type() == chunk_type))
+            .and_then(Option::take);
+        let old = Some(ChunkSummary::new(index, chunk));
+        match counterpart {
+            None => diffs.push(ChunkDiff { kind: DiffKind::Removed, chunk_type, old, new: None }),
+            Some((new_index, other)) if other.crc() != chunk.crc() || other.data() != chunk.data() => {
+                let new = Some(ChunkSummary::new(new_index, other));
+                diffs.push(ChunkDiff { kind: DiffKind::Modified, chunk_type, old, new });
+            }
+            Some(_) => {}
+        }
+    }
+
+    for (index, chunk) in unmatched.into_iter().flatten() {
+        let new = Some(ChunkSummary::new(index, chunk));
+        diffs.push(ChunkDiff { kind: DiffKind::Added, chunk_type: *chunk.chunk_type(), old: None, new });
+    }
+    diffs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn chunk(code: &str, data: &str) -> Chunk {
+        Chunk::new(ChunkType::from_str(code).unwrap(), data.as_bytes().to_vec()).unwrap()
+    }
+
+    #[test]
+    fn test_identical() {
+        let png = Png::from_chunks(vec![chunk("IHDR", "a"), chunk("IEND", "")]);
+        assert!(diff(&png, &png).is_empty());
+    }
+
+    #[test]
+    fn test_added_removed_modified() {
+        let old = Png::from_chunks(vec![
+            chunk("IHDR", "a"),
+            chunk("tEXt", "uno"),
+            chunk("tEXt", "dos"),
+            chunk("IDAT", "x"),
+            chunk("IEND", ""),
+        ]);
+        let new = Png::from_chunks(vec![
+            chunk("IHDR", "a"),
+            chunk("tEXt", "uno"),
+            chunk("IDAT", "xy"),
+            chunk("ruSt", "nuevo"),
+            chunk("IEND", ""),
+        ]);
+        let kinds: Vec<(DiffKind, String)> =
+            diff(&old, &new).iter().map(|d| (d.kind, d.chunk_type.to_string())).collect();
+        assert_eq!(
+            kinds,
+            [
+                (DiffKind::Removed, "tEXt".to_string()),
+                (DiffKind::Modified, "IDAT".to_string()),
+                (DiffKind::Added, "ruSt".to_string()),
+            ]
+        );
+    }
+}
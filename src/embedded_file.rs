@@ -0,0 +1,170 @@
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+use crate::sniff::detect_mime;
+use crate::{tr, PngmeError, Result};
+use crate::locale::text;
+
+// Un archivo cualquiera guardado con su nombre, tamaño, fecha de modificación y tipo MIME:
+// firma "PNGe" + versión + largo del nombre (u16) + nombre + tamaño (u64) + hay fecha (u8)
+// + segundos desde 1970 (u64) + largo del MIME (u8) + MIME, seguido del contenido
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbeddedFile {
+    pub name: String,
+    pub modified: Option<u64>,
+    pub mime: String,
+    pub data: Vec<u8>,
+}
+
+impl EmbeddedFile {
+    pub const MAGIC: [u8; 4] = *b"PNGe";
+    pub const VERSION: u8 = 1;
+
+    // Lee el archivo del disco; del camino sólo se guarda el nombre
+    pub fn read(path: &Path) -> Result<EmbeddedFile> {
+        let data = fs::read(path)?;
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| PngmeError::InvalidPayload(tr!(
+                "{} no tiene un nombre de archivo válido",
+                "{} does not have a valid file name",
+                path.display()
+            )))?;
+        let modified = fs::metadata(path)?
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| duration.as_secs());
+        Ok(EmbeddedFile { name: name.to_string(), modified, mime: detect_mime(&data).to_string(), data })
+    }
+
+    pub fn is_embedded_file(bytes: &[u8]) -> bool {
+        bytes.len() > 4 && bytes[..4] == EmbeddedFile::MAGIC
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let name_len = u16::try_from(self.name.len())
+            .map_err(|_| {
+                let reason = text("el nombre del archivo es demasiado largo", "the file name is too long");
+                PngmeError::InvalidPayload(reason.to_string())
+            })?;
+        let mime_len = u8::try_from(self.mime.len())
+            .map_err(|_| {
+                let reason = text("el tipo MIME es demasiado largo", "the MIME type is too long");
+                PngmeError::InvalidPayload(reason.to_string())
+            })?;
+        let mut bytes = Vec::with_capacity(self.data.len() + self.name.len() + self.mime.len() + 26);
+        bytes.extend_from_slice(&EmbeddedFile::MAGIC);
+        bytes.push(EmbeddedFile::VERSION);
+        bytes.extend_from_slice(&name_len.to_be_bytes());
+        bytes.extend_from_slice(self.name.as_bytes());
+        bytes.extend_from_slice(&(self.data.len() as u64).to_be_bytes());
+        bytes.push(self.modified.is_some() as u8);
+        bytes.extend_from_slice(&self.modified.unwrap_or_default().to_be_bytes());
+        bytes.push(mime_len);
+        bytes.extend_from_slice(self.mime.as_bytes());
+        bytes.extend_from_slice(&self.data);
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<EmbeddedFile> {
+        if !EmbeddedFile::is_embedded_file(bytes) {
+            let reason = text("el mensaje no es un archivo incrustado", "the message is not an embedded file");
+            return Err(PngmeError::InvalidPayload(reason.to_string()));
+        }
+        if bytes[4] != EmbeddedFile::VERSION {
+            return Err(PngmeError::InvalidPayload(tr!(
+                "versión de archivo incrustado desconocida: {}",
+                "unknown embedded file version: {}",
+                bytes[4]
+            )));
+        }
+        let mut reader = Reader { bytes, position: 5 };
+        let name_len = u16::from_be_bytes(reader.take_array()?) as usize;
+        let name = reader.take_string(name_len)?;
+        let size = u64::from_be_bytes(reader.take_array()?);
+        let has_modified = reader.take_array::<1>()?[0] != 0;
+        let modified = u64::from_be_bytes(reader.take_array()?);
+        let mime_len = reader.take_array::<1>()?[0] as usize;
+        let mime = reader.take_string(mime_len)?;
+
+        let data = &bytes[reader.position..];
+        if data.len() as u64 != size {
+            return Err(PngmeError::PayloadTruncated { expected: size as usize, actual: data.len() });
+        }
+        Ok(EmbeddedFile { name, modified: has_modified.then_some(modified), mime, data: data.to_vec() })
+    }
+
+    // Nombre con el que se restaura: sin directorios, para no escribir fuera de donde se extrae
+    pub fn safe_name(&self) -> Option<&str> {
+        let name = self.name.rsplit(['/', '\\']).next()?;
+        (!name.is_empty() && name != "." && name != "..").then_some(name)
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8]> {
+        let end = self.position + len;
+        if end > self.bytes.len() {
+            let reason = text(
+                "el encabezado del archivo incrustado está incompleto",
+                "the embedded file header is incomplete"
+            );
+            return Err(PngmeError::InvalidPayload(reason.to_string()));
+        }
+        let slice = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(slice)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.take(N)?.try_into().expect("largo verificado"))
+    }
+
+    fn take_string(&mut self, len: usize) -> Result<String> {
+        String::from_utf8(self.take(len)?.to_vec())
+            .map_err(|_| {
+                let reason = text(
+                    "el encabezado del archivo incrustado no es UTF-8",
+                    "the embedded file header is not UTF-8"
+                );
+                PngmeError::InvalidPayload(reason.to_string())
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedded_file_round_trip() {
+        let file = EmbeddedFile {
+            name: "informe.pdf".to_string(),
+            modified: Some(1_700_000_000),
+            mime: detect_mime(b"%PDF-1.7 ...").to_string(),
+            data: b"%PDF-1.7 ...".to_vec(),
+        };
+        let bytes = file.to_bytes().unwrap();
+        assert!(EmbeddedFile::is_embedded_file(&bytes));
+        assert_eq!(EmbeddedFile::from_bytes(&bytes).unwrap(), file);
+        assert_eq!(file.mime, "application/pdf");
+
+        let error = EmbeddedFile::from_bytes(&bytes[..bytes.len() - 3]).unwrap_err();
+        assert!(matches!(error, PngmeError::PayloadTruncated { expected: 12, actual: 9 }));
+    }
+
+    #[test]
+    fn test_safe_name_drops_directories() {
+        let file = |name: &str| EmbeddedFile { name: name.to_string(), modified: None, mime: String::new(), data: Vec::new() };
+        assert_eq!(file("../../etc/passwd").safe_name(), Some("passwd"));
+        assert_eq!(file("..\\fotos\\gato.png").safe_name(), Some("gato.png"));
+        assert_eq!(file("..").safe_name(), None);
+    }
+}
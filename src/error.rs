@@ -0,0 +1,262 @@
+use std::fmt::Display;
+use crate::locale::text;
+use crate::tr;
+
+// Campo del chunk en el que falló la lectura
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkField {
+    Length,
+    Type,
+    Data,
+    Crc,
+}
+
+impl Display for ChunkField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChunkField::Length => write!(f, "length"),
+            ChunkField::Type => write!(f, "{}", text("tipo", "type")),
+            ChunkField::Data => write!(f, "{}", text("datos", "data")),
+            ChunkField::Crc => write!(f, "CRC"),
+        }
+    }
+}
+
+// Qué límite de ParseOptions se superó
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    ChunkLen,
+    TotalLen,
+}
+
+// Error común de toda la crate. Los offsets son absolutos respecto al inicio del buffer leído
+#[derive(Debug)]
+pub enum PngmeError {
+    InvalidSignature,
+    InvalidChunkType { offset: Option<usize>, bytes: [u8; 4] },
+    // un tipo escrito a mano que no tiene 4 bytes
+    InvalidChunkTypeLength { length: usize },
+    UnexpectedEof { field: ChunkField, offset: usize, needed: usize, available: usize },
+    LengthOutOfRange { offset: usize, length: u32 },
+    BadCrc { offset: usize, expected: u32, actual: u32 },
+    // datos de más de 2^31-1 bytes para un chunk nuevo
+    ChunkTooLong { chunk_type: String, length: usize },
+    // `length` es lo que declara el archivo (el chunk, o el archivo hasta el final de ese chunk)
+    OverLimit { limit: Limit, offset: usize, length: usize, max: usize },
+    // `similar` son los tipos del archivo que se parecen al pedido, para sugerirlos
+    ChunkNotFound { chunk_type: String, similar: Vec<String> },
+    // con tipo, `index` es el número (desde 1) entre los chunks de ese tipo; sin tipo, la posición en el
+    // archivo. `count` es cuántos hay
+    ChunkIndexOutOfRange { chunk_type: Option<String>, index: usize, count: usize },
+    // decode sin tipo no encontró ningún chunk con un mensaje legible
+    NoMessageFound,
+    // un offset más allá del final de los `length` bytes de datos del chunk
+    DataOffsetOutOfRange { chunk_type: String, offset: usize, length: usize },
+    InvalidUtf8 { index: usize },
+    InvalidChunkData { chunk_type: String, reason: String },
+    InvalidPayload(String),
+    // lo pedido en la línea de comandos no se puede hacer, o no se hace sin confirmarlo (--force, -o...)
+    Usage(String),
+    // un zTXt, iTXt o iCCP (o el mensaje, sin tipo) que al descomprimirse pasa de `limit` bytes
+    DecompressionLimit { chunk_type: Option<String>, limit: usize },
+    // IHDR, PLTE, IDAT o IEND: quitarlos deja una imagen que nadie puede leer
+    CriticalChunkRemoval { chunk_type: String },
+    // un segundo IHDR, PLTE o IEND, que la especificación no permite
+    DuplicateCriticalChunk { chunk_type: String },
+    DecryptionFailed,
+    PayloadTruncated { expected: usize, actual: usize },
+    IntegrityCheckFailed,
+    InvalidKey(String),
+    BadSignature(String),
+    // verify, lint o scan terminaron bien pero encontraron problemas; `errors` si alguno es grave
+    ProblemsFound { count: usize, errors: bool },
+    Io(std::io::Error),
+}
+
+impl PngmeError {
+    pub fn field(&self) -> Option<ChunkField> {
+        match self {
+            PngmeError::UnexpectedEof { field, .. } => Some(*field),
+            PngmeError::LengthOutOfRange { .. } | PngmeError::OverLimit { .. } => Some(ChunkField::Length),
+            PngmeError::InvalidChunkType { .. } => Some(ChunkField::Type),
+            PngmeError::BadCrc { .. } => Some(ChunkField::Crc),
+            _ => None,
+        }
+    }
+
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            PngmeError::InvalidChunkType { offset, .. } => *offset,
+            PngmeError::UnexpectedEof { offset, .. }
+            | PngmeError::LengthOutOfRange { offset, .. }
+            | PngmeError::OverLimit { offset, .. }
+            | PngmeError::BadCrc { offset, .. } => Some(*offset),
+            _ => None,
+        }
+    }
+}
+
+impl std::error::Error for PngmeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PngmeError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl Display for PngmeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            PngmeError::InvalidSignature => {
+                tr!("La cabecera no corresponde a la firma de un PNG", "The header does not match the PNG signature")
+            }
+            PngmeError::InvalidChunkType { offset: Some(offset), bytes } => tr!(
+                "Tipo de chunk inválido {:?} en el offset {}: los caracteres deben estar en los rangos ASCII 65-90 o 97-122",
+                "Invalid chunk type {:?} at offset {}: characters must be in the ASCII ranges 65-90 or 97-122",
+                bytes,
+                offset
+            ),
+            PngmeError::InvalidChunkType { offset: None, bytes } => tr!(
+                "Tipo de chunk inválido {:?}: los caracteres deben estar en los rangos ASCII 65-90 o 97-122",
+                "Invalid chunk type {:?}: characters must be in the ASCII ranges 65-90 or 97-122",
+                bytes
+            ),
+            PngmeError::InvalidChunkTypeLength { length } => tr!(
+                "Un tipo de chunk tiene 4 letras ASCII y el indicado tiene {} bytes",
+                "A chunk type has 4 ASCII letters and the given one has {} bytes",
+                length
+            ),
+            PngmeError::UnexpectedEof { field, offset, needed, available } => tr!(
+                "Faltan bytes para el campo {} en el offset {}: se necesitan {} y hay {}",
+                "Not enough bytes for the {} field at offset {}: {} needed, {} available",
+                field,
+                offset,
+                needed,
+                available
+            ),
+            PngmeError::LengthOutOfRange { offset, length } => tr!(
+                "El length {} en el offset {} supera el máximo de 2^31-1",
+                "The length {} at offset {} exceeds the maximum of 2^31-1",
+                length,
+                offset
+            ),
+            PngmeError::OverLimit { limit: Limit::ChunkLen, offset, length, max } => tr!(
+                "El chunk del offset {} declara {} bytes y el límite es {} (max_chunk_len)",
+                "The chunk at offset {} declares {} bytes and the limit is {} (max_chunk_len)",
+                offset,
+                length,
+                max
+            ),
+            PngmeError::OverLimit { limit: Limit::TotalLen, offset, length, max } => tr!(
+                "Con el chunk del offset {} el archivo llega a {} bytes y el límite es {} (max_total_len)",
+                "With the chunk at offset {} the file reaches {} bytes and the limit is {} (max_total_len)",
+                offset,
+                length,
+                max
+            ),
+            PngmeError::BadCrc { offset, expected, actual } => tr!(
+                "CRC inválido en el offset {}: se esperaba {:08x} y se leyó {:08x}",
+                "Invalid CRC at offset {}: expected {:08x}, read {:08x}",
+                offset,
+                expected,
+                actual
+            ),
+            PngmeError::ChunkTooLong { chunk_type, length } => tr!(
+                "Un chunk {} no puede tener {} bytes de datos: el máximo es 2^31-1",
+                "A {} chunk cannot hold {} data bytes: the maximum is 2^31-1",
+                chunk_type,
+                length
+            ),
+            PngmeError::ChunkNotFound { chunk_type, similar } if similar.is_empty() => {
+                tr!("No se encontró un chunk de tipo {}", "No chunk of type {} was found", chunk_type)
+            }
+            PngmeError::ChunkNotFound { chunk_type, similar } => tr!(
+                "No se encontró un chunk de tipo {}; ¿quisiste decir {}?",
+                "No chunk of type {} was found; did you mean {}?",
+                chunk_type,
+                similar.join(text(" o ", " or "))
+            ),
+            PngmeError::ChunkIndexOutOfRange { chunk_type: Some(chunk_type), index, count } => tr!(
+                "No hay un chunk {} número {} (hay {})",
+                "There is no {} chunk number {} (there are {})",
+                chunk_type,
+                index,
+                count
+            ),
+            PngmeError::ChunkIndexOutOfRange { chunk_type: None, index, count } => tr!(
+                "La posición {} está fuera del archivo ({} chunks)",
+                "Position {} is outside the file ({} chunks)",
+                index,
+                count
+            ),
+            PngmeError::NoMessageFound => tr!("No se encontró ningún mensaje legible", "No readable message was found"),
+            PngmeError::DataOffsetOutOfRange { chunk_type, offset, length } => tr!(
+                "El offset {:#x} queda fuera de los datos de {} ({} bytes)",
+                "Offset {:#x} is outside the data of {} ({} bytes)",
+                offset,
+                chunk_type,
+                length
+            ),
+            PngmeError::InvalidUtf8 { index } => tr!(
+                "Los datos del chunk no son UTF-8 válido: el primer byte inválido está en el índice {}",
+                "The chunk data is not valid UTF-8: the first invalid byte is at index {}",
+                index
+            ),
+            PngmeError::InvalidChunkData { chunk_type, reason } => tr!(
+                "Contenido inválido para un chunk {}: {}",
+                "Invalid contents for a {} chunk: {}",
+                chunk_type,
+                reason
+            ),
+            PngmeError::InvalidPayload(reason) => tr!("Mensaje inválido: {}", "Invalid message: {}", reason),
+            PngmeError::Usage(reason) => reason.clone(),
+            PngmeError::DecompressionLimit { chunk_type: Some(chunk_type), limit } => tr!(
+                "Los datos comprimidos del chunk {} pasan de {} bytes al descomprimirse; se cortó la lectura",
+                "The compressed data of the {} chunk exceeds {} bytes when decompressed; reading was stopped",
+                chunk_type,
+                limit
+            ),
+            PngmeError::DecompressionLimit { chunk_type: None, limit } => tr!(
+                "El mensaje comprimido pasa de {} bytes al descomprimirse; se cortó la lectura",
+                "The compressed message exceeds {} bytes when decompressed; reading was stopped",
+                limit
+            ),
+            PngmeError::CriticalChunkRemoval { chunk_type } => tr!(
+                "{} es un chunk crítico y sin él la imagen queda rota",
+                "{} is a critical chunk and the image is broken without it",
+                chunk_type
+            ),
+            PngmeError::DuplicateCriticalChunk { chunk_type } => tr!(
+                "El PNG ya tiene un {}; no puede haber dos",
+                "The PNG already has a {}; there cannot be two",
+                chunk_type
+            ),
+            PngmeError::DecryptionFailed => tr!(
+                "No se pudo descifrar el mensaje: la contraseña es incorrecta o los datos fueron alterados",
+                "The message could not be decrypted: the password is wrong or the data was tampered with"
+            ),
+            PngmeError::PayloadTruncated { expected, actual } => tr!(
+                "El mensaje está truncado: se esperaban {} bytes de datos y hay {}",
+                "The message is truncated: {} data bytes expected, {} available",
+                expected,
+                actual
+            ),
+            PngmeError::IntegrityCheckFailed => tr!(
+                "El código de integridad no coincide: el mensaje fue alterado o la contraseña es incorrecta",
+                "The integrity code does not match: the message was tampered with or the password is wrong"
+            ),
+            PngmeError::InvalidKey(reason) => tr!("Clave inválida: {}", "Invalid key: {}", reason),
+            PngmeError::BadSignature(reason) => tr!("La firma no es válida: {}", "The signature is not valid: {}", reason),
+            PngmeError::ProblemsFound { count, .. } => tr!("Se encontraron {} problemas", "{} problems found", count),
+            PngmeError::Io(err) => tr!("Error de entrada/salida: {}", "I/O error: {}", err),
+        };
+        f.write_str(&message)
+    }
+}
+
+impl From<std::io::Error> for PngmeError {
+    fn from(err: std::io::Error) -> PngmeError {
+        PngmeError::Io(err)
+    }
+}
@@ -0,0 +1,209 @@
+use std::borrow::Cow;
+use crate::chunk::Chunk;
+use crate::chunk_type::ChunkType;
+use crate::hash::sha256;
+use crate::png::Png;
+use crate::{tr, PngmeError, Result};
+use crate::locale::text;
+
+// Encabezado de cada fragmento de un mensaje repartido en varios chunks del mismo tipo:
+// firma "PNGf" + versión + id (u32) + índice (u32) + total de fragmentos (u32), seguido de los datos.
+// El id agrupa los fragmentos de un mismo mensaje y sale del hash de los datos completos
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FragmentHeader {
+    pub id: u32,
+    pub index: u32,
+    pub total: u32,
+}
+
+impl FragmentHeader {
+    pub const MAGIC: [u8; 4] = *b"PNGf";
+    pub const VERSION: u8 = 1;
+    pub const LEN: usize = 17;
+
+    // Separa encabezado y datos; None si el chunk no es un fragmento
+    pub fn parse(data: &[u8]) -> Option<(FragmentHeader, &[u8])> {
+        if data.len() < FragmentHeader::LEN || data[..4] != FragmentHeader::MAGIC || data[4] != FragmentHeader::VERSION {
+            return None;
+        }
+        let field = |start: usize| u32::from_be_bytes(data[start..start + 4].try_into().expect("largo verificado"));
+        let header = FragmentHeader { id: field(5), index: field(9), total: field(13) };
+        if header.total == 0 || header.index >= header.total {
+            return None;
+        }
+        Some((header, &data[FragmentHeader::LEN..]))
+    }
+
+    pub fn to_bytes(&self) -> [u8; FragmentHeader::LEN] {
+        let mut bytes = [0; FragmentHeader::LEN];
+        bytes[..4].copy_from_slice(&FragmentHeader::MAGIC);
+        bytes[4] = FragmentHeader::VERSION;
+        bytes[5..9].copy_from_slice(&self.id.to_be_bytes());
+        bytes[9..13].copy_from_slice(&self.index.to_be_bytes());
+        bytes[13..17].copy_from_slice(&self.total.to_be_bytes());
+        bytes
+    }
+}
+
+// Largo máximo de los datos de un fragmento para que el chunk no supere Chunk::MAX_LENGTH
+pub const MAX_FRAGMENT_DATA: usize = Chunk::MAX_LENGTH as usize - FragmentHeader::LEN;
+
+// Cuántos bytes de mensaje entran en `budget` bytes de archivo, contando largo, tipo y CRC de cada
+// chunk y, si hace falta fragmentar, el encabezado de cada fragmento
+pub fn capacity_for(budget: u64) -> u64 {
+    const CHUNK_OVERHEAD: u64 = 12;
+    let single = budget.saturating_sub(CHUNK_OVERHEAD);
+    if single <= MAX_FRAGMENT_DATA as u64 {
+        return single;
+    }
+    let per_chunk = CHUNK_OVERHEAD + FragmentHeader::LEN as u64;
+    let full = budget / (per_chunk + MAX_FRAGMENT_DATA as u64);
+    let rest = budget % (per_chunk + MAX_FRAGMENT_DATA as u64);
+    full * MAX_FRAGMENT_DATA as u64 + rest.saturating_sub(per_chunk)
+}
+
+// Parte los datos en chunks de a lo sumo `max_data` bytes de datos más el encabezado.
+// Si entran en un solo chunk se guardan tal cual, sin encabezado
+pub fn split(chunk_type: ChunkType, data: &[u8], max_data: usize) -> Result<Vec<Chunk>> {
+    let max_data = max_data.min(MAX_FRAGMENT_DATA);
+    if data.len() <= max_data {
+        return Ok(vec![Chunk::new(chunk_type, data.to_vec())?]);
+    }
+    if max_data == 0 {
+        let reason = text(
+            "el tamaño de los fragmentos tiene que ser mayor a 0",
+            "the fragment size must be greater than 0"
+        );
+        return Err(PngmeError::InvalidPayload(reason.to_string()));
+    }
+    let total = u32::try_from(data.len().div_ceil(max_data))
+        .map_err(|_| {
+            let reason = text("el mensaje necesita demasiados fragmentos", "the message needs too many fragments");
+            PngmeError::InvalidPayload(reason.to_string())
+        })?;
+    let hash = sha256(data);
+    let id = u32::from_be_bytes([hash[0], hash[1], hash[2], hash[3]]);
+    tracing::debug!(id, total, bytes = data.len(), "mensaje repartido en fragmentos");
+
+    data
+        .chunks(max_data)
+        .zip(0..)
+        .map(|(piece, index)| {
+            let header = FragmentHeader { id, index, total };
+            Chunk::new(chunk_type, [&header.to_bytes()[..], piece].concat())
+        })
+        .collect()
+}
+
+impl Png {
+    // Los fragmentos del mismo mensaje que `first`, en el orden en que aparecen en el archivo
+    pub fn fragments_of(&self, first: &Chunk) -> Vec<(FragmentHeader, &[u8])> {
+        let Some((header, _)) = FragmentHeader::parse(first.data()) else {
+            return Vec::new();
+        };
+        self.chunks()
+            .iter()
+            .filter(|chunk| chunk.chunk_type() == first.chunk_type())
+            .filter_map(|chunk| FragmentHeader::parse(chunk.data()))
+            .filter(|(other, _)| other.id == header.id && other.total == header.total)
+            .collect()
+    }
+
+    // Borra los fragmentos restantes de un mensaje; devuelve cuántos había
+    pub fn remove_fragments(&mut self, chunk_type: &ChunkType, id: u32) -> usize {
+        let before = self.chunks().len();
+        self.chunks_mut().retain(|chunk| {
+            chunk.chunk_type() != chunk_type || FragmentHeader::parse(chunk.data()).is_none_or(|(header, _)| header.id != id)
+        });
+        before - self.chunks().len()
+    }
+
+    // Datos del mensaje que empieza en `chunk`. Si es un fragmento se juntan todos en orden;
+    // falta o sobra alguno es un error
+    pub fn reassemble<'a>(&'a self, chunk: &'a Chunk) -> Result<Cow<'a, [u8]>> {
+        let Some((header, _)) = FragmentHeader::parse(chunk.data()) else {
+            return Ok(Cow::Borrowed(chunk.data()));
+        };
+        let mut pieces: Vec<Option<&[u8]>> = vec![None; header.total as usize];
+        for (fragment, data) in self.fragments_of(chunk) {
+            let slot = &mut pieces[fragment.index as usize];
+            if slot.is_some() {
+                return Err(PngmeError::InvalidPayload(tr!(
+                    "el fragmento {} está repetido",
+                    "fragment {} is repeated",
+                    fragment.index + 1
+                )));
+            }
+            *slot = Some(data);
+        }
+
+        let mut data = Vec::new();
+        for (index, piece) in pieces.into_iter().enumerate() {
+            let piece = piece.ok_or_else(|| {
+                PngmeError::InvalidPayload(tr!(
+                    "falta el fragmento {} de {}",
+                    "fragment {} of {} is missing",
+                    index + 1,
+                    header.total
+                ))
+            })?;
+            data.extend_from_slice(piece);
+        }
+        Ok(Cow::Owned(data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn testing_png(chunks: Vec<Chunk>) -> Png {
+        let ihdr = Chunk::new(ChunkType::IHDR, vec![0; 13]).unwrap();
+        let iend = Chunk::new(ChunkType::IEND, Vec::new()).unwrap();
+        Png::from_chunks([vec![ihdr], chunks, vec![iend]].into_iter().flatten().collect())
+    }
+
+    #[test]
+    fn test_small_payload_is_not_split() {
+        let chunk_type = ChunkType::from_str("ruSt").unwrap();
+        let chunks = split(chunk_type, b"corto", 100).unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].data(), b"corto");
+        assert!(FragmentHeader::parse(chunks[0].data()).is_none());
+    }
+
+    #[test]
+    fn test_split_and_reassemble() {
+        let chunk_type = ChunkType::from_str("ruSt").unwrap();
+        let data: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let chunks = split(chunk_type, &data, 300).unwrap();
+        assert_eq!(chunks.len(), 4);
+        let (header, piece) = FragmentHeader::parse(chunks[3].data()).unwrap();
+        assert_eq!((header.index, header.total, piece.len()), (3, 4, 100));
+
+        // el orden en el archivo no importa
+        let png = testing_png(chunks.into_iter().rev().collect());
+        let first = png.chunk_by_type("ruSt").unwrap();
+        assert_eq!(png.fragments_of(first).len(), 4);
+        assert_eq!(png.reassemble(first).unwrap(), data.as_slice());
+    }
+
+    #[test]
+    fn test_capacity_for() {
+        assert_eq!(capacity_for(5), 0);
+        assert_eq!(capacity_for(112), 100);
+        let two_chunks = 2 * (12 + Chunk::MAX_LENGTH as u64);
+        assert_eq!(capacity_for(two_chunks), 2 * MAX_FRAGMENT_DATA as u64);
+    }
+
+    #[test]
+    fn test_missing_fragment() {
+        let chunk_type = ChunkType::from_str("ruSt").unwrap();
+        let mut chunks = split(chunk_type, &[7; 90], 30).unwrap();
+        chunks.remove(1);
+        let png = testing_png(chunks);
+        let error = png.reassemble(png.chunk_by_type("ruSt").unwrap()).unwrap_err();
+        assert!(error.to_string().contains("falta el fragmento 2 de 3"));
+    }
+}
@@ -0,0 +1,103 @@
+use sha2::{Digest, Sha256};
+use crate::chunk_type::ChunkType;
+use crate::png::Png;
+
+impl Png {
+    // SHA-256 de la imagen en sí: IHDR, PLTE, los IDAT concatenados e IEND, cada uno como
+    // longitud + tipo + datos y sin CRC. Los chunks auxiliares y la forma en que se dividen
+    // los IDAT no cambian el resultado
+    pub fn content_hash(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        let mut update = |chunk_type: ChunkType, data: &[u8]| {
+            hasher.update((data.len() as u32).to_be_bytes());
+            hasher.update(chunk_type.bytes());
+            hasher.update(data);
+        };
+
+        for chunk_type in [ChunkType::IHDR, ChunkType::PLTE] {
+            if let Some(chunk) = self.chunks().iter().find(|chunk| *chunk.chunk_type() == chunk_type) {
+                update(chunk_type, chunk.data());
+            }
+        }
+        let idat: Vec<u8> = self
+            .chunks()
+            .iter()
+            .filter(|chunk| *chunk.chunk_type() == ChunkType::IDAT)
+            .flat_map(|chunk| chunk.data().iter().copied())
+            .collect();
+        update(ChunkType::IDAT, &idat);
+        update(ChunkType::IEND, &[]);
+
+        hasher.finalize().into()
+    }
+}
+
+pub fn sha256(bytes: &[u8]) -> [u8; 32] {
+    Sha256::digest(bytes).into()
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+// Lo inverso de to_hex, en mayúsculas o minúsculas; None si hay algo que no es un dígito hexa o si la
+// cantidad de dígitos es impar
+pub fn from_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) || !text.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..text.len()).step_by(2).map(|i| u8::from_str_radix(&text[i..i + 2], 16).ok()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::Chunk;
+
+    fn chunk(chunk_type: ChunkType, data: &[u8]) -> Chunk {
+        Chunk::new(chunk_type, data.to_vec()).unwrap()
+    }
+
+    #[test]
+    fn test_content_hash_ignores_ancillary() {
+        let plain = Png::from_chunks(vec![
+            chunk(ChunkType::IHDR, b"ihdr"),
+            chunk(ChunkType::IDAT, b"pixeles"),
+            chunk(ChunkType::IEND, b""),
+        ]);
+        let annotated = Png::from_chunks(vec![
+            chunk(ChunkType::IHDR, b"ihdr"),
+            chunk(ChunkType::tIME, &[7, 234, 1, 1, 0, 0, 0]),
+            chunk(ChunkType::IDAT, b"pixe"),
+            chunk(ChunkType::IDAT, b"les"),
+            chunk(ChunkType::tEXt, b"Author\0alguien"),
+            chunk(ChunkType::IEND, b""),
+        ]);
+        assert_eq!(plain.content_hash(), annotated.content_hash());
+        assert_ne!(plain.as_bytes(), annotated.as_bytes());
+    }
+
+    #[test]
+    fn test_content_hash_detects_pixel_changes() {
+        let png = |pixels: &[u8]| Png::from_chunks(vec![chunk(ChunkType::IHDR, b"ihdr"), chunk(ChunkType::IDAT, pixels)]);
+        assert_ne!(png(b"uno").content_hash(), png(b"dos").content_hash());
+    }
+
+    #[test]
+    fn test_to_hex() {
+        assert_eq!(to_hex(&[0, 15, 255]), "000fff");
+        assert_eq!(
+            to_hex(&sha256(b"")),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn test_from_hex() {
+        assert_eq!(from_hex("000fFF"), Some(vec![0, 15, 255]));
+        assert_eq!(from_hex(""), Some(vec![]));
+        assert_eq!(from_hex("abc"), None);
+        assert_eq!(from_hex("zz"), None);
+        assert_eq!(from_hex("+1"), None);
+    }
+}
@@ -0,0 +1,91 @@
+use std::fmt::Write;
+use crate::{tr, PngmeError, Result};
+
+const BYTES_PER_LINE: usize = 16;
+
+// Escribe un volcado estilo xxd: offset, 16 bytes en hexa y su versión ASCII.
+// `base` se suma a los offsets para poder mostrar posiciones absolutas del archivo
+pub fn write_hexdump<W: Write>(out: &mut W, data: &[u8], base: usize, indent: &str) -> std::fmt::Result {
+    for (line, bytes) in data.chunks(BYTES_PER_LINE).enumerate() {
+        write!(out, "{}{:08x}  ", indent, base + line * BYTES_PER_LINE)?;
+        for i in 0..BYTES_PER_LINE {
+            match bytes.get(i) {
+                Some(byte) => write!(out, "{:02x} ", byte)?,
+                None => write!(out, "   ")?,
+            }
+            if i == 7 {
+                write!(out, " ")?;
+            }
+        }
+        write!(out, " |")?;
+        for byte in bytes {
+            let c = if byte.is_ascii_graphic() || *byte == b' ' { *byte as char } else { '.' };
+            write!(out, "{}", c)?;
+        }
+        writeln!(out, "|")?;
+    }
+    Ok(())
+}
+
+pub fn hexdump(data: &[u8], base: usize) -> String {
+    let mut out = String::new();
+    // escribir en un String no puede fallar
+    let _ = write_hexdump(&mut out, data, base, "");
+    out
+}
+
+// Lee de vuelta un volcado como el de hexdump, quizá editado a mano. De cada línea sólo cuentan los
+// bytes: la columna ASCII (desde el primer |) se descarta, y también el offset del principio, que es la
+// única palabra de más de dos dígitos. Así se pueden agregar o borrar bytes sin arreglar los offsets
+pub fn parse_hexdump(text: &str) -> Result<Vec<u8>> {
+    let mut data = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let hex = line.split('|').next().unwrap_or_default();
+        let mut words = hex.split_whitespace().peekable();
+        if words.peek().is_some_and(|word| word.len() > 2) {
+            words.next();
+        }
+        for word in words {
+            let byte = (word.len() == 2)
+                .then(|| u8::from_str_radix(word, 16).ok())
+                .flatten()
+                .ok_or_else(|| PngmeError::InvalidPayload(tr!(
+                    "línea {}: byte inválido: {}",
+                    "line {}: invalid byte: {}",
+                    number + 1,
+                    word
+                )))?;
+            data.push(byte);
+        }
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hexdump_line() {
+        let dump = hexdump(b"hola\x00mundo", 0x10);
+        assert_eq!(
+            dump,
+            "00000010  68 6f 6c 61 00 6d 75 6e  64 6f                    |hola.mundo|\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_hexdump_roundtrip() {
+        let data: Vec<u8> = (0..=255).collect();
+        assert_eq!(parse_hexdump(&hexdump(&data, 0)).unwrap(), data);
+    }
+
+    #[test]
+    fn test_parse_hexdump_edited() {
+        // un byte agregado, la columna ASCII sin actualizar y una línea vacía
+        let dump = "00000000  68 6f 6c 61 21 |hola|\n\n00000004  00 ff\n";
+        assert_eq!(parse_hexdump(dump).unwrap(), b"hola!\x00\xff");
+        assert!(parse_hexdump("00000000  68 6g").is_err());
+        assert!(parse_hexdump("00000000  686f").is_err());
+    }
+}
@@ -0,0 +1,44 @@
+use keyring::Entry;
+use crate::secret::Secret;
+use crate::{tr, PngmeError, Result};
+use crate::locale::text;
+
+// Todas las claves de pngme se guardan bajo este servicio; el id es el nombre de usuario de la entrada
+const SERVICE: &str = "pngme";
+
+fn keyring_error(id: &str, err: keyring::Error) -> PngmeError {
+    match err {
+        keyring::Error::NoEntry => PngmeError::InvalidKey(tr!(
+            "no hay ninguna clave guardada con el id {}",
+            "there is no key stored with the id {}",
+            id
+        )),
+        err => PngmeError::InvalidKey(tr!(
+            "almacén de claves del sistema ({}): {}",
+            "system key store ({}): {}",
+            id,
+            err
+        )),
+    }
+}
+
+fn entry(id: &str) -> Result<Entry> {
+    if id.is_empty() {
+        let reason = text("el id de la clave no puede estar vacío", "the key id cannot be empty");
+        return Err(PngmeError::InvalidKey(reason.to_string()));
+    }
+    Entry::new(SERVICE, id).map_err(|err| keyring_error(id, err))
+}
+
+// Guarda la clave o contraseña en el almacén de credenciales del sistema; reemplaza la anterior
+pub fn store_secret(id: &str, secret: &[u8]) -> Result<()> {
+    entry(id)?.set_secret(secret).map_err(|err| keyring_error(id, err))
+}
+
+pub fn load_secret(id: &str) -> Result<Secret<Vec<u8>>> {
+    entry(id)?.get_secret().map(Secret::new).map_err(|err| keyring_error(id, err))
+}
+
+pub fn delete_secret(id: &str) -> Result<()> {
+    entry(id)?.delete_credential().map_err(|err| keyring_error(id, err))
+}
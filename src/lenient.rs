@@ -0,0 +1,167 @@
+use std::fmt::Display;
+use crate::chunk::Chunk;
+use crate::chunk_ref::ChunkRef;
+use crate::chunk_type::ChunkType;
+use crate::png::Png;
+use crate::tr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseIssue {
+    InvalidSignature,
+    BadCrc { index: usize, offset: usize, expected: u32, actual: u32 },
+    UnknownCritical { index: usize, offset: usize, chunk_type: ChunkType },
+    // bytes que no forman parte de ningún chunk y se saltearon para resincronizar
+    Garbage { offset: usize, length: usize },
+    // el final del archivo no se pudo leer
+    Truncated { offset: usize, reason: String },
+    DataAfterIend { offset: usize, length: usize },
+}
+
+impl Display for ParseIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            ParseIssue::InvalidSignature => {
+                tr!("la firma no corresponde a un PNG", "the signature is not a PNG signature")
+            }
+            ParseIssue::BadCrc { index, offset, expected, actual } => tr!(
+                "#{} (offset {}): CRC inválido, se esperaba {:08x} y se leyó {:08x}",
+                "#{} (offset {}): invalid CRC, expected {:08x}, read {:08x}",
+                index,
+                offset,
+                expected,
+                actual
+            ),
+            ParseIssue::UnknownCritical { index, offset, chunk_type } => tr!(
+                "#{} (offset {}): chunk crítico desconocido {}",
+                "#{} (offset {}): unknown critical chunk {}",
+                index,
+                offset,
+                chunk_type
+            ),
+            ParseIssue::Garbage { offset, length } => tr!(
+                "offset {}: {} bytes que no forman un chunk",
+                "offset {}: {} bytes that do not form a chunk",
+                offset,
+                length
+            ),
+            ParseIssue::Truncated { offset, reason } => format!("offset {}: {}", offset, reason),
+            ParseIssue::DataAfterIend { offset, length } => {
+                tr!("offset {}: {} bytes después de IEND", "offset {}: {} bytes after IEND", offset, length)
+            }
+        };
+        f.write_str(&message)
+    }
+}
+
+// Busca desde `from` el próximo offset en el que empieza un chunk con CRC correcto
+fn resync(bytes: &[u8], from: usize) -> Option<usize> {
+    (from..bytes.len().saturating_sub(11)).find(|offset| {
+        ChunkRef::parse_at(&bytes[*offset..], *offset).is_ok_and(|chunk| chunk.is_crc_valid())
+    })
+}
+
+impl Png {
+    // Parsea todo lo posible sin cortar en el primer error: los problemas se devuelven
+    // junto al PNG para poder inspeccionar archivos dañados o manipulados
+    pub fn parse_lenient(bytes: &[u8]) -> (Png, Vec<ParseIssue>) {
+        let mut issues = Vec::new();
+        if bytes.len() < 8 || bytes[..8] != Png::STANDARD_HEADER {
+            issues.push(ParseIssue::InvalidSignature);
+        }
+
+        let mut chunks: Vec<Chunk> = Vec::new();
+        let mut offset = 8.min(bytes.len());
+        while offset < bytes.len() {
+            let chunk_ref = match ChunkRef::parse_at(&bytes[offset..], offset) {
+                Ok(chunk_ref) => chunk_ref,
+                Err(err) => match resync(bytes, offset + 1) {
+                    Some(next) => {
+                        issues.push(ParseIssue::Garbage { offset, length: next - offset });
+                        offset = next;
+                        continue;
+                    }
+                    None => {
+                        issues.push(ParseIssue::Truncated { offset, reason: err.to_string() });
+                        break;
+                    }
+                },
+            };
+
+            let index = chunks.len();
+            let ct = chunk_ref.chunk_type();
+            if !chunk_ref.is_crc_valid() {
+                issues.push(ParseIssue::BadCrc {
+                    index,
+                    offset,
+                    expected: chunk_ref.computed_crc(),
+                    actual: chunk_ref.crc(),
+                });
+            }
+            if ct.is_critical() && !ct.is_standard() {
+                issues.push(ParseIssue::UnknownCritical { index, offset, chunk_type: ct });
+            }
+
+            chunks.push(chunk_ref.to_chunk());
+            offset += chunk_ref.total_len();
+            if ct == ChunkType::IEND {
+                if offset < bytes.len() {
+                    issues.push(ParseIssue::DataAfterIend { offset, length: bytes.len() - offset });
+                }
+                break;
+            }
+        }
+
+        (Png::from_chunks(chunks), issues)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn testing_bytes() -> Vec<u8> {
+        let ihdr = Chunk::new(ChunkType::IHDR, vec![0, 0, 0, 2, 0, 0, 0, 2, 8, 2, 0, 0, 0]).unwrap();
+        let idat = Chunk::new(ChunkType::IDAT, vec![1, 2, 3]).unwrap();
+        let iend = Chunk::new(ChunkType::IEND, Vec::new()).unwrap();
+        Png::from_chunks(vec![ihdr, idat, iend]).as_bytes()
+    }
+
+    #[test]
+    fn test_clean_file_has_no_issues() {
+        let bytes = testing_bytes();
+        let (png, issues) = Png::parse_lenient(&bytes);
+        assert!(issues.is_empty());
+        assert_eq!(png.as_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_collects_bad_crc_and_unknown_critical() {
+        let mut bytes = testing_bytes();
+        bytes[33 + 12 + 3 - 1] ^= 0xff;
+        let weird = Chunk::new(ChunkType::from_str("RuSt").unwrap(), b"x".to_vec()).unwrap().as_bytes();
+        bytes.splice(33..33, weird);
+
+        let (png, issues) = Png::parse_lenient(&bytes);
+        assert_eq!(png.chunks().len(), 4);
+        assert!(matches!(issues[0], ParseIssue::UnknownCritical { index: 1, offset: 33, .. }));
+        assert!(matches!(issues[1], ParseIssue::BadCrc { index: 2, offset: 46, .. }));
+    }
+
+    #[test]
+    fn test_skips_garbage_between_chunks() {
+        let mut bytes = testing_bytes();
+        bytes.splice(33..33, b"basura!".iter().copied());
+        let (png, issues) = Png::parse_lenient(&bytes);
+        assert_eq!(issues, vec![ParseIssue::Garbage { offset: 33, length: 7 }]);
+        assert_eq!(png.as_bytes(), testing_bytes());
+    }
+
+    #[test]
+    fn test_truncated_tail() {
+        let bytes = testing_bytes();
+        let (png, issues) = Png::parse_lenient(&bytes[..40]);
+        assert_eq!(png.chunks().len(), 1);
+        assert!(matches!(issues[0], ParseIssue::Truncated { offset: 33, .. }));
+    }
+}
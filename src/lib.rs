@@ -0,0 +1,45 @@
+pub mod analysis;
+pub mod archive;
+pub mod chunk;
+pub mod chunk_ref;
+pub mod chunk_type;
+pub mod compression;
+#[cfg(feature = "crypto")]
+pub mod crypto;
+pub mod diff;
+pub mod embedded_file;
+pub mod error;
+pub mod fragment;
+pub mod hash;
+pub mod hexdump;
+#[cfg(feature = "keyring")]
+pub mod keystore;
+pub mod lenient;
+pub mod lint;
+pub mod locale;
+pub mod merge;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+pub mod payload;
+pub mod png;
+#[cfg(feature = "age")]
+pub mod recipients;
+pub mod repair;
+pub mod scan;
+pub mod secret;
+#[cfg(feature = "crypto")]
+pub mod shamir;
+#[cfg(feature = "crypto")]
+pub mod signature;
+pub mod sniff;
+pub mod spread;
+pub mod strip;
+pub mod suggest;
+#[cfg(feature = "serde")]
+mod serialize;
+pub mod typed;
+pub mod verify;
+
+pub use error::PngmeError;
+
+pub type Result<T> = std::result::Result <T, PngmeError>;
@@ -0,0 +1,267 @@
+use std::fmt::Display;
+use crate::chunk_type::{ChunkType, Placement};
+use crate::locale::text;
+use crate::png::Png;
+use crate::typed::{ColorType, Ihdr};
+
+// Reglas de la especificación. Los códigos son estables: no se renumeran ni se reutilizan
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rule {
+    IhdrFirst,
+    SingleIhdr,
+    IendMissing,
+    ChunksAfterIend,
+    IdatMissing,
+    IdatNotContiguous,
+    PlteAfterIdat,
+    PlteMissing,
+    PlteForbidden,
+    Duplicate,
+    BeforePlte,
+    BetweenPlteAndIdat,
+    BeforeIdat,
+    AfterIdat,
+    UnknownCritical,
+    ReservedBit,
+    IccpWithSrgb,
+    InvalidIhdr,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 18] = [
+        Rule::IhdrFirst,
+        Rule::SingleIhdr,
+        Rule::IendMissing,
+        Rule::ChunksAfterIend,
+        Rule::IdatMissing,
+        Rule::IdatNotContiguous,
+        Rule::PlteAfterIdat,
+        Rule::PlteMissing,
+        Rule::PlteForbidden,
+        Rule::Duplicate,
+        Rule::BeforePlte,
+        Rule::BetweenPlteAndIdat,
+        Rule::BeforeIdat,
+        Rule::AfterIdat,
+        Rule::UnknownCritical,
+        Rule::ReservedBit,
+        Rule::IccpWithSrgb,
+        Rule::InvalidIhdr,
+    ];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Rule::IhdrFirst => "P001",
+            Rule::SingleIhdr => "P002",
+            Rule::IendMissing => "P003",
+            Rule::ChunksAfterIend => "P004",
+            Rule::IdatMissing => "P005",
+            Rule::IdatNotContiguous => "P006",
+            Rule::PlteAfterIdat => "P007",
+            Rule::PlteMissing => "P008",
+            Rule::PlteForbidden => "P009",
+            Rule::Duplicate => "P010",
+            Rule::BeforePlte => "P011",
+            Rule::BetweenPlteAndIdat => "P012",
+            Rule::BeforeIdat => "P013",
+            Rule::AfterIdat => "P014",
+            Rule::UnknownCritical => "P015",
+            Rule::ReservedBit => "P016",
+            Rule::IccpWithSrgb => "P017",
+            Rule::InvalidIhdr => "P018",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        let (es, en) = match self {
+            Rule::IhdrFirst => ("el primer chunk tiene que ser IHDR", "the first chunk must be IHDR"),
+            Rule::SingleIhdr => ("sólo puede haber un IHDR", "there can be only one IHDR"),
+            Rule::IendMissing => ("el archivo tiene que terminar con IEND", "the file must end with IEND"),
+            Rule::ChunksAfterIend => ("no puede haber chunks después de IEND", "there can be no chunks after IEND"),
+            Rule::IdatMissing => ("tiene que haber al menos un IDAT", "there must be at least one IDAT"),
+            Rule::IdatNotContiguous => ("los IDAT tienen que estar seguidos", "IDAT chunks must be consecutive"),
+            Rule::PlteAfterIdat => ("PLTE tiene que estar antes del primer IDAT", "PLTE must come before the first IDAT"),
+            Rule::PlteMissing => ("las imágenes con paleta necesitan PLTE", "indexed-color images need PLTE"),
+            Rule::PlteForbidden => ("las imágenes en escala de grises no pueden tener PLTE", "grayscale images cannot have PLTE"),
+            Rule::Duplicate => ("el tipo de chunk no admite más de una instancia", "the chunk type does not allow more than one instance"),
+            Rule::BeforePlte => ("el chunk tiene que estar antes de PLTE e IDAT", "the chunk must come before PLTE and IDAT"),
+            Rule::BetweenPlteAndIdat => ("el chunk tiene que estar después de PLTE y antes de IDAT", "the chunk must come after PLTE and before IDAT"),
+            Rule::BeforeIdat => ("el chunk tiene que estar antes del primer IDAT", "the chunk must come before the first IDAT"),
+            Rule::AfterIdat => ("el chunk tiene que estar después del último IDAT", "the chunk must come after the last IDAT"),
+            Rule::UnknownCritical => ("chunk crítico desconocido", "unknown critical chunk"),
+            Rule::ReservedBit => ("el bit reservado del tipo está en uso", "the reserved bit of the type is set"),
+            Rule::IccpWithSrgb => ("iCCP y sRGB no deberían aparecer juntos", "iCCP and sRGB should not appear together"),
+            Rule::InvalidIhdr => ("los campos de IHDR no son válidos", "the IHDR fields are not valid"),
+        };
+        text(es, en)
+    }
+
+    pub fn from_code(code: &str) -> Option<Rule> {
+        Rule::ALL.into_iter().find(|rule| rule.code().eq_ignore_ascii_case(code))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub rule: Rule,
+    pub index: Option<usize>,
+    pub message: String,
+}
+
+impl Display for Finding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.index {
+            Some(index) => write!(f, "{} #{}: {}", self.rule.code(), index, self.message),
+            None => write!(f, "{}: {}", self.rule.code(), self.message),
+        }
+    }
+}
+
+fn finding(rule: Rule, index: Option<usize>, detail: Option<String>) -> Finding {
+    let message = match detail {
+        Some(detail) => format!("{} ({})", rule.description(), detail),
+        None => rule.description().to_string(),
+    };
+    Finding { rule, index, message }
+}
+
+pub fn lint(png: &Png) -> Vec<Finding> {
+    let chunks = png.chunks();
+    let types: Vec<ChunkType> = chunks.iter().map(|chunk| *chunk.chunk_type()).collect();
+    let position = |ct: ChunkType| types.iter().position(|t| *t == ct);
+    let first_idat = position(ChunkType::IDAT);
+    let last_idat = types.iter().rposition(|t| *t == ChunkType::IDAT);
+    let plte = position(ChunkType::PLTE);
+    let iend = position(ChunkType::IEND);
+    let mut findings = Vec::new();
+
+    match types.first() {
+        Some(first) if *first == ChunkType::IHDR => {
+            if let Err(err) = Ihdr::from_chunk(&chunks[0]) {
+                findings.push(finding(Rule::InvalidIhdr, Some(0), Some(err.to_string())));
+            }
+        }
+        _ => findings.push(finding(Rule::IhdrFirst, Some(0), None)),
+    }
+    match iend {
+        None => findings.push(finding(Rule::IendMissing, None, None)),
+        Some(index) if index + 1 < types.len() => {
+            findings.push(finding(Rule::ChunksAfterIend, Some(index + 1), None));
+        }
+        _ => {}
+    }
+    match (first_idat, last_idat) {
+        (None, _) | (_, None) => findings.push(finding(Rule::IdatMissing, None, None)),
+        (Some(first), Some(last)) => {
+            if let Some(gap) = (first..=last).find(|index| types[*index] != ChunkType::IDAT) {
+                findings.push(finding(Rule::IdatNotContiguous, Some(gap), Some(types[gap].to_string())));
+            }
+        }
+    }
+
+    if let Ok(ihdr) = png.header_info() {
+        match (ihdr.color(), plte) {
+            (Some(ColorType::Indexed), None) => findings.push(finding(Rule::PlteMissing, None, None)),
+            (Some(ColorType::Grayscale | ColorType::GrayscaleAlpha), Some(index)) => {
+                findings.push(finding(Rule::PlteForbidden, Some(index), None));
+            }
+            _ => {}
+        }
+    }
+    if position(ChunkType::iCCP).is_some() && position(ChunkType::sRGB).is_some() {
+        findings.push(finding(Rule::IccpWithSrgb, None, None));
+    }
+
+    for (index, ct) in types.iter().enumerate() {
+        let name = Some(ct.to_string());
+        if ct.is_critical() && !ct.is_standard() {
+            findings.push(finding(Rule::UnknownCritical, Some(index), name.clone()));
+        }
+        if !ct.is_reserved_bit_valid() {
+            findings.push(finding(Rule::ReservedBit, Some(index), name.clone()));
+        }
+
+        let Some(constraints) = ct.constraints() else { continue };
+        if !constraints.multiple && types[..index].contains(ct) {
+            findings.push(finding(Rule::Duplicate, Some(index), name.clone()));
+        }
+        let before_idat = first_idat.is_none_or(|idat| index < idat);
+        let misplaced = match constraints.placement {
+            Placement::BeforePlte if !(before_idat && plte.is_none_or(|p| index < p)) => Some(Rule::BeforePlte),
+            Placement::BetweenPlteAndIdat if !(before_idat && plte.is_none_or(|p| index > p)) => {
+                Some(Rule::BetweenPlteAndIdat)
+            }
+            Placement::BeforeIdat if *ct == ChunkType::PLTE && !before_idat => Some(Rule::PlteAfterIdat),
+            Placement::BeforeIdat if !before_idat => Some(Rule::BeforeIdat),
+            Placement::AfterIdat if last_idat.is_none_or(|idat| index < idat) => Some(Rule::AfterIdat),
+            _ => None,
+        };
+        if let Some(rule) = misplaced {
+            findings.push(finding(rule, Some(index), name));
+        }
+    }
+
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::Chunk;
+
+    fn chunk(chunk_type: ChunkType, data: &[u8]) -> Chunk {
+        Chunk::new(chunk_type, data.to_vec()).unwrap()
+    }
+
+    fn ihdr(color_type: u8) -> Chunk {
+        chunk(ChunkType::IHDR, &[0, 0, 0, 2, 0, 0, 0, 2, 8, color_type, 0, 0, 0])
+    }
+
+    fn codes(png: &Png) -> Vec<&'static str> {
+        lint(png).iter().map(|finding| finding.rule.code()).collect()
+    }
+
+    #[test]
+    fn test_valid_layout() {
+        let png = Png::from_chunks(vec![
+            ihdr(2),
+            chunk(ChunkType::gAMA, &[0, 0, 177, 143]),
+            chunk(ChunkType::tIME, &[7, 234, 10, 16, 12, 30, 0]),
+            chunk(ChunkType::IDAT, &[1]),
+            chunk(ChunkType::IDAT, &[2]),
+            chunk(ChunkType::tEXt, b"a\0b"),
+            chunk(ChunkType::IEND, &[]),
+        ]);
+        assert!(lint(&png).is_empty());
+    }
+
+    #[test]
+    fn test_ordering_rules() {
+        let png = Png::from_chunks(vec![
+            ihdr(3),
+            chunk(ChunkType::IDAT, &[1]),
+            chunk(ChunkType::pHYs, &[0, 0, 0, 1, 0, 0, 0, 1, 0]),
+            chunk(ChunkType::IDAT, &[2]),
+            chunk(ChunkType::PLTE, &[0, 0, 0]),
+            chunk(ChunkType::IEND, &[]),
+        ]);
+        assert_eq!(codes(&png), ["P006", "P013", "P007"]);
+    }
+
+    #[test]
+    fn test_structure_rules() {
+        let png = Png::from_chunks(vec![
+            chunk(ChunkType::gAMA, &[0, 0, 177, 143]),
+            ihdr(0),
+            ihdr(0),
+        ]);
+        assert_eq!(codes(&png), ["P001", "P003", "P005", "P010"]);
+    }
+
+    #[test]
+    fn test_rule_codes_are_unique() {
+        for rule in Rule::ALL {
+            assert_eq!(Rule::from_code(rule.code()), Some(rule));
+        }
+    }
+}
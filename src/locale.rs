@@ -0,0 +1,78 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+
+// Idioma de los errores y de los reportes. Es global porque los errores se muestran con Display,
+// que no recibe ningún contexto
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lang {
+    Es,
+    En,
+}
+
+impl Lang {
+    // "es", "en" o un locale como "en_US.UTF-8" o "es-AR"
+    pub fn from_name(name: &str) -> Option<Lang> {
+        let language = name.split(['_', '-', '.', '@']).next().unwrap_or_default();
+        match language.to_ascii_lowercase().as_str() {
+            "es" => Some(Lang::Es),
+            "en" => Some(Lang::En),
+            _ => None,
+        }
+    }
+
+    // El primero definido de LC_ALL, LC_MESSAGES y LANG, en ese orden, como hace gettext
+    pub fn from_env() -> Option<Lang> {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Lang::from_name(&value))
+    }
+}
+
+static LANG: AtomicU8 = AtomicU8::new(Lang::Es as u8);
+
+pub fn set_lang(lang: Lang) {
+    LANG.store(lang as u8, Ordering::Relaxed);
+}
+
+pub fn lang() -> Lang {
+    if LANG.load(Ordering::Relaxed) == Lang::En as u8 {
+        Lang::En
+    } else {
+        Lang::Es
+    }
+}
+
+// El texto fijo en el idioma elegido
+pub fn text(es: &'static str, en: &'static str) -> &'static str {
+    match lang() {
+        Lang::Es => es,
+        Lang::En => en,
+    }
+}
+
+// Como format!, con una plantilla por idioma y los mismos argumentos para las dos
+#[macro_export]
+macro_rules! tr {
+    ($es:literal, $en:literal $(, $arg:expr)* $(,)?) => {
+        match $crate::locale::lang() {
+            $crate::locale::Lang::Es => format!($es $(, $arg)*),
+            $crate::locale::Lang::En => format!($en $(, $arg)*),
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(Lang::from_name("es"), Some(Lang::Es));
+        assert_eq!(Lang::from_name("en_US.UTF-8"), Some(Lang::En));
+        assert_eq!(Lang::from_name("es-AR"), Some(Lang::Es));
+        assert_eq!(Lang::from_name("EN"), Some(Lang::En));
+        assert_eq!(Lang::from_name("C.UTF-8"), None);
+        assert_eq!(Lang::from_name("fr_FR"), None);
+    }
+}
@@ -1,12 +1,172 @@
 mod args;
-mod chunk;
-mod chunk_type;
+mod batch;
+mod color;
 mod commands;
-mod png;
+mod config;
+mod progress;
+mod shell;
+#[cfg(feature = "tui")]
+mod tui;
 
-pub type Error = Box<dyn std::error::Error>;
-pub type Result<T> = std::result::Result <T, Error>;
+use std::io::IsTerminal;
+use clap::{CommandFactory, FromArgMatches};
+use args::{ChunkTypeCommand, Cli, LogFormat, PngMeArgs};
+use color::ColorChoice;
+use pngme::locale::{self, Lang};
+use pngme::PngmeError;
+use tracing::Level;
+use tracing_subscriber::fmt::format::FmtSpan;
 
 fn main() {
-    todo!()
+    // el idioma se elige antes de leer los argumentos, para que la ayuda ya salga traducida
+    locale::set_lang(lang_from_args().or_else(Lang::from_env).unwrap_or(Lang::Es));
+    let cli = match args::command().try_get_matches().and_then(|matches| Cli::from_arg_matches(&matches)) {
+        Ok(cli) => cli,
+        Err(err) => {
+            let _ = err.print();
+            std::process::exit(if err.use_stderr() { EXIT_USAGE } else { 0 });
+        }
+    };
+    progress::set_quiet(cli.quiet);
+    // lo que se pasa en la línea de comandos tiene prioridad sobre la configuración
+    let config = match config::load(cli.config.as_deref(), cli.profile.as_deref()) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            std::process::exit(exit_code(&err));
+        }
+    };
+    color::init(cli.color.or(config.color).unwrap_or(ColorChoice::Auto));
+    config::init(config);
+    init_logging(cli.verbose, cli.log_format);
+    if let Err(err) = run(cli.command) {
+        eprintln!("Error: {}", err);
+        std::process::exit(exit_code(&err));
+    }
+}
+
+// --lang, buscado a mano antes del nombre del comando; si el valor no es válido, clap lo rechaza después
+fn lang_from_args() -> Option<Lang> {
+    let command = Cli::command();
+    let is_command = |arg: &String| command.get_subcommands().any(|sub| sub.get_name() == arg);
+    let argv: Vec<String> = std::env::args().skip(1).take_while(|arg| arg != "--" && !is_command(arg)).collect();
+    argv.iter().enumerate().find_map(|(i, arg)| match arg.strip_prefix("--lang=") {
+        Some(value) => args::parse_lang(value).ok(),
+        None if arg == "--lang" => argv.get(i + 1).and_then(|value| args::parse_lang(value).ok()),
+        None => None,
+    })
+}
+
+// Códigos de salida, para que los scripts no tengan que leer los mensajes de error
+const EXIT_NOT_FOUND: i32 = 1;
+const EXIT_INVALID_DATA: i32 = 2;
+const EXIT_CRYPTO: i32 = 3;
+const EXIT_IO: i32 = 4;
+const EXIT_PROBLEMS_FOUND: i32 = 5;
+const EXIT_USAGE: i32 = 64;
+
+fn exit_code(err: &PngmeError) -> i32 {
+    match err {
+        PngmeError::ChunkNotFound { .. } | PngmeError::ChunkIndexOutOfRange { .. } | PngmeError::NoMessageFound => {
+            EXIT_NOT_FOUND
+        }
+        PngmeError::InvalidSignature
+        | PngmeError::InvalidChunkType { .. }
+        | PngmeError::InvalidChunkTypeLength { .. }
+        | PngmeError::UnexpectedEof { .. }
+        | PngmeError::LengthOutOfRange { .. }
+        | PngmeError::ChunkTooLong { .. }
+        | PngmeError::OverLimit { .. }
+        | PngmeError::BadCrc { .. }
+        | PngmeError::InvalidUtf8 { .. }
+        | PngmeError::InvalidChunkData { .. }
+        | PngmeError::InvalidPayload(_)
+        | PngmeError::DecompressionLimit { .. }
+        | PngmeError::CriticalChunkRemoval { .. }
+        | PngmeError::DuplicateCriticalChunk { .. }
+        | PngmeError::DataOffsetOutOfRange { .. }
+        | PngmeError::PayloadTruncated { .. } => EXIT_INVALID_DATA,
+        PngmeError::DecryptionFailed
+        | PngmeError::IntegrityCheckFailed
+        | PngmeError::InvalidKey(_)
+        | PngmeError::BadSignature(_) => EXIT_CRYPTO,
+        PngmeError::ProblemsFound { errors: true, .. } => EXIT_INVALID_DATA,
+        PngmeError::ProblemsFound { errors: false, .. } => EXIT_PROBLEMS_FOUND,
+        PngmeError::Usage(_) => EXIT_USAGE,
+        PngmeError::Io(_) => EXIT_IO,
+    }
+}
+
+// Los mensajes van a stderr para no mezclarse con la salida; desde -vv se ve cuánto tarda cada fase
+fn init_logging(verbose: u8, format: LogFormat) {
+    let level = match verbose {
+        0 => Level::WARN,
+        1 => Level::INFO,
+        2 => Level::DEBUG,
+        _ => Level::TRACE,
+    };
+    let span_events = if verbose >= 2 { FmtSpan::CLOSE } else { FmtSpan::NONE };
+    let builder = tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_span_events(span_events)
+        .with_ansi(std::io::stderr().is_terminal())
+        .with_writer(std::io::stderr);
+    match format {
+        LogFormat::Text => builder.init(),
+        LogFormat::Json => builder.json().init(),
+    }
+}
+
+// También lo usa watch para aplicar un comando a cada archivo nuevo
+pub fn run(command: PngMeArgs) -> pngme::Result<()> {
+    match command {
+        PngMeArgs::Encode(args) => commands::encode(*args),
+        PngMeArgs::Decode(args) => commands::decode(args),
+        PngMeArgs::EncodeFile(args) => commands::encode_file(args),
+        PngMeArgs::Extract(args) => commands::extract(args),
+        PngMeArgs::Pack(args) => commands::pack(args),
+        PngMeArgs::Unpack(args) => commands::unpack(args),
+        PngMeArgs::Ls(args) => commands::ls(args),
+        PngMeArgs::Remove(args) => commands::remove(args),
+        PngMeArgs::Edit(args) => commands::edit(args),
+        PngMeArgs::ExportChunk(args) => commands::export_chunk(args),
+        PngMeArgs::ImportChunk(args) => commands::import_chunk(args),
+        PngMeArgs::Dump(args) => commands::dump(args),
+        PngMeArgs::Patch(args) => commands::patch(args),
+        PngMeArgs::Crc(args) => commands::crc(args),
+        PngMeArgs::ChunkType(ChunkTypeCommand::Describe(args)) => commands::describe_chunk_type(args),
+        PngMeArgs::ChunkType(ChunkTypeCommand::Generate(args)) => commands::generate_chunk_type(args),
+        PngMeArgs::Print(args) => commands::print_chunks(args),
+        PngMeArgs::List(args) => commands::list(args),
+        PngMeArgs::Info(args) => commands::info(args),
+        PngMeArgs::Capacity(args) => commands::capacity(args),
+        PngMeArgs::Analyze(args) => commands::analyze(args),
+        PngMeArgs::Scan(args) => commands::scan(args),
+        PngMeArgs::Verify(args) => commands::verify(args),
+        PngMeArgs::Lint(args) => commands::lint(args),
+        PngMeArgs::FixOrder(args) => commands::fix_order(args),
+        PngMeArgs::Strip(args) => commands::strip(args),
+        PngMeArgs::Diff(args) => commands::diff(args),
+        PngMeArgs::CopyChunks(args) => commands::copy_chunks(args),
+        PngMeArgs::Hash(args) => commands::hash(args),
+        PngMeArgs::Shell(args) => shell::shell(args),
+        #[cfg(feature = "tui")]
+        PngMeArgs::Tui(args) => tui::tui(args),
+        #[cfg(feature = "crypto")]
+        PngMeArgs::Sign(args) => commands::sign(args),
+        #[cfg(feature = "crypto")]
+        PngMeArgs::VerifySig(args) => commands::verify_sig(args),
+        #[cfg(feature = "keyring")]
+        PngMeArgs::KeyringSet(args) => commands::keyring_set(args),
+        #[cfg(feature = "keyring")]
+        PngMeArgs::KeyringDelete(args) => commands::keyring_delete(args),
+        PngMeArgs::Repair(args) => commands::repair(args),
+        PngMeArgs::Recover(args) => commands::recover(args),
+        #[cfg(feature = "serde")]
+        PngMeArgs::Export(args) => commands::export(args),
+        #[cfg(feature = "serde")]
+        PngMeArgs::Import(args) => commands::import(args),
+        #[cfg(feature = "watch")]
+        PngMeArgs::Watch(args) => commands::watch(args),
+    }
 }
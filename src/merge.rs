@@ -0,0 +1,106 @@
+use crate::chunk::Chunk;
+use crate::chunk_type::ChunkType;
+use crate::png::Png;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MergeReport {
+    pub copied: Vec<ChunkType>,
+    // chunks críticos, o inseguros para copiar entre imágenes distintas
+    pub skipped: Vec<ChunkType>,
+}
+
+impl Png {
+    // Copia desde `source` los chunks auxiliares que acepta el filtro.
+    // Los que no son seguros para copiar (bit 5 del cuarto byte) sólo se copian si los dos
+    // IHDR coinciden, es decir, si son la misma imagen. Los chunks que no admiten más de una
+    // instancia reemplazan al existente; el resto se inserta en una posición válida
+    pub fn merge_from<F>(&mut self, source: &Png, filter: F) -> MergeReport
+    where
+        F: Fn(&ChunkType) -> bool,
+    {
+        let same_image = match (self.chunk_by_type("IHDR"), source.chunk_by_type("IHDR")) {
+            (Some(ours), Some(theirs)) => ours.data() == theirs.data(),
+            _ => false,
+        };
+        let mut report = MergeReport::default();
+
+        for chunk in source.chunks() {
+            let chunk_type = *chunk.chunk_type();
+            if !filter(&chunk_type) {
+                continue;
+            }
+            if chunk_type.is_critical() || (!chunk_type.is_safe_to_copy() && !same_image) {
+                report.skipped.push(chunk_type);
+                continue;
+            }
+
+            let copy = Chunk::from_parsed(chunk_type, chunk.data().to_vec());
+            let single = chunk_type.constraints().is_some_and(|constraints| !constraints.multiple);
+            let existing = self.chunks().iter().position(|chunk| *chunk.chunk_type() == chunk_type);
+            match existing {
+                Some(index) if single => self.chunks_mut()[index] = copy,
+                _ => {
+                    let index = self.legal_insert_index(&chunk_type);
+                    self.chunks_mut().insert(index, copy);
+                }
+            }
+            report.copied.push(chunk_type);
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn chunk(code: &str, data: &str) -> Chunk {
+        Chunk::new(ChunkType::from_str(code).unwrap(), data.as_bytes().to_vec()).unwrap()
+    }
+
+    fn types(png: &Png) -> Vec<String> {
+        png.chunks().iter().map(|chunk| chunk.chunk_type().to_string()).collect()
+    }
+
+    fn render(ihdr: &str) -> Png {
+        Png::from_chunks(vec![chunk("IHDR", ihdr), chunk("IDAT", "pixeles"), chunk("IEND", "")])
+    }
+
+    #[test]
+    fn test_merge_same_image() {
+        let mut source = render("2x2");
+        source.chunks_mut().insert(1, chunk("iCCP", "perfil"));
+        source.chunks_mut().insert(3, chunk("tEXt", "Title\0hola"));
+        let mut target = render("2x2");
+
+        let report = target.merge_from(&source, |_| true);
+        assert_eq!(report.copied, [ChunkType::iCCP, ChunkType::tEXt]);
+        assert_eq!(report.skipped, [ChunkType::IHDR, ChunkType::IDAT, ChunkType::IEND]);
+        assert_eq!(types(&target), ["IHDR", "iCCP", "IDAT", "tEXt", "IEND"]);
+    }
+
+    #[test]
+    fn test_merge_skips_unsafe_between_images() {
+        let mut source = render("2x2");
+        source.chunks_mut().insert(1, chunk("iCCP", "perfil"));
+        source.chunks_mut().insert(3, chunk("tEXt", "Title\0hola"));
+        let mut target = render("4x4");
+
+        let report = target.merge_from(&source, |_| true);
+        assert_eq!(report.copied, [ChunkType::tEXt]);
+        assert_eq!(report.skipped, [ChunkType::IHDR, ChunkType::iCCP, ChunkType::IDAT, ChunkType::IEND]);
+    }
+
+    #[test]
+    fn test_merge_replaces_single_instance() {
+        let mut source = render("2x2");
+        source.chunks_mut().insert(1, chunk("iCCP", "nuevo"));
+        let mut target = render("2x2");
+        target.chunks_mut().insert(1, chunk("iCCP", "viejo"));
+
+        target.merge_from(&source, |ct| *ct == ChunkType::iCCP);
+        assert_eq!(types(&target), ["IHDR", "iCCP", "IDAT", "IEND"]);
+        assert_eq!(target.chunks()[1].data(), b"nuevo");
+    }
+}
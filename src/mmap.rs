@@ -0,0 +1,151 @@
+use std::fs::File;
+use std::path::Path;
+use memmap2::Mmap;
+use crate::chunk::Chunk;
+use crate::chunk_ref::ChunkRef;
+use crate::chunk_type::ChunkType;
+use crate::png::Png;
+use crate::{PngmeError, Result};
+
+// Cabecera de un chunk dentro del archivo mapeado; los datos no se leen hasta que se piden
+#[derive(Debug, Clone)]
+pub struct MappedChunk {
+    chunk_type: ChunkType,
+    offset: usize,
+    length: u32,
+    crc: u32,
+}
+
+impl MappedChunk {
+    pub fn chunk_type(&self) -> &ChunkType {
+        &self.chunk_type
+    }
+
+    // Offset absoluto del campo length dentro del archivo
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn length(&self) -> u32 {
+        self.length
+    }
+
+    pub fn crc(&self) -> u32 {
+        self.crc
+    }
+
+    fn data_range(&self) -> std::ops::Range<usize> {
+        let start = self.offset + 8;
+        start..start + self.length as usize
+    }
+}
+
+pub struct MappedPng {
+    mmap: Mmap,
+    chunks: Vec<MappedChunk>,
+}
+
+impl MappedPng {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<MappedPng> {
+        let file = File::open(path)?;
+        // SAFETY: el mapeo es de solo lectura; si otro proceso trunca el archivo mientras
+        // está mapeado el acceso puede fallar, igual que con cualquier lector de mmap
+        let mmap = unsafe { Mmap::map(&file)? };
+        let chunks = MappedPng::scan(&mmap)?;
+        Ok(MappedPng { mmap, chunks })
+    }
+
+    // Recorre sólo las cabeceras y los CRC, saltando por encima de los datos
+    fn scan(bytes: &[u8]) -> Result<Vec<MappedChunk>> {
+        if bytes.len() < 8 || bytes[..8] != Png::STANDARD_HEADER {
+            return Err(PngmeError::InvalidSignature);
+        }
+
+        let mut chunks = Vec::new();
+        let mut offset = 8;
+        while offset < bytes.len() {
+            let chunk = ChunkRef::parse_at(&bytes[offset..], offset)?;
+            let is_end = chunk.chunk_type() == ChunkType::IEND;
+            chunks.push(MappedChunk {
+                chunk_type: chunk.chunk_type(),
+                offset,
+                length: chunk.length(),
+                crc: chunk.crc(),
+            });
+            offset += chunk.total_len();
+            if is_end {
+                break;
+            }
+        }
+        Ok(chunks)
+    }
+
+    pub fn chunks(&self) -> &[MappedChunk] {
+        &self.chunks
+    }
+
+    pub fn chunk_by_type(&self, chunk_type: &str) -> Option<&MappedChunk> {
+        self.chunks
+            .iter()
+            .find(|chunk| chunk.chunk_type.to_string() == chunk_type)
+    }
+
+    // Sólo acá se tocan las páginas con el contenido del chunk
+    pub fn data(&self, chunk: &MappedChunk) -> &[u8] {
+        &self.mmap[chunk.data_range()]
+    }
+
+    // Copia el chunk a memoria verificando el CRC
+    pub fn load(&self, chunk: &MappedChunk) -> Result<Chunk> {
+        let loaded = Chunk::from_parsed(chunk.chunk_type, self.data(chunk).to_vec());
+        if loaded.crc() != chunk.crc {
+            let offset = chunk.offset + 8 + chunk.length as usize;
+            return Err(PngmeError::BadCrc { offset, expected: loaded.crc(), actual: chunk.crc });
+        }
+        Ok(loaded)
+    }
+
+    pub fn to_png(&self) -> Result<Png> {
+        let chunks = self
+            .chunks
+            .iter()
+            .map(|chunk| self.load(chunk))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Png::from_chunks(chunks))
+    }
+}
+
+impl Png {
+    pub fn open_mmap<P: AsRef<Path>>(path: P) -> Result<MappedPng> {
+        MappedPng::open(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn testing_file(name: &str) -> std::path::PathBuf {
+        let chunks = vec![
+            Chunk::new(ChunkType::from_str("FrSt").unwrap(), b"I am the first chunk".to_vec()).unwrap(),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), Vec::new()).unwrap(),
+        ];
+        let path = std::env::temp_dir().join(name);
+        std::fs::write(&path, Png::from_chunks(chunks).as_bytes()).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_open_mmap() {
+        let path = testing_file("pngme_test_open_mmap.png");
+        let mapped = Png::open_mmap(&path).unwrap();
+        assert_eq!(mapped.chunks().len(), 2);
+
+        let first = mapped.chunk_by_type("FrSt").unwrap();
+        assert_eq!(first.offset(), 8);
+        assert_eq!(mapped.data(first), b"I am the first chunk");
+        assert_eq!(mapped.to_png().unwrap().as_bytes(), std::fs::read(&path).unwrap());
+        std::fs::remove_file(path).unwrap();
+    }
+}
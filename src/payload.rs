@@ -0,0 +1,535 @@
+use std::fmt::Debug;
+use zeroize::Zeroize;
+use crate::compression::Compression;
+#[cfg(feature = "crypto")]
+use crate::crypto;
+#[cfg(feature = "age")]
+use crate::recipients::{self, Identities};
+use crate::secret::Secret;
+use crate::{tr, PngmeError, Result};
+use crate::locale::text;
+
+const ENCRYPTION_NONE: u8 = 0;
+// XChaCha20-Poly1305 con clave derivada por Argon2id con los parámetros por defecto de argon2;
+// le siguen salt (16) y nonce (24). Sólo se lee
+const ENCRYPTION_XCHACHA: u8 = 1;
+// Igual, pero con los parámetros de Argon2id en el encabezado antes del salt:
+// memoria en KiB (u32), iteraciones (u32) y paralelismo (u8)
+const ENCRYPTION_XCHACHA_ARGON2ID: u8 = 2;
+// Archivo age en formato binario, cifrado para uno o más destinatarios
+const ENCRYPTION_AGE: u8 = 3;
+// Sin cifrar, autenticado con HMAC-SHA256 y una clave derivada de la contraseña por Argon2id;
+// le siguen parámetros (9), salt (16), largo de los datos (u32) y la etiqueta (32)
+const INTEGRITY_HMAC_SHA256: u8 = 4;
+
+// Con qué se intenta abrir un mensaje cifrado
+enum Unlock<'a> {
+    Password(Option<&'a [u8]>),
+    #[cfg(feature = "age")]
+    Identities(&'a [Box<dyn age::Identity + Send + Sync>]),
+}
+
+// Mensaje guardado en un chunk. Si tiene alguna transformación se guarda dentro de un envoltorio:
+// firma "PNGm" + versión + método de compresión + método de cifrado [+ parámetros + salt + nonce] + datos.
+// El byte de cifrado también indica si el mensaje va sin cifrar pero con un HMAC.
+// La versión 1 no tenía el byte de cifrado. Se comprime antes de cifrar, y el encabezado
+// completo se autentica junto con el mensaje (salvo con age, que no admite datos asociados). Sin transformaciones se guarda tal cual, así los
+// mensajes de versiones anteriores se siguen leyendo igual
+#[derive(Clone, PartialEq, Eq)]
+pub struct Payload {
+    data: Secret<Vec<u8>>,
+    compression: Compression,
+    password: Option<Secret<Vec<u8>>>,
+    // la contraseña sólo se usa para el HMAC
+    hmac: bool,
+    #[cfg(feature = "crypto")]
+    kdf: crypto::KdfParams,
+    #[cfg(feature = "age")]
+    recipients: Vec<age::x25519::Recipient>,
+}
+
+impl Payload {
+    pub const MAGIC: [u8; 4] = *b"PNGm";
+    pub const VERSION: u8 = 2;
+    // Lo que agrega --encrypt: encabezado (7), parámetros (9), salt (16), nonce (24) y etiqueta de Poly1305 (16)
+    pub const ENCRYPTED_OVERHEAD: usize = 72;
+    // Lo más que se descomprime al abrir un mensaje: los archivos de pack y encode-file entran de sobra,
+    // y un envoltorio de pocos KB no puede agotar la memoria de decode, scan o analyze
+    pub const MAX_DECOMPRESSED: usize = 256 << 20;
+
+    pub fn new(data: impl Into<Vec<u8>>) -> Payload {
+        Payload {
+            data: Secret::new(data.into()),
+            compression: Compression::None,
+            password: None,
+            hmac: false,
+            #[cfg(feature = "crypto")]
+            kdf: crypto::KdfParams::default(),
+            #[cfg(feature = "age")]
+            recipients: Vec::new(),
+        }
+    }
+
+    pub fn compressed(mut self, compression: Compression) -> Payload {
+        self.compression = compression;
+        self
+    }
+
+    #[cfg(feature = "crypto")]
+    pub fn encrypted(mut self, password: impl Into<Secret<Vec<u8>>>) -> Payload {
+        self.password = Some(password.into());
+        self.hmac = false;
+        self
+    }
+
+    // No cifra, pero agrega un HMAC-SHA256 que se verifica al abrir el mensaje
+    #[cfg(feature = "crypto")]
+    pub fn authenticated(mut self, password: impl Into<Secret<Vec<u8>>>) -> Payload {
+        self.password = Some(password.into());
+        self.hmac = true;
+        self
+    }
+
+    // Costo de derivar la clave; por defecto KdfParams::default()
+    #[cfg(feature = "crypto")]
+    pub fn with_kdf(mut self, params: crypto::KdfParams) -> Payload {
+        self.kdf = params;
+        self
+    }
+
+    // Cifra para destinatarios age en lugar de usar una contraseña
+    #[cfg(feature = "age")]
+    pub fn encrypted_to(mut self, recipients: Vec<age::x25519::Recipient>) -> Payload {
+        self.recipients = recipients;
+        self
+    }
+
+    fn has_recipients(&self) -> bool {
+        #[cfg(feature = "age")]
+        return !self.recipients.is_empty();
+        #[cfg(not(feature = "age"))]
+        return false;
+    }
+
+    pub fn data(&self) -> &[u8] {
+        self.data.expose()
+    }
+
+    // Los datos dejan de borrarse solos al liberarse
+    pub fn into_data(self) -> Vec<u8> {
+        self.data.into_inner()
+    }
+
+    pub fn compression(&self) -> Compression {
+        self.compression
+    }
+
+    pub fn is_envelope(bytes: &[u8]) -> bool {
+        bytes.starts_with(&Payload::MAGIC)
+    }
+
+    pub fn is_encrypted(bytes: &[u8]) -> bool {
+        Payload::is_envelope(bytes)
+            && bytes.get(4) == Some(&2)
+            && bytes.get(6).is_some_and(|code| ![ENCRYPTION_NONE, INTEGRITY_HMAC_SHA256].contains(code))
+    }
+
+    // Sin cifrar pero con HMAC: hace falta la contraseña para verificarlo
+    pub fn is_authenticated(bytes: &[u8]) -> bool {
+        Payload::is_envelope(bytes) && bytes.get(4) == Some(&2) && bytes.get(6) == Some(&INTEGRITY_HMAC_SHA256)
+    }
+
+    // Cifrado para destinatarios age, no con contraseña
+    pub fn is_recipient_encrypted(bytes: &[u8]) -> bool {
+        Payload::is_encrypted(bytes) && bytes[6] == ENCRYPTION_AGE
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let _span = tracing::debug_span!("encode", bytes = self.data().len(), compression = ?self.compression).entered();
+        // un mensaje crudo que empieza con la firma se envuelve igual para no confundirlo
+        let protected = self.password.is_some() || self.has_recipients();
+        if self.compression == Compression::None && !protected && !Payload::is_envelope(self.data()) {
+            return Ok(self.data().to_vec());
+        }
+        if self.password.is_some() && self.has_recipients() {
+            let reason = text(
+                "no se puede cifrar con contraseña y con destinatarios age a la vez",
+                "cannot encrypt with a password and with age recipients at the same time"
+            );
+            return Err(PngmeError::InvalidPayload(reason.to_string()));
+        }
+        let body = Secret::new(self.compression.compress(self.data())?);
+        let mut bytes = Vec::with_capacity(body.expose().len() + 64);
+        bytes.extend_from_slice(&Payload::MAGIC);
+        bytes.push(Payload::VERSION);
+        bytes.push(self.compression.code());
+
+        #[cfg(feature = "age")]
+        if !self.recipients.is_empty() {
+            bytes.push(ENCRYPTION_AGE);
+            bytes.extend_from_slice(&recipients::encrypt(&self.recipients, body.expose())?);
+            return Ok(bytes);
+        }
+        match &self.password {
+            None => {
+                bytes.push(ENCRYPTION_NONE);
+                bytes.extend_from_slice(body.expose());
+            }
+            #[cfg(feature = "crypto")]
+            Some(password) if self.hmac => {
+                let salt = crypto::random_bytes::<{ crypto::SALT_LEN }>()?;
+                let length = u32::try_from(body.expose().len())
+                    .map_err(|_| {
+                        let reason = text("el mensaje es demasiado largo", "the message is too long");
+                        PngmeError::InvalidPayload(reason.to_string())
+                    })?;
+                bytes.push(INTEGRITY_HMAC_SHA256);
+                bytes.extend_from_slice(&self.kdf.to_bytes());
+                bytes.extend_from_slice(&salt);
+                bytes.extend_from_slice(&length.to_be_bytes());
+                let key = crypto::derive_key(password.expose(), &salt, &self.kdf)?;
+                let tag = crypto::hmac_sha256(key.expose(), &[&bytes, body.expose()]);
+                bytes.extend_from_slice(&tag);
+                bytes.extend_from_slice(body.expose());
+            }
+            #[cfg(feature = "crypto")]
+            Some(password) => {
+                let salt = crypto::random_bytes::<{ crypto::SALT_LEN }>()?;
+                let nonce = crypto::random_bytes::<{ crypto::NONCE_LEN }>()?;
+                bytes.push(ENCRYPTION_XCHACHA_ARGON2ID);
+                bytes.extend_from_slice(&self.kdf.to_bytes());
+                bytes.extend_from_slice(&salt);
+                bytes.extend_from_slice(&nonce);
+                let key = crypto::derive_key(password.expose(), &salt, &self.kdf)?;
+                let ciphertext = crypto::encrypt(key.expose(), &nonce, &bytes, body.expose())?;
+                bytes.extend_from_slice(&ciphertext);
+            }
+            #[cfg(not(feature = "crypto"))]
+            Some(_) => unreachable!("sólo se puede asignar una contraseña con la feature crypto"),
+        }
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Payload> {
+        Payload::open(bytes, None)
+    }
+
+    // Igual que from_bytes, pero descifra si el mensaje está cifrado
+    pub fn open(bytes: &[u8], password: Option<&[u8]>) -> Result<Payload> {
+        Payload::unlock(bytes, Unlock::Password(password))
+    }
+
+    // Abre un mensaje cifrado para destinatarios age con alguna de las identidades
+    #[cfg(feature = "age")]
+    pub fn open_with_identities(bytes: &[u8], identities: &Identities) -> Result<Payload> {
+        Payload::unlock(bytes, Unlock::Identities(identities))
+    }
+
+    fn unlock(bytes: &[u8], unlock: Unlock) -> Result<Payload> {
+        if !Payload::is_envelope(bytes) {
+            return Ok(Payload::new(bytes));
+        }
+        let incomplete = || {
+            let reason = text("el encabezado está incompleto", "the header is incomplete");
+            PngmeError::InvalidPayload(reason.to_string())
+        };
+        let version = *bytes.get(4).ok_or_else(incomplete)?;
+        let compression = Compression::from_code(*bytes.get(5).ok_or_else(incomplete)?)?;
+        let (encryption, header_len) = match version {
+            1 => (ENCRYPTION_NONE, 6),
+            2 => (*bytes.get(6).ok_or_else(incomplete)?, 7),
+            _ => return Err(PngmeError::InvalidPayload(tr!(
+                "versión no soportada: {}",
+                "unsupported version: {}",
+                version
+            ))),
+        };
+        let _span = tracing::debug_span!("open", bytes = bytes.len(), compression = ?compression, encryption).entered();
+
+        let body = match encryption {
+            ENCRYPTION_NONE => Secret::new(bytes[header_len..].to_vec()),
+            ENCRYPTION_XCHACHA | ENCRYPTION_XCHACHA_ARGON2ID | INTEGRITY_HMAC_SHA256 => {
+                // sin la feature age sólo existe la variante Password
+                #[cfg_attr(not(feature = "age"), allow(clippy::infallible_destructuring_match))]
+                let password = match unlock {
+                    Unlock::Password(password) => password,
+                    #[cfg(feature = "age")]
+                    Unlock::Identities(_) => None,
+                };
+                match encryption {
+                    INTEGRITY_HMAC_SHA256 => authenticate(bytes, header_len, password)?,
+                    _ => decrypt(bytes, header_len, encryption, password)?,
+                }
+            }
+            ENCRYPTION_AGE => match unlock {
+                #[cfg(feature = "age")]
+                Unlock::Identities(identities) => recipients::decrypt(identities, &bytes[header_len..])?,
+                #[cfg(feature = "age")]
+                Unlock::Password(_) => {
+                    let reason = text(
+                        "el mensaje está cifrado para destinatarios age; hace falta una identidad",
+                        "the message is encrypted for age recipients; an identity is needed"
+                    );
+                    return Err(PngmeError::InvalidPayload(reason.to_string()));
+                }
+                #[cfg(not(feature = "age"))]
+                Unlock::Password(_) => {
+                    let reason = text(
+                        "pngme se compiló sin soporte para age (feature \"age\")",
+                        "pngme was built without age support (feature \"age\")"
+                    );
+                    return Err(PngmeError::InvalidPayload(reason.to_string()));
+                }
+            },
+            _ => return Err(PngmeError::InvalidPayload(tr!(
+                "método de cifrado desconocido: {}",
+                "unknown encryption method: {}",
+                encryption
+            ))),
+        };
+        let data = compression.decompress(body.expose(), Payload::MAX_DECOMPRESSED)?;
+        Ok(Payload { compression, ..Payload::new(data) })
+    }
+
+    // Texto del mensaje; falla si no es UTF-8
+    pub fn into_string(self) -> Result<String> {
+        String::from_utf8(self.into_data()).map_err(|err| {
+            let index = err.utf8_error().valid_up_to();
+            err.into_bytes().zeroize();
+            PngmeError::InvalidUtf8 { index }
+        })
+    }
+}
+
+#[cfg(feature = "crypto")]
+fn decrypt(bytes: &[u8], header_len: usize, encryption: u8, password: Option<&[u8]>) -> Result<Secret<Vec<u8>>> {
+    use crypto::KdfParams;
+
+    let password =
+        password.ok_or_else(|| {
+            let reason = text(
+                "el mensaje está cifrado; hace falta la contraseña",
+                "the message is encrypted; the password is needed"
+            );
+            PngmeError::InvalidPayload(reason.to_string())
+        })?;
+    let params_len = if encryption == ENCRYPTION_XCHACHA_ARGON2ID { KdfParams::ENCODED_LEN } else { 0 };
+    let salt_start = header_len + params_len;
+    let nonce_start = salt_start + crypto::SALT_LEN;
+    let nonce_end = nonce_start + crypto::NONCE_LEN;
+    if bytes.len() < nonce_end {
+        let reason = text("el encabezado está incompleto", "the header is incomplete");
+        return Err(PngmeError::InvalidPayload(reason.to_string()));
+    }
+
+    let params = match encryption {
+        ENCRYPTION_XCHACHA_ARGON2ID => {
+            KdfParams::from_bytes(bytes[header_len..salt_start].try_into().expect("largo verificado"))?
+        }
+        _ => KdfParams::LEGACY,
+    };
+    let nonce: [u8; crypto::NONCE_LEN] = bytes[nonce_start..nonce_end].try_into().expect("largo verificado");
+    let key = crypto::derive_key(password, &bytes[salt_start..nonce_start], &params)?;
+    crypto::decrypt(key.expose(), &nonce, &bytes[..nonce_end], &bytes[nonce_end..])
+}
+
+#[cfg(not(feature = "crypto"))]
+fn decrypt(_bytes: &[u8], _header_len: usize, _encryption: u8, _password: Option<&[u8]>) -> Result<Secret<Vec<u8>>> {
+    let reason = text(
+        "pngme se compiló sin soporte de cifrado (feature \"crypto\")",
+        "pngme was built without encryption support (feature \"crypto\")"
+    );
+    Err(PngmeError::InvalidPayload(reason.to_string()))
+}
+
+// Verifica el HMAC y devuelve los datos. El largo guardado distingue un mensaje truncado de uno alterado
+#[cfg(feature = "crypto")]
+fn authenticate(bytes: &[u8], header_len: usize, password: Option<&[u8]>) -> Result<Secret<Vec<u8>>> {
+    use crypto::KdfParams;
+
+    let password = password.ok_or_else(|| {
+        let reason = text(
+            "el mensaje tiene un código de integridad; hace falta la contraseña",
+            "the message has an integrity code; the password is needed"
+        );
+        PngmeError::InvalidPayload(reason.to_string())
+    })?;
+    let salt_start = header_len + KdfParams::ENCODED_LEN;
+    let length_start = salt_start + crypto::SALT_LEN;
+    let tag_start = length_start + 4;
+    let body_start = tag_start + crypto::TAG_LEN;
+    if bytes.len() < tag_start {
+        let reason = text("el encabezado está incompleto", "the header is incomplete");
+        return Err(PngmeError::InvalidPayload(reason.to_string()));
+    }
+
+    let length = u32::from_be_bytes(bytes[length_start..tag_start].try_into().expect("largo verificado")) as usize;
+    let expected = body_start.saturating_add(length);
+    if bytes.len() < expected {
+        return Err(PngmeError::PayloadTruncated { expected, actual: bytes.len() });
+    }
+    if bytes.len() > expected {
+        return Err(PngmeError::InvalidPayload(tr!(
+            "sobran {} bytes después de los datos autenticados",
+            "{} extra bytes after the authenticated data",
+            bytes.len() - expected
+        )));
+    }
+
+    let params = KdfParams::from_bytes(bytes[header_len..salt_start].try_into().expect("largo verificado"))?;
+    let key = crypto::derive_key(password, &bytes[salt_start..length_start], &params)?;
+    let body = &bytes[body_start..];
+    crypto::verify_hmac_sha256(key.expose(), &[&bytes[..tag_start], body], &bytes[tag_start..body_start])?;
+    Ok(Secret::new(body.to_vec()))
+}
+
+#[cfg(not(feature = "crypto"))]
+fn authenticate(_bytes: &[u8], _header_len: usize, _password: Option<&[u8]>) -> Result<Secret<Vec<u8>>> {
+    let reason = text(
+        "pngme se compiló sin soporte de cifrado (feature \"crypto\")",
+        "pngme was built without encryption support (feature \"crypto\")"
+    );
+    Err(PngmeError::InvalidPayload(reason.to_string()))
+}
+
+// La contraseña nunca aparece en la salida de depuración
+impl Debug for Payload {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Payload")
+            .field("data", &self.data)
+            .field("compression", &self.compression)
+            .field("encrypted", &((self.password.is_some() && !self.hmac) || self.has_recipients()))
+            .field("hmac", &self.hmac)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_payload_is_stored_raw() {
+        let bytes = Payload::new("hola").to_bytes().unwrap();
+        assert_eq!(bytes, b"hola");
+        assert_eq!(Payload::from_bytes(&bytes).unwrap().into_string().unwrap(), "hola");
+    }
+
+    #[test]
+    fn test_compressed_round_trip() {
+        let message = "un mensaje bastante repetido ".repeat(20);
+        let bytes = Payload::new(message.clone()).compressed(Compression::Deflate).to_bytes().unwrap();
+        assert!(Payload::is_envelope(&bytes));
+        assert!(bytes.len() < message.len());
+        let payload = Payload::from_bytes(&bytes).unwrap();
+        assert_eq!(payload.compression(), Compression::Deflate);
+        assert_eq!(payload.into_string().unwrap(), message);
+    }
+
+    #[test]
+    fn test_raw_data_that_looks_like_envelope() {
+        let bytes = Payload::new(&b"PNGm no es un envoltorio"[..]).to_bytes().unwrap();
+        assert_eq!(Payload::from_bytes(&bytes).unwrap().data(), b"PNGm no es un envoltorio");
+    }
+
+    #[test]
+    fn test_invalid_envelope() {
+        assert!(Payload::from_bytes(b"PNGm").is_err());
+        assert!(Payload::from_bytes(b"PNGm\x09\x00datos").is_err());
+        assert!(Payload::from_bytes(b"PNGm\x01\x07datos").is_err());
+        assert!(Payload::from_bytes(b"PNGm\x02\x00\x09datos").is_err());
+    }
+
+    #[test]
+    fn test_reads_version_1() {
+        let bytes = [&Payload::MAGIC[..], &[1, 1], &crate::compression::deflate(b"viejo").unwrap()].concat();
+        assert_eq!(Payload::from_bytes(&bytes).unwrap().data(), b"viejo");
+    }
+
+    #[cfg(feature = "crypto")]
+    #[test]
+    fn test_encrypted_round_trip() {
+        let payload = Payload::new("secreto").compressed(Compression::Deflate).encrypted("clave").with_kdf(FAST_KDF);
+        assert!(!format!("{:?}", payload).contains("clave"));
+        let bytes = payload.to_bytes().unwrap();
+        assert!(Payload::is_encrypted(&bytes));
+        assert!(Payload::from_bytes(&bytes).is_err());
+        assert!(matches!(Payload::open(&bytes, Some(b"otra")), Err(PngmeError::DecryptionFailed)));
+        let opened = Payload::open(&bytes, Some(b"clave")).unwrap();
+        assert_eq!(opened.compression(), Compression::Deflate);
+        assert_eq!(opened.into_string().unwrap(), "secreto");
+
+        // el encabezado está autenticado: cambiar el método de compresión invalida el mensaje
+        let mut tampered = bytes.clone();
+        tampered[5] = 0;
+        assert!(matches!(Payload::open(&tampered, Some(b"clave")), Err(PngmeError::DecryptionFailed)));
+    }
+
+    #[cfg(feature = "crypto")]
+    const FAST_KDF: crypto::KdfParams = crypto::KdfParams { memory_kib: 1024, iterations: 1, parallelism: 1 };
+
+    #[cfg(feature = "crypto")]
+    #[test]
+    fn test_kdf_params_are_stored() {
+        let bytes = Payload::new("secreto").encrypted("clave").with_kdf(FAST_KDF).to_bytes().unwrap();
+        assert_eq!(bytes[6], ENCRYPTION_XCHACHA_ARGON2ID);
+        assert_eq!(&bytes[7..16], &FAST_KDF.to_bytes());
+        assert_eq!(Payload::open(&bytes, Some(b"clave")).unwrap().data(), b"secreto");
+
+        // parámetros absurdos en el encabezado se rechazan antes de derivar la clave
+        let mut hostile = bytes.clone();
+        hostile[7..11].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(matches!(Payload::open(&hostile, Some(b"clave")), Err(PngmeError::InvalidPayload(_))));
+    }
+
+    #[cfg(feature = "crypto")]
+    #[test]
+    fn test_hmac_round_trip() {
+        let payload = Payload::new("público").compressed(Compression::Deflate).authenticated("clave").with_kdf(FAST_KDF);
+        assert!(!format!("{:?}", payload).contains("clave"));
+        let bytes = payload.to_bytes().unwrap();
+        assert!(Payload::is_authenticated(&bytes));
+        assert!(!Payload::is_encrypted(&bytes));
+        assert!(Payload::from_bytes(&bytes).is_err());
+        assert!(matches!(Payload::open(&bytes, Some(b"otra")), Err(PngmeError::IntegrityCheckFailed)));
+        assert_eq!(Payload::open(&bytes, Some(b"clave")).unwrap().into_string().unwrap(), "público");
+
+        let mut tampered = bytes.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(matches!(Payload::open(&tampered, Some(b"clave")), Err(PngmeError::IntegrityCheckFailed)));
+
+        let truncated = &bytes[..bytes.len() - 3];
+        assert!(matches!(
+            Payload::open(truncated, Some(b"clave")),
+            Err(PngmeError::PayloadTruncated { expected, actual }) if expected == bytes.len() && actual == truncated.len()
+        ));
+    }
+
+    #[cfg(feature = "age")]
+    #[test]
+    fn test_age_recipients() {
+        use age::x25519::Identity;
+
+        let teammate = Identity::generate();
+        let outsider = Identity::generate();
+        let bytes = Payload::new("config").encrypted_to(vec![teammate.to_public()]).to_bytes().unwrap();
+        assert!(Payload::is_recipient_encrypted(&bytes));
+        assert!(Payload::open(&bytes, Some(b"clave")).is_err());
+
+        let identities: Identities = vec![Box::new(outsider)];
+        assert!(Payload::open_with_identities(&bytes, &identities).is_err());
+        let identities: Identities = vec![Box::new(teammate)];
+        assert_eq!(Payload::open_with_identities(&bytes, &identities).unwrap().data(), b"config");
+    }
+
+    #[cfg(feature = "crypto")]
+    #[test]
+    fn test_reads_legacy_encryption() {
+        let salt = [3; crypto::SALT_LEN];
+        let nonce = [4; crypto::NONCE_LEN];
+        let header = [&Payload::MAGIC[..], &[2, 0, ENCRYPTION_XCHACHA], &salt, &nonce].concat();
+        let key = crypto::derive_key(b"clave", &salt, &crypto::KdfParams::LEGACY).unwrap();
+        let bytes = [header.clone(), crypto::encrypt(key.expose(), &nonce, &header, b"viejo").unwrap()].concat();
+        assert_eq!(Payload::open(&bytes, Some(b"clave")).unwrap().data(), b"viejo");
+    }
+}
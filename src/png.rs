@@ -0,0 +1,991 @@
+use std::fmt::Display;
+use std::io::{Read, Write};
+use std::ops::Index;
+use std::str::FromStr;
+use crate::chunk::{Chunk, ParseOptions};
+use crate::chunk_ref::ChunkRef;
+use crate::chunk_type::{ChunkType, Placement};
+use crate::suggest;
+use crate::typed::Ihdr;
+use crate::{tr, PngmeError, Result};
+
+// Dónde queda un chunk en el archivo escrito: desde el length hasta el CRC inclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkSpan {
+    pub offset: usize,
+    pub len: usize,
+}
+
+impl ChunkSpan {
+    // después del length y el tipo
+    pub fn data_offset(&self) -> usize {
+        self.offset + 8
+    }
+
+    pub fn end(&self) -> usize {
+        self.offset + self.len
+    }
+}
+
+// Dónde agrega append_chunk_with los chunks auxiliares
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppendPolicy {
+    BeforeIend,
+    // algunos lectores sólo miran los auxiliares que están antes del primer IDAT
+    BeforeIdat,
+}
+
+pub struct Png {
+    header: [u8; 8],
+    chunks: Vec<Chunk>,
+}
+
+impl Png {
+    pub const STANDARD_HEADER: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
+
+    pub fn from_chunks(chunks: Vec<Chunk>) -> Png {
+        Png {
+            header: Png::STANDARD_HEADER,
+            chunks,
+        }
+    }
+
+    // Agrega el chunk antes de IEND (al final si no hay), que es donde un lector estricto todavía lo lee
+    pub fn append_chunk(&mut self, chunk: Chunk) -> Result<()> {
+        self.append_chunk_with(chunk, AppendPolicy::BeforeIend)
+    }
+
+    // Como append_chunk, pero con BeforeIdat los auxiliares quedan antes de los datos de la imagen. Los
+    // críticos van siempre donde los pide la especificación: IHDR primero, PLTE antes del primer IDAT, un
+    // IDAT más detrás del último e IEND al final. Un segundo IHDR, PLTE o IEND no se acepta
+    pub fn append_chunk_with(&mut self, chunk: Chunk, policy: AppendPolicy) -> Result<()> {
+        let chunk_type = *chunk.chunk_type();
+        let position = |ct: ChunkType| self.chunks.iter().position(|chunk| *chunk.chunk_type() == ct);
+        let single = [ChunkType::IHDR, ChunkType::PLTE, ChunkType::IEND].contains(&chunk_type);
+        if single && position(chunk_type).is_some() {
+            return Err(PngmeError::DuplicateCriticalChunk { chunk_type: chunk_type.to_string() });
+        }
+        let iend = position(ChunkType::IEND).unwrap_or(self.chunks.len());
+        let first_idat = position(ChunkType::IDAT);
+        let last_idat = self.chunks.iter().rposition(|chunk| *chunk.chunk_type() == ChunkType::IDAT);
+        let index = match chunk_type {
+            ChunkType::IHDR => 0,
+            ChunkType::IEND => self.chunks.len(),
+            ChunkType::PLTE => first_idat.unwrap_or(iend),
+            ChunkType::IDAT => last_idat.map_or(iend, |index| index + 1),
+            _ if chunk_type.is_critical() || policy == AppendPolicy::BeforeIend => iend,
+            _ => first_idat.unwrap_or(iend),
+        };
+        self.chunks.insert(index, chunk);
+        Ok(())
+    }
+
+    // Quita el primero de ese tipo y lo devuelve, para deshacer o llevarlo a otro PNG
+    pub fn remove_chunk(&mut self, chunk_type: &str) -> Result<Chunk> {
+        self.remove_chunk_nth(chunk_type, 0)
+    }
+
+    // Quita el chunk número `nth` (desde 0) de ese tipo, para cuando hay varios. Los críticos de la
+    // especificación no: eso es remove_chunk_nth_forced
+    pub fn remove_chunk_nth(&mut self, chunk_type: &str, nth: usize) -> Result<Chunk> {
+        refuse_critical(chunk_type)?;
+        self.remove_chunk_nth_forced(chunk_type, nth)
+    }
+
+    // remove_chunk_nth aunque sea IHDR, PLTE, IDAT o IEND
+    pub fn remove_chunk_nth_forced(&mut self, chunk_type: &str, nth: usize) -> Result<Chunk> {
+        let wanted = ChunkType::from_str(chunk_type)?;
+        let index = self.chunks_by_type(chunk_type).nth(nth).map(|(index, _)| index);
+        if let Some(index) = index {
+            return Ok(self.chunks.remove(index));
+        }
+        let count = self.chunks_by_type(chunk_type).count();
+        if count == 0 {
+            return Err(self.chunk_not_found(&wanted.to_string()));
+        }
+        Err(PngmeError::ChunkIndexOutOfRange { chunk_type: Some(wanted.to_string()), index: nth + 1, count })
+    }
+
+    // Quita todos los chunks de ese tipo y los devuelve en el orden en que estaban; vacío si no había
+    // ninguno
+    pub fn remove_chunks(&mut self, chunk_type: &str) -> Result<Vec<Chunk>> {
+        refuse_critical(chunk_type)?;
+        self.remove_chunks_forced(chunk_type)
+    }
+
+    // remove_chunks aunque sea IHDR, PLTE, IDAT o IEND
+    pub fn remove_chunks_forced(&mut self, chunk_type: &str) -> Result<Vec<Chunk>> {
+        let wanted = ChunkType::from_str(chunk_type)?;
+        let (removed, kept) = std::mem::take(&mut self.chunks).into_iter().partition(|chunk| *chunk.chunk_type() == wanted);
+        self.chunks = kept;
+        Ok(removed)
+    }
+
+    // Agrega el chunk en la primera posición válida para su tipo según la especificación, en lugar de antes
+    // de IEND como append_chunk. Devuelve dónde quedó
+    pub fn insert_chunk(&mut self, chunk: Chunk) -> usize {
+        let index = self.legal_insert_index(chunk.chunk_type());
+        self.chunks.insert(index, chunk);
+        index
+    }
+
+    // Agrega el chunk en esa posición, corriendo los siguientes; con la cantidad de chunks queda al final
+    pub fn insert_chunk_at(&mut self, index: usize, chunk: Chunk) -> Result<()> {
+        if index > self.chunks.len() {
+            return Err(PngmeError::ChunkIndexOutOfRange { chunk_type: None, index, count: self.chunks.len() });
+        }
+        self.chunks.insert(index, chunk);
+        Ok(())
+    }
+
+    // Agrega el chunk justo antes del primero de ese tipo. Devuelve dónde quedó
+    pub fn insert_before(&mut self, chunk_type: &str, chunk: Chunk) -> Result<usize> {
+        let wanted = ChunkType::from_str(chunk_type)?;
+        let index = self
+            .chunks
+            .iter()
+            .position(|chunk| *chunk.chunk_type() == wanted)
+            .ok_or_else(|| self.chunk_not_found(chunk_type))?;
+        self.chunks.insert(index, chunk);
+        Ok(index)
+    }
+
+    // Agrega el chunk justo después del último de ese tipo, para que after IDAT quede detrás de todos los
+    // IDAT. Devuelve dónde quedó
+    pub fn insert_after(&mut self, chunk_type: &str, chunk: Chunk) -> Result<usize> {
+        let wanted = ChunkType::from_str(chunk_type)?;
+        let index = self
+            .chunks
+            .iter()
+            .rposition(|chunk| *chunk.chunk_type() == wanted)
+            .ok_or_else(|| self.chunk_not_found(chunk_type))?
+            + 1;
+        self.chunks.insert(index, chunk);
+        Ok(index)
+    }
+
+    // Quita el chunk en esa posición, sin importar su tipo; None si no existe
+    pub fn remove_chunk_at(&mut self, index: usize) -> Option<Chunk> {
+        (index < self.chunks.len()).then(|| self.chunks.remove(index))
+    }
+
+    // Cambia los datos del primer chunk de ese tipo sin moverlo de lugar; devuelve los datos anteriores
+    pub fn replace_chunk_data(&mut self, chunk_type: &str, chunk_data: Vec<u8>) -> Result<Vec<u8>> {
+        let wanted = ChunkType::from_str(chunk_type)?;
+        match self.chunks.iter_mut().find(|chunk| *chunk.chunk_type() == wanted) {
+            Some(chunk) => chunk.set_data(chunk_data),
+            None => Err(self.chunk_not_found(chunk_type)),
+        }
+    }
+
+    // Pone otro chunk en esa posición y devuelve el anterior; None (sin cambiar nada) si no existe
+    pub fn replace_chunk_at(&mut self, index: usize, chunk: Chunk) -> Option<Chunk> {
+        let slot = self.chunks.get_mut(index)?;
+        Some(std::mem::replace(slot, chunk))
+    }
+
+    // Escribe `bytes` sobre los datos del chunk `index` desde `offset`; se puede escribir justo al final para
+    // alargarlos, pero no dejar un hueco. Devuelve los bytes que se pisaron
+    pub fn patch_chunk_at(&mut self, index: usize, offset: usize, bytes: &[u8]) -> Result<Vec<u8>> {
+        let count = self.chunks.len();
+        let Some(chunk) = self.chunks.get_mut(index) else {
+            return Err(PngmeError::ChunkIndexOutOfRange { chunk_type: None, index, count });
+        };
+        let mut data = chunk.data().to_vec();
+        if offset > data.len() {
+            let chunk_type = chunk.chunk_type().to_string();
+            return Err(PngmeError::DataOffsetOutOfRange { chunk_type, offset, length: data.len() });
+        }
+        let end = offset + bytes.len();
+        let old = data[offset..end.min(data.len())].to_vec();
+        if end > data.len() {
+            data.resize(end, 0);
+        }
+        data[offset..end].copy_from_slice(bytes);
+        chunk.set_data(data)?;
+        Ok(old)
+    }
+
+    // Mueve los chunks a posiciones válidas según la especificación sin tocar su contenido.
+    // Los chunks del mismo tipo conservan su orden relativo. Devuelve cuántos cambiaron de lugar
+    pub fn normalize_order(&mut self) -> usize {
+        let types: Vec<ChunkType> = self.chunks.iter().map(|chunk| *chunk.chunk_type()).collect();
+        let plte = types.iter().position(|t| *t == ChunkType::PLTE);
+        let first_idat = types.iter().position(|t| *t == ChunkType::IDAT);
+
+        // 0 IHDR, 1 antes de PLTE, 2 PLTE, 3 entre PLTE e IDAT, 4 IDAT, 5 después de IDAT, 6 IEND
+        let region = |index: usize| -> u8 {
+            if first_idat.is_some_and(|idat| index > idat) {
+                5
+            } else if plte.is_some_and(|plte| index > plte) {
+                3
+            } else {
+                1
+            }
+        };
+        let rank = |index: usize| -> u8 {
+            let chunk_type = types[index];
+            if chunk_type == ChunkType::PLTE {
+                return 2;
+            }
+            match chunk_type.constraints().map(|constraints| constraints.placement) {
+                Some(Placement::First) => 0,
+                Some(Placement::BeforePlte) => 1,
+                Some(Placement::BetweenPlteAndIdat) => 3,
+                Some(Placement::BeforeIdat) => region(index).min(3),
+                Some(Placement::Idat) => 4,
+                Some(Placement::AfterIdat) => 5,
+                Some(Placement::Last) => 6,
+                Some(Placement::Anywhere) | None => region(index),
+            }
+        };
+
+        let mut order: Vec<usize> = (0..types.len()).collect();
+        order.sort_by_key(|index| rank(*index));
+        let moved = order.iter().enumerate().filter(|(to, from)| to != *from).count();
+
+        let mut chunks: Vec<Option<Chunk>> = std::mem::take(&mut self.chunks).into_iter().map(Some).collect();
+        self.chunks = order.into_iter().filter_map(|index| chunks[index].take()).collect();
+        moved
+    }
+
+    pub fn header(&self) -> &[u8; 8] {
+        &self.header
+    }
+
+    // Error para un tipo que no está en el archivo, sugiriendo los tipos presentes que se le parecen
+    pub fn chunk_not_found(&self, chunk_type: &str) -> PngmeError {
+        let types: Vec<String> = self.chunks.iter().map(|chunk| chunk.chunk_type().to_string()).collect();
+        let similar = suggest::similar(chunk_type, types.iter().map(String::as_str));
+        PngmeError::ChunkNotFound {
+            chunk_type: chunk_type.to_string(),
+            similar: similar.into_iter().map(str::to_string).collect(),
+        }
+    }
+
+    // Datos del IHDR, que tiene que ser el primer chunk
+    pub fn header_info(&self) -> Result<Ihdr> {
+        let first = self
+            .chunks
+            .first()
+            .filter(|chunk| *chunk.chunk_type() == ChunkType::IHDR)
+            .ok_or_else(|| self.chunk_not_found("IHDR"))?;
+        Ihdr::from_chunk(first)
+    }
+
+    pub fn chunks(&self) -> &[Chunk] {
+        &self.chunks
+    }
+
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Chunk> {
+        self.chunks.get(index)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Chunk> {
+        self.chunks.iter()
+    }
+
+    // Un span por chunk, en el mismo orden, con los offsets que tendrían en as_bytes
+    pub fn chunk_spans(&self) -> Vec<ChunkSpan> {
+        let mut offset = Png::STANDARD_HEADER.len();
+        self.chunks
+            .iter()
+            .map(|chunk| {
+                let span = ChunkSpan { offset, len: 12 + chunk.length() as usize };
+                offset = span.end();
+                span
+            })
+            .collect()
+    }
+
+    // Primera posición donde un chunk de este tipo queda en un lugar válido según la especificación
+    pub(crate) fn legal_insert_index(&self, chunk_type: &ChunkType) -> usize {
+        let position = |ct: ChunkType| self.chunks.iter().position(|chunk| *chunk.chunk_type() == ct);
+        let iend = position(ChunkType::IEND).unwrap_or(self.chunks.len());
+        let first_idat = position(ChunkType::IDAT);
+        match chunk_type.constraints().map(|constraints| constraints.placement) {
+            Some(Placement::First) => 0,
+            Some(Placement::BeforePlte) => position(ChunkType::PLTE).or(first_idat).unwrap_or(iend),
+            Some(Placement::BetweenPlteAndIdat | Placement::BeforeIdat) => first_idat.unwrap_or(iend),
+            Some(Placement::Idat) => match self.chunks.iter().rposition(|chunk| *chunk.chunk_type() == ChunkType::IDAT) {
+                Some(last) => last + 1,
+                None => iend,
+            },
+            Some(Placement::AfterIdat | Placement::Anywhere | Placement::Last) | None => iend,
+        }
+    }
+
+    pub(crate) fn chunks_mut(&mut self) -> &mut Vec<Chunk> {
+        &mut self.chunks
+    }
+
+    pub fn iter_refs(&self) -> impl Iterator<Item = ChunkRef<'_>> {
+        self.chunks.iter().map(ChunkRef::from)
+    }
+
+    pub fn chunk_by_type(&self, chunk_type: &str) -> Option<&Chunk> {
+        let chunk_type = ChunkType::from_str(chunk_type).ok()?;
+        self.chunks
+            .iter()
+            .find(|chunk| *chunk.chunk_type() == chunk_type)
+    }
+
+    // Todos los chunks de ese tipo con su índice, para los que se repiten (varios tEXt, un mensaje partido)
+    pub fn chunks_by_type(&self, chunk_type: &str) -> impl Iterator<Item = (usize, &Chunk)> {
+        let wanted = ChunkType::from_str(chunk_type).ok();
+        self.chunks
+            .iter()
+            .enumerate()
+            .filter(move |(_, chunk)| Some(*chunk.chunk_type()) == wanted)
+    }
+
+    // Lee el PNG desde un stream, chunk por chunk, y se detiene en IEND
+    pub fn from_reader<R: Read>(reader: &mut R) -> Result<Png> {
+        Png::from_reader_with(reader, &ParseOptions::default())
+    }
+
+    pub fn from_reader_with<R: Read>(reader: &mut R, options: &ParseOptions) -> Result<Png> {
+        let _span = tracing::debug_span!("parse").entered();
+        let mut header = [0; 8];
+        reader.read_exact(&mut header).map_err(|_| PngmeError::InvalidSignature)?;
+        if header != Png::STANDARD_HEADER {
+            return Err(PngmeError::InvalidSignature);
+        }
+
+        let mut chunks = Vec::new();
+        let mut offset = 8;
+        while let Some(chunk) = Chunk::from_reader_at(reader, offset, options)? {
+            tracing::trace!(offset, chunk_type = %chunk.chunk_type(), length = chunk.length(), "chunk leído");
+            offset += 12 + chunk.length() as usize;
+            let is_end = *chunk.chunk_type() == ChunkType::IEND;
+            chunks.push(chunk);
+            if is_end {
+                break;
+            }
+        }
+
+        Ok(Png::from_chunks(chunks))
+    }
+
+    // Como TryFrom<&[u8]>, con otros límites
+    pub fn parse_with(value: &[u8], options: &ParseOptions) -> Result<Png> {
+        let _span = tracing::debug_span!("parse", bytes = value.len()).entered();
+        if value.len() < 8 || value[..8] != Png::STANDARD_HEADER {
+            return Err(PngmeError::InvalidSignature);
+        }
+
+        let mut chunks = Vec::new();
+        let mut offset = 8;
+        while offset < value.len() {
+            let chunk = Chunk::from_bytes_at(&value[offset..], offset, options)?;
+            tracing::trace!(offset, chunk_type = %chunk.chunk_type(), length = chunk.length(), "chunk leído");
+            // length + tipo + datos + crc
+            offset += 12 + chunk.length() as usize;
+            chunks.push(chunk);
+        }
+
+        tracing::debug!(chunks = chunks.len(), "PNG leído");
+        Ok(Png::from_chunks(chunks))
+    }
+
+    // La salida es byte a byte igual a la entrada: se respetan la firma, el orden de los chunks
+    // y el CRC leído. Sólo cambian los chunks que se editaron explícitamente
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let _span = tracing::debug_span!("write", chunks = self.chunks.len()).entered();
+        writer.write_all(&self.header)?;
+        for chunk in &self.chunks {
+            chunk.write_to(writer)?;
+        }
+        Ok(())
+    }
+
+    pub fn as_bytes(&self) -> Vec<u8> {
+        self.header
+            .iter()
+            .copied()
+            .chain(self.chunks.iter().flat_map(|chunk| chunk.as_bytes()))
+            .collect()
+    }
+}
+
+// Un crítico desconocido sí se puede quitar: ningún lector lo entiende de todos modos
+fn refuse_critical(chunk_type: &str) -> Result<()> {
+    let chunk_type = ChunkType::from_str(chunk_type)?;
+    if chunk_type.is_critical() && chunk_type.is_standard() {
+        return Err(PngmeError::CriticalChunkRemoval { chunk_type: chunk_type.to_string() });
+    }
+    Ok(())
+}
+
+// Header (8 bytes) -> Chunk -> Chunk -> ... -> IEND
+impl TryFrom<&[u8]> for Png {
+    type Error = PngmeError;
+    fn try_from(value: &[u8]) -> Result<Png> {
+        Png::parse_with(value, &ParseOptions::default())
+    }
+}
+
+impl Index<usize> for Png {
+    type Output = Chunk;
+    fn index(&self, index: usize) -> &Chunk {
+        &self.chunks[index]
+    }
+}
+
+impl IntoIterator for Png {
+    type Item = Chunk;
+    type IntoIter = std::vec::IntoIter<Chunk>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.chunks.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Png {
+    type Item = &'a Chunk;
+    type IntoIter = std::slice::Iter<'a, Chunk>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.chunks.iter()
+    }
+}
+
+impl Display for Png {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", tr!("PNG con {} chunks", "PNG with {} chunks", self.chunks.len()))?;
+        for (index, chunk) in self.chunks.iter().enumerate() {
+            write!(f, "#{} ", index)?;
+            match f.precision() {
+                Some(max) => write!(f, "{:.*}", max, chunk)?,
+                None => write!(f, "{}", chunk)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::Chunk;
+    use crate::chunk_type::ChunkType;
+    use std::convert::TryFrom;
+    use std::str::FromStr;
+
+    fn testing_chunks() -> Vec<Chunk> {
+        vec![
+            chunk_from_strings("FrSt", "I am the first chunk").unwrap(),
+            chunk_from_strings("miDl", "I am another chunk").unwrap(),
+            chunk_from_strings("LASt", "I am the last chunk").unwrap(),
+        ]
+    }
+
+    fn testing_png() -> Png {
+        let chunks = testing_chunks();
+        Png::from_chunks(chunks)
+    }
+
+    fn chunk_from_strings(chunk_type: &str, data: &str) -> Result<Chunk> {
+        let chunk_type = ChunkType::from_str(chunk_type)?;
+        let data: Vec<u8> = data.bytes().collect();
+
+        Ok(Chunk::new(chunk_type, data).unwrap())
+    }
+
+    #[test]
+    fn test_from_chunks() {
+        let chunks = testing_chunks();
+        let png = Png::from_chunks(chunks);
+
+        assert_eq!(png.chunks().len(), 3);
+    }
+
+    #[test]
+    fn test_valid_from_bytes() {
+        let chunk_bytes: Vec<u8> = testing_chunks()
+            .into_iter()
+            .flat_map(|chunk| chunk.as_bytes())
+            .collect();
+
+        let bytes: Vec<u8> = Png::STANDARD_HEADER
+            .iter()
+            .chain(chunk_bytes.iter())
+            .copied()
+            .collect();
+
+        let png = Png::try_from(bytes.as_ref());
+
+        assert!(png.is_ok());
+    }
+
+    #[test]
+    fn test_invalid_header() {
+        let chunk_bytes: Vec<u8> = testing_chunks()
+            .into_iter()
+            .flat_map(|chunk| chunk.as_bytes())
+            .collect();
+
+        let bytes: Vec<u8> = [13, 80, 78, 71, 13, 10, 26, 10]
+            .iter()
+            .chain(chunk_bytes.iter())
+            .copied()
+            .collect();
+
+        let png = Png::try_from(bytes.as_ref());
+
+        assert!(png.is_err());
+    }
+
+    #[test]
+    fn test_invalid_chunk() {
+        let mut chunk_bytes: Vec<u8> = testing_chunks()
+            .into_iter()
+            .flat_map(|chunk| chunk.as_bytes())
+            .collect();
+
+        #[rustfmt::skip]
+        let mut bad_chunk = vec![
+            0, 0, 0, 5,         // length
+            32, 117, 83, 116,   // Chunk Type (bad)
+            65, 64, 65, 66, 67, // Data
+            1, 2, 3, 4, 5       // CRC (bad)
+        ];
+
+        chunk_bytes.append(&mut bad_chunk);
+
+        let png = Png::try_from(chunk_bytes.as_ref());
+
+        assert!(png.is_err());
+    }
+
+    #[test]
+    fn test_list_chunks() {
+        let png = testing_png();
+        let chunks = png.chunks();
+        assert_eq!(chunks.len(), 3);
+    }
+
+    #[test]
+    fn test_chunk_by_type() {
+        let png = testing_png();
+        let chunk = png.chunk_by_type("FrSt").unwrap();
+        assert_eq!(&chunk.chunk_type().to_string(), "FrSt");
+        assert_eq!(&chunk.data_as_string().unwrap(), "I am the first chunk");
+    }
+
+    #[test]
+    fn test_chunks_by_type() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("TeSt", "uno").unwrap()).unwrap();
+        png.append_chunk(chunk_from_strings("TeSt", "dos").unwrap()).unwrap();
+        let found: Vec<(usize, &[u8])> = png.chunks_by_type("TeSt").map(|(i, chunk)| (i, chunk.data())).collect();
+        assert_eq!(found, vec![(3, &b"uno"[..]), (4, &b"dos"[..])]);
+        assert_eq!(png.chunks_by_type("NoPe").count(), 0);
+    }
+
+    #[test]
+    fn test_png_as_collection() {
+        let png = testing_png();
+        assert_eq!(png.len(), 3);
+        assert!(!png.is_empty());
+        assert_eq!(png[1].chunk_type().to_string(), "miDl");
+        assert!(png.get(3).is_none());
+        let borrowed: Vec<String> = (&png).into_iter().map(|chunk| chunk.chunk_type().to_string()).collect();
+        let owned: Vec<Chunk> = png.into_iter().collect();
+        assert_eq!(borrowed, ["FrSt", "miDl", "LASt"]);
+        assert_eq!(owned.len(), 3);
+    }
+
+    #[test]
+    fn test_chunk_spans() {
+        let png = testing_png();
+        let spans = png.chunk_spans();
+        let bytes = png.as_bytes();
+        assert_eq!(spans[0], ChunkSpan { offset: 8, len: 12 + 20 });
+        assert_eq!(spans[2].end(), bytes.len());
+        for (span, chunk) in spans.iter().zip(png.chunks()) {
+            assert_eq!(bytes[span.offset..span.end()], chunk.as_bytes());
+            assert_eq!(&bytes[span.data_offset()..span.end() - 4], chunk.data());
+        }
+    }
+
+    #[test]
+    fn test_append_chunk() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("TeSt", "Message").unwrap()).unwrap();
+        let chunk = png.chunk_by_type("TeSt").unwrap();
+        assert_eq!(&chunk.chunk_type().to_string(), "TeSt");
+        assert_eq!(&chunk.data_as_string().unwrap(), "Message");
+    }
+
+    #[test]
+    fn test_remove_chunk() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("TeSt", "Message").unwrap()).unwrap();
+        png.remove_chunk("TeSt").unwrap();
+        let chunk = png.chunk_by_type("TeSt");
+        assert!(chunk.is_none());
+    }
+
+    #[test]
+    fn test_move_removed_chunk() {
+        let mut from = testing_png();
+        let mut to = Png::from_chunks(Vec::new());
+        to.append_chunk(from.remove_chunk("miDl").unwrap()).unwrap();
+        assert!(from.chunk_by_type("miDl").is_none());
+        assert_eq!(to.chunk_by_type("miDl").unwrap().data_as_string().unwrap(), "I am another chunk");
+    }
+
+    #[test]
+    fn test_remove_chunk_nth() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("TeSt", "uno").unwrap()).unwrap();
+        png.append_chunk(chunk_from_strings("TeSt", "dos").unwrap()).unwrap();
+        let out_of_range = png.remove_chunk_nth("TeSt", 2);
+        assert!(matches!(out_of_range, Err(PngmeError::ChunkIndexOutOfRange { index: 3, count: 2, .. })));
+        assert_eq!(png.remove_chunk_nth("TeSt", 1).unwrap().data(), b"dos");
+        assert_eq!(png.chunk_by_type("TeSt").unwrap().data(), b"uno");
+        assert!(matches!(png.remove_chunk_nth("NoPe", 0), Err(PngmeError::ChunkNotFound { .. })));
+    }
+
+    #[test]
+    fn test_remove_critical_chunk() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        assert!(matches!(png.remove_chunk("IHDR"), Err(PngmeError::CriticalChunkRemoval { .. })));
+        assert!(matches!(png.remove_chunks("IDAT"), Err(PngmeError::CriticalChunkRemoval { .. })));
+        assert_eq!(png.len(), 3);
+        assert_eq!(png.remove_chunk_nth_forced("IHDR", 0).unwrap().chunk_type().to_string(), "IHDR");
+        assert_eq!(png.remove_chunks_forced("IDAT").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_remove_chunks() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("TeSt", "uno").unwrap()).unwrap();
+        png.append_chunk(chunk_from_strings("LASt", "otro").unwrap()).unwrap();
+        png.append_chunk(chunk_from_strings("TeSt", "dos").unwrap()).unwrap();
+        let removed = png.remove_chunks("TeSt").unwrap();
+        let data: Vec<&[u8]> = removed.iter().map(|chunk| chunk.data()).collect();
+        assert_eq!(data, [b"uno".as_slice(), b"dos"]);
+        assert_eq!(png.chunks().len(), 4);
+        assert!(png.remove_chunks("TeSt").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_remove_missing_chunk_suggests_similar() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("ruSt", "Message").unwrap()).unwrap();
+        let Err(PngmeError::ChunkNotFound { chunk_type, similar }) = png.remove_chunk("rust") else {
+            panic!("se esperaba ChunkNotFound");
+        };
+        assert_eq!(chunk_type, "rust");
+        assert_eq!(similar, vec!["ruSt".to_string()]);
+    }
+
+    #[test]
+    fn test_remove_chunk_at() {
+        let mut png = testing_png();
+        let count = png.chunks().len();
+        let second = png.chunks()[1].chunk_type().to_string();
+        assert_eq!(png.remove_chunk_at(1).unwrap().chunk_type().to_string(), second);
+        assert_eq!(png.chunks().len(), count - 1);
+        assert!(png.remove_chunk_at(count).is_none());
+    }
+
+    #[test]
+    fn test_insert_chunk() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let index = png.insert_chunk(chunk_from_strings("iCCP", "perfil").unwrap());
+        let first_idat = png.chunks().iter().position(|chunk| chunk.chunk_type().to_string() == "IDAT").unwrap();
+        assert!(index > 0 && index < first_idat);
+        let index = png.insert_chunk(chunk_from_strings("ruSt", "hola").unwrap());
+        assert_eq!(index, png.chunks().len() - 2);
+        assert_eq!(png.chunks().last().unwrap().chunk_type().to_string(), "IEND");
+    }
+
+    #[test]
+    fn test_insert_chunk_at() {
+        let mut png = testing_png();
+        png.insert_chunk_at(1, chunk_from_strings("TeSt", "uno").unwrap()).unwrap();
+        assert_eq!(png.chunks()[1].chunk_type().to_string(), "TeSt");
+        png.insert_chunk_at(4, chunk_from_strings("TeSt", "dos").unwrap()).unwrap();
+        assert_eq!(png.chunks()[4].data(), b"dos");
+        let out_of_range = png.insert_chunk_at(6, chunk_from_strings("TeSt", "tres").unwrap());
+        assert!(matches!(out_of_range, Err(PngmeError::ChunkIndexOutOfRange { chunk_type: None, index: 6, count: 5 })));
+    }
+
+    #[test]
+    fn test_patch_chunk_at() {
+        let mut png = testing_png();
+        assert_eq!(png.patch_chunk_at(1, 2, b"XY").unwrap(), b"am");
+        assert_eq!(png.chunks()[1].data_as_string().unwrap(), "I XY another chunk");
+        assert!(png.patch_chunk_at(1, 18, b"!").unwrap().is_empty());
+        assert!(matches!(png.patch_chunk_at(3, 0, b"!"), Err(PngmeError::ChunkIndexOutOfRange { index: 3, count: 3, .. })));
+        assert!(matches!(png.patch_chunk_at(1, 20, b"!"), Err(PngmeError::DataOffsetOutOfRange { length: 19, .. })));
+    }
+
+    #[test]
+    fn test_insert_before_and_after() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("miDl", "otro").unwrap()).unwrap();
+        assert_eq!(png.insert_before("miDl", chunk_from_strings("TeSt", "antes").unwrap()).unwrap(), 1);
+        // después del último miDl, que ahora está al final
+        assert_eq!(png.insert_after("miDl", chunk_from_strings("TeSt", "después").unwrap()).unwrap(), 5);
+        assert_eq!(png.chunks().len(), 6);
+        assert!(png.insert_after("NoPe", chunk_from_strings("TeSt", "nada").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_replace_chunk_data_keeps_position() {
+        let mut png = testing_png();
+        let old = png.replace_chunk_data("miDl", b"nuevo".to_vec()).unwrap();
+        assert_eq!(old, b"I am another chunk");
+        assert_eq!(png.chunks()[1].chunk_type().to_string(), "miDl");
+        assert_eq!(png.chunks()[1].data(), b"nuevo");
+        assert_eq!(png.chunks()[1].length(), 5);
+        assert!(png.replace_chunk_data("NoPe", Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_replace_chunk_at() {
+        let mut png = testing_png();
+        let count = png.chunks().len();
+        let chunk = Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"nuevo".to_vec()).unwrap();
+        let old = png.replace_chunk_at(1, chunk).unwrap();
+        assert_ne!(old.data(), b"nuevo");
+        assert_eq!(png.chunks()[1].data(), b"nuevo");
+        assert_eq!(png.chunks().len(), count);
+        let chunk = Chunk::new(ChunkType::from_str("ruSt").unwrap(), Vec::new()).unwrap();
+        assert!(png.replace_chunk_at(count, chunk).is_none());
+    }
+
+    #[test]
+    fn test_png_from_image_file() {
+        let png = Png::try_from(&PNG_FILE[..]);
+        assert!(png.is_ok());
+    }
+
+    #[test]
+    fn test_as_bytes() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let actual = png.as_bytes();
+        let expected: Vec<u8> = PNG_FILE.to_vec();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_png_trait_impls() {
+        let chunk_bytes: Vec<u8> = testing_chunks()
+            .into_iter()
+            .flat_map(|chunk| chunk.as_bytes())
+            .collect();
+
+        let bytes: Vec<u8> = Png::STANDARD_HEADER
+            .iter()
+            .chain(chunk_bytes.iter())
+            .copied()
+            .collect();
+
+        let png: Png = TryFrom::try_from(bytes.as_ref()).unwrap();
+
+        let _png_string = format!("{}", png);
+    }
+
+    #[test]
+    fn test_from_reader() {
+        let mut reader = &PNG_FILE[..];
+        let png = Png::from_reader(&mut reader).unwrap();
+        assert_eq!(png.as_bytes(), PNG_FILE.to_vec());
+    }
+
+    // Cada prefijo del archivo y cada byte cambiado tienen que terminar en Ok o en un PngmeError, nunca en panic
+    #[test]
+    fn test_parse_damaged_input_never_panics() {
+        let mut inputs: Vec<Vec<u8>> = (0..PNG_FILE.len()).map(|len| PNG_FILE[..len].to_vec()).collect();
+        for index in 0..PNG_FILE.len() {
+            for byte in [0x00, 0x7f, 0xff] {
+                let mut bytes = PNG_FILE.to_vec();
+                bytes[index] = byte;
+                inputs.push(bytes);
+            }
+        }
+        for bytes in &inputs {
+            if let Ok(png) = Png::try_from(bytes.as_slice()) {
+                png.chunks().iter().for_each(|chunk| drop(chunk.decode_typed()));
+            }
+            let _ = Png::from_reader(&mut bytes.as_slice());
+            let _ = Png::parse_lenient(bytes);
+            let _ = Png::from_bytes_partial(bytes);
+            let _ = Chunk::try_from(bytes.get(8..).unwrap_or_default());
+            let _ = crate::chunk_ref::ChunkRefs::new(bytes.get(8..).unwrap_or_default()).count();
+        }
+    }
+
+    #[test]
+    fn test_parse_over_total_limit() {
+        let options = ParseOptions { max_total_len: PNG_FILE.len() - 1, ..ParseOptions::default() };
+        assert!(matches!(Png::parse_with(&PNG_FILE[..], &options), Err(PngmeError::OverLimit { .. })));
+        assert!(matches!(Png::from_reader_with(&mut &PNG_FILE[..], &options), Err(PngmeError::OverLimit { .. })));
+        let options = ParseOptions { max_total_len: PNG_FILE.len(), ..ParseOptions::default() };
+        assert_eq!(Png::parse_with(&PNG_FILE[..], &options).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_from_reader_stops_at_iend() {
+        let mut bytes = PNG_FILE.to_vec();
+        bytes.extend_from_slice(b"basura despues de IEND");
+        let mut reader = bytes.as_slice();
+        let png = Png::from_reader(&mut reader).unwrap();
+        assert_eq!(png.chunks().len(), 3);
+        assert_eq!(reader, b"basura despues de IEND");
+    }
+
+    #[test]
+    fn test_write_to() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let mut out = Vec::new();
+        png.write_to(&mut out).unwrap();
+        assert_eq!(out, PNG_FILE.to_vec());
+    }
+
+    #[test]
+    fn test_header_info() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let ihdr = png.header_info().unwrap();
+        assert_eq!((ihdr.width, ihdr.height), (2, 2));
+        assert!(testing_png().header_info().is_err());
+    }
+
+    #[test]
+    fn test_iter_refs() {
+        let png = testing_png();
+        let types: Vec<String> = png.iter_refs().map(|chunk| chunk.chunk_type().to_string()).collect();
+        assert_eq!(types, ["FrSt", "miDl", "LASt"]);
+        assert!(png.iter_refs().all(|chunk| chunk.is_crc_valid()));
+    }
+
+    #[test]
+    fn test_append_chunk_policy() {
+        let chunk = |code: &str| Chunk::new(ChunkType::from_str(code).unwrap(), Vec::new()).unwrap();
+        let mut png = Png::from_chunks(vec![chunk("IHDR"), chunk("IDAT"), chunk("IEND")]);
+        png.append_chunk_with(chunk("IDAT"), AppendPolicy::BeforeIdat).unwrap();
+        png.append_chunk(chunk("ruSt")).unwrap();
+        png.append_chunk_with(chunk("tEXt"), AppendPolicy::BeforeIdat).unwrap();
+        let types: Vec<String> = png.chunks().iter().map(|chunk| chunk.chunk_type().to_string()).collect();
+        assert_eq!(types, ["IHDR", "tEXt", "IDAT", "IDAT", "ruSt", "IEND"]);
+        assert!(matches!(png.append_chunk(chunk("IHDR")), Err(PngmeError::DuplicateCriticalChunk { .. })));
+        assert!(matches!(png.append_chunk(chunk("IEND")), Err(PngmeError::DuplicateCriticalChunk { .. })));
+        assert_eq!(png.len(), 6);
+    }
+
+    #[test]
+    fn test_append_plte_before_idat() {
+        let chunk = |code: &str| Chunk::new(ChunkType::from_str(code).unwrap(), Vec::new()).unwrap();
+        let mut png = Png::from_chunks(vec![chunk("IHDR"), chunk("IDAT"), chunk("IDAT"), chunk("tEXt"), chunk("IEND")]);
+        png.append_chunk(chunk("PLTE")).unwrap();
+        let types: Vec<String> = png.chunks().iter().map(|chunk| chunk.chunk_type().to_string()).collect();
+        assert_eq!(types, ["IHDR", "PLTE", "IDAT", "IDAT", "tEXt", "IEND"]);
+        assert!(matches!(png.append_chunk(chunk("PLTE")), Err(PngmeError::DuplicateCriticalChunk { .. })));
+    }
+
+    #[test]
+    fn test_append_idat_after_last_idat() {
+        let chunk = |code: &str, data: &[u8]| Chunk::new(ChunkType::from_str(code).unwrap(), data.to_vec()).unwrap();
+        let mut png = Png::from_chunks(vec![
+            chunk("IHDR", &[]),
+            chunk("IDAT", &[1]),
+            chunk("IDAT", &[2]),
+            chunk("tEXt", b"a\0b"),
+            chunk("IEND", &[]),
+        ]);
+        png.append_chunk(chunk("IDAT", &[3])).unwrap();
+        let idats: Vec<&[u8]> = png.chunks_by_type("IDAT").map(|(_, chunk)| chunk.data()).collect();
+        assert_eq!(idats, [[1], [2], [3]]);
+        assert_eq!(png.chunks()[3].data(), [3]);
+        assert_eq!(png.chunks()[4].chunk_type().to_string(), "tEXt");
+    }
+
+    #[test]
+    fn test_normalize_order() {
+        let chunk = |code: &str, data: &[u8]| Chunk::new(ChunkType::from_str(code).unwrap(), data.to_vec()).unwrap();
+        let mut png = Png::from_chunks(vec![
+            chunk("IHDR", &[0, 0, 0, 2, 0, 0, 0, 2, 8, 3, 0, 0, 0]),
+            chunk("PLTE", &[0, 0, 0]),
+            chunk("gAMA", &[0, 0, 177, 143]),
+            chunk("tEXt", b"a\0primero"),
+            chunk("IDAT", &[1]),
+            chunk("pHYs", &[0, 0, 0, 1, 0, 0, 0, 1, 0]),
+            chunk("IDAT", &[2]),
+            chunk("tEXt", b"a\0segundo"),
+            chunk("IEND", &[]),
+        ]);
+        assert!(png.normalize_order() > 0);
+        let types: Vec<String> = png.chunks().iter().map(|chunk| chunk.chunk_type().to_string()).collect();
+        assert_eq!(types, ["IHDR", "gAMA", "PLTE", "tEXt", "pHYs", "IDAT", "IDAT", "tEXt", "IEND"]);
+        assert!(crate::lint::lint(&png).is_empty());
+        assert_eq!(png.normalize_order(), 0);
+    }
+
+    // Layouts raros pero válidos que tienen que sobrevivir intactos a leer y volver a escribir
+    fn unusual_layouts() -> Vec<Vec<u8>> {
+        let file = |chunks: Vec<Chunk>| Png::from_chunks(chunks).as_bytes();
+        let chunk = |code: &str, data: &[u8]| Chunk::new(ChunkType::from_str(code).unwrap(), data.to_vec()).unwrap();
+        let ihdr = || chunk("IHDR", &PNG_FILE[16..29]);
+        let idat = &PNG_FILE[41..57];
+        vec![
+            PNG_FILE.to_vec(),
+            // IDAT partido en chunks de un byte, con IDAT vacíos en el medio
+            file(
+                std::iter::once(ihdr())
+                    .chain(idat.iter().flat_map(|byte| [chunk("IDAT", &[*byte]), chunk("IDAT", &[])]))
+                    .chain(std::iter::once(chunk("IEND", &[])))
+                    .collect(),
+            ),
+            // chunks privados, texto vacío y chunks después de IEND
+            file(vec![
+                ihdr(),
+                chunk("prVt", &[0, 255, 0]),
+                chunk("tEXt", &[]),
+                chunk("IDAT", idat),
+                chunk("IEND", &[]),
+                chunk("afTr", b"despues de IEND"),
+            ]),
+        ]
+    }
+
+    #[test]
+    fn test_byte_stable_round_trip() {
+        for bytes in unusual_layouts() {
+            let png = Png::try_from(bytes.as_slice()).unwrap();
+            assert_eq!(png.as_bytes(), bytes);
+            let mut out = Vec::new();
+            png.write_to(&mut out).unwrap();
+            assert_eq!(out, bytes);
+        }
+    }
+
+    #[test]
+    fn test_edit_only_touches_edited_chunk() {
+        let bytes = unusual_layouts().remove(2);
+        let mut png = Png::try_from(bytes.as_slice()).unwrap();
+        let removed = png.remove_chunk("prVt").unwrap();
+        // el chunk borrado ocupaba los bytes 33..48; el resto queda igual
+        let mut expected = bytes.clone();
+        expected.drain(33..33 + 12 + removed.length() as usize);
+        assert_eq!(png.as_bytes(), expected);
+    }
+
+    const PNG_FILE: [u8; 73] = [
+        137, 80, 78, 71, 13, 10, 26, 10, 0, 0, 0, 13, 73, 72, 68, 82,
+        0, 0, 0, 2, 0, 0, 0, 2, 8, 2, 0, 0, 0, 253, 212, 154,
+        115, 0, 0, 0, 16, 73, 68, 65, 84, 120, 156, 99, 248, 207, 192, 0,
+        68, 12, 16, 10, 0, 31, 238, 3, 253, 139, 95, 20, 212, 0, 0, 0,
+        0, 73, 69, 78, 68, 174, 66, 96, 130,
+    ];
+}
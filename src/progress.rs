@@ -0,0 +1,44 @@
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use pngme::locale::text;
+
+// A partir de este tamaño un archivo muestra su propia barra mientras se lee o se escribe
+pub const LARGE_FILE: u64 = 64 * 1024 * 1024;
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+// Todas las barras comparten un mismo lugar en stderr; sin terminal o con --quiet no se dibuja nada
+fn multi() -> &'static MultiProgress {
+    static MULTI: OnceLock<MultiProgress> = OnceLock::new();
+    MULTI.get_or_init(|| {
+        let target = if QUIET.load(Ordering::Relaxed) || !std::io::stdout().is_terminal() {
+            ProgressDrawTarget::hidden()
+        } else {
+            ProgressDrawTarget::stderr()
+        };
+        MultiProgress::with_draw_target(target)
+    })
+}
+
+// Avance total de un lote de archivos
+pub fn files(count: usize) -> ProgressBar {
+    let template = text(
+        "{bar:40} {pos}/{len} archivos  {elapsed_precise}",
+        "{bar:40} {pos}/{len} files  {elapsed_precise}"
+    );
+    let style = ProgressStyle::with_template(template).expect("plantilla válida");
+    multi().add(ProgressBar::new(count as u64).with_style(style))
+}
+
+// Avance de la lectura o escritura de un archivo grande
+pub fn bytes(len: u64, name: String) -> ProgressBar {
+    let style = ProgressStyle::with_template("{bar:40} {bytes}/{total_bytes} {bytes_per_sec}  {msg}")
+        .expect("plantilla válida");
+    multi().add(ProgressBar::new(len).with_style(style).with_message(name))
+}
@@ -0,0 +1,87 @@
+use std::io::{Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+use age::x25519::Recipient;
+use crate::secret::Secret;
+use crate::{tr, PngmeError, Result};
+
+pub type Identities = Vec<Box<dyn age::Identity + Send + Sync>>;
+
+// Clave pública age ("age1...")
+pub fn parse_recipient(recipient: &str) -> Result<Recipient> {
+    Recipient::from_str(recipient.trim())
+        .map_err(|err| PngmeError::InvalidPayload(tr!(
+            "destinatario age inválido {:?}: {}",
+            "invalid age recipient {:?}: {}",
+            recipient,
+            err
+        )))
+}
+
+// Archivo de identidades como el que genera age-keygen ("AGE-SECRET-KEY-1...", '#' para comentarios)
+pub fn read_identities(path: &Path) -> Result<Identities> {
+    let file = age::IdentityFile::from_file(path.to_string_lossy().into_owned())?;
+    file.into_identities()
+        .map_err(|err| PngmeError::InvalidPayload(tr!(
+            "identidades age inválidas: {}",
+            "invalid age identities: {}",
+            err
+        )))
+}
+
+pub fn encrypt(recipients: &[Recipient], plaintext: &[u8]) -> Result<Vec<u8>> {
+    let encryptor = age::Encryptor::with_recipients(recipients.iter().map(|r| r as &dyn age::Recipient))
+        .map_err(|err| PngmeError::InvalidPayload(tr!(
+            "no se pudo cifrar para los destinatarios: {}",
+            "could not encrypt for the recipients: {}",
+            err
+        )))?;
+    let mut out = Vec::new();
+    let mut writer = encryptor.wrap_output(&mut out)?;
+    writer.write_all(plaintext)?;
+    writer.finish()?;
+    Ok(out)
+}
+
+pub fn decrypt(identities: &[Box<dyn age::Identity + Send + Sync>], ciphertext: &[u8]) -> Result<Secret<Vec<u8>>> {
+    let decryptor = age::Decryptor::new_buffered(ciphertext)
+        .map_err(|err| PngmeError::InvalidPayload(tr!(
+            "el contenido cifrado con age no es válido: {}",
+            "the age-encrypted contents are not valid: {}",
+            err
+        )))?;
+    let mut reader = decryptor
+        .decrypt(identities.iter().map(|identity| identity.as_ref() as &dyn age::Identity))
+        .map_err(|_| PngmeError::DecryptionFailed)?;
+    let mut plaintext = Secret::new(Vec::new());
+    reader.read_to_end(plaintext.expose_mut()).map_err(|_| PngmeError::DecryptionFailed)?;
+    Ok(plaintext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use age::x25519::Identity;
+
+    #[test]
+    fn test_encrypt_to_several_recipients() {
+        let alice = Identity::generate();
+        let bob = Identity::generate();
+        let eve = Identity::generate();
+        let ciphertext = encrypt(&[alice.to_public(), bob.to_public()], b"para el equipo").unwrap();
+
+        for identity in [alice, bob] {
+            let identities: Identities = vec![Box::new(identity)];
+            assert_eq!(decrypt(&identities, &ciphertext).unwrap().expose(), b"para el equipo");
+        }
+        let identities: Identities = vec![Box::new(eve)];
+        assert!(matches!(decrypt(&identities, &ciphertext), Err(PngmeError::DecryptionFailed)));
+    }
+
+    #[test]
+    fn test_parse_recipient() {
+        let recipient = Identity::generate().to_public().to_string();
+        assert!(parse_recipient(&recipient).is_ok());
+        assert!(parse_recipient("age1nada").is_err());
+    }
+}
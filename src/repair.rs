@@ -0,0 +1,246 @@
+use std::fmt::Display;
+use crate::chunk::{Chunk, ParseOptions};
+use crate::chunk_ref::ChunkRef;
+use crate::chunk_type::ChunkType;
+use crate::locale::text;
+use crate::png::Png;
+use crate::{tr, PngmeError, Result};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrcFix {
+    pub index: usize,
+    pub chunk_type: ChunkType,
+    pub offset: usize,
+    pub old_crc: u32,
+    pub new_crc: u32,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RepairReport {
+    pub crc_fixes: Vec<CrcFix>,
+    pub iend_added: bool,
+    // bytes descartados después de IEND
+    pub trailing_bytes: usize,
+}
+
+impl RepairReport {
+    pub fn is_clean(&self) -> bool {
+        self.crc_fixes.is_empty() && !self.iend_added && self.trailing_bytes == 0
+    }
+}
+
+impl Display for RepairReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_clean() {
+            return writeln!(f, "{}", text("No hizo falta reparar nada", "Nothing needed repairing"));
+        }
+        for fix in &self.crc_fixes {
+            let message = tr!(
+                "CRC de #{} {} (offset {}) corregido: {:08x} -> {:08x}",
+                "CRC of #{} {} (offset {}) fixed: {:08x} -> {:08x}",
+                fix.index,
+                fix.chunk_type,
+                fix.offset,
+                fix.old_crc,
+                fix.new_crc
+            );
+            writeln!(f, "{}", message)?;
+        }
+        if self.iend_added {
+            writeln!(f, "{}", text("Se agregó el chunk IEND que faltaba", "Added the missing IEND chunk"))?;
+        }
+        if self.trailing_bytes > 0 {
+            let message = tr!(
+                "Se descartaron {} bytes después de IEND",
+                "Discarded {} bytes after IEND",
+                self.trailing_bytes
+            );
+            writeln!(f, "{}", message)?;
+        }
+        Ok(())
+    }
+}
+
+// Reconstruye el archivo recalculando los CRC incorrectos. Los datos tienen que estar
+// completos: un archivo truncado sigue devolviendo error
+pub fn repair_bytes(bytes: &[u8], add_iend: bool) -> Result<(Png, RepairReport)> {
+    if bytes.len() < 8 || bytes[..8] != Png::STANDARD_HEADER {
+        return Err(PngmeError::InvalidSignature);
+    }
+
+    let mut report = RepairReport::default();
+    let mut chunks = Vec::new();
+    let mut offset = 8;
+    while offset < bytes.len() {
+        let chunk_ref = ChunkRef::parse_at(&bytes[offset..], offset)?;
+        let chunk: Chunk = chunk_ref.to_chunk();
+        if chunk.crc() != chunk_ref.crc() {
+            report.crc_fixes.push(CrcFix {
+                index: chunks.len(),
+                chunk_type: chunk_ref.chunk_type(),
+                offset,
+                old_crc: chunk_ref.crc(),
+                new_crc: chunk.crc(),
+            });
+        }
+        offset += chunk_ref.total_len();
+        let is_end = *chunk.chunk_type() == ChunkType::IEND;
+        chunks.push(chunk);
+        if is_end {
+            report.trailing_bytes = bytes.len() - offset;
+            break;
+        }
+    }
+
+    let has_iend = chunks.last().is_some_and(|chunk| *chunk.chunk_type() == ChunkType::IEND);
+    if add_iend && !has_iend {
+        chunks.push(Chunk::from_parsed(ChunkType::IEND, Vec::new()));
+        report.iend_added = true;
+    }
+
+    Ok((Png::from_chunks(chunks), report))
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RecoveryReport {
+    pub recovered_chunks: usize,
+    // offset donde empieza la parte descartada
+    pub lost_offset: Option<usize>,
+    pub lost_bytes: usize,
+    pub reason: Option<String>,
+    pub iend_added: bool,
+}
+
+impl Display for RecoveryReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", tr!("Chunks recuperados: {}", "Recovered chunks: {}", self.recovered_chunks))?;
+        if let Some(offset) = self.lost_offset {
+            let message = tr!(
+                "Se descartaron {} bytes a partir del offset {}",
+                "Discarded {} bytes from offset {}",
+                self.lost_bytes,
+                offset
+            );
+            writeln!(f, "{}", message)?;
+        }
+        if let Some(reason) = &self.reason {
+            writeln!(f, "{}", tr!("Motivo: {}", "Reason: {}", reason))?;
+        }
+        if self.iend_added {
+            writeln!(f, "{}", text("Se agregó IEND al final", "Added IEND at the end"))?;
+        }
+        Ok(())
+    }
+}
+
+impl Png {
+    // Rescata todos los chunks completos y con CRC correcto hasta el primer daño,
+    // descarta el resto y cierra el archivo con IEND
+    pub fn from_bytes_partial(bytes: &[u8]) -> Result<(Png, RecoveryReport)> {
+        if bytes.len() < 8 || bytes[..8] != Png::STANDARD_HEADER {
+            return Err(PngmeError::InvalidSignature);
+        }
+
+        let mut report = RecoveryReport::default();
+        let mut chunks = Vec::new();
+        let mut offset = 8;
+        while offset < bytes.len() {
+            match Chunk::from_bytes_at(&bytes[offset..], offset, &ParseOptions::default()) {
+                Ok(chunk) => {
+                    offset += 12 + chunk.length() as usize;
+                    let is_end = *chunk.chunk_type() == ChunkType::IEND;
+                    chunks.push(chunk);
+                    if is_end {
+                        break;
+                    }
+                }
+                Err(err) => {
+                    report.reason = Some(err.to_string());
+                    break;
+                }
+            }
+        }
+        if offset < bytes.len() {
+            report.lost_offset = Some(offset);
+            report.lost_bytes = bytes.len() - offset;
+        }
+
+        report.recovered_chunks = chunks.len();
+        if chunks.last().is_none_or(|chunk| *chunk.chunk_type() != ChunkType::IEND) {
+            chunks.push(Chunk::from_parsed(ChunkType::IEND, Vec::new()));
+            report.iend_added = true;
+        }
+        Ok((Png::from_chunks(chunks), report))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn testing_bytes() -> Vec<u8> {
+        let ihdr = Chunk::new(ChunkType::IHDR, vec![0, 0, 0, 2, 0, 0, 0, 2, 8, 2, 0, 0, 0]).unwrap();
+        let idat = Chunk::new(ChunkType::IDAT, vec![1, 2, 3]).unwrap();
+        let iend = Chunk::new(ChunkType::IEND, Vec::new()).unwrap();
+        Png::from_chunks(vec![ihdr, idat, iend]).as_bytes()
+    }
+
+    #[test]
+    fn test_repair_bad_crc() {
+        let original = testing_bytes();
+        let mut bytes = original.clone();
+        bytes[33 + 12 + 3 - 1] ^= 0xff;
+        let (png, report) = repair_bytes(&bytes, false).unwrap();
+        assert_eq!(report.crc_fixes.len(), 1);
+        assert_eq!(report.crc_fixes[0].chunk_type, ChunkType::IDAT);
+        assert_eq!(png.as_bytes(), original);
+    }
+
+    #[test]
+    fn test_repair_missing_iend() {
+        let original = testing_bytes();
+        let (png, report) = repair_bytes(&original[..original.len() - 12], true).unwrap();
+        assert!(report.iend_added);
+        assert_eq!(png.as_bytes(), original);
+
+        let (_, report) = repair_bytes(&original, true).unwrap();
+        assert!(report.is_clean());
+    }
+
+    #[test]
+    fn test_repair_truncated_fails() {
+        let original = testing_bytes();
+        assert!(repair_bytes(&original[..original.len() - 14], true).is_err());
+    }
+
+    #[test]
+    fn test_recover_truncated() {
+        let original = testing_bytes();
+        // se corta en medio de IDAT
+        let (png, report) = Png::from_bytes_partial(&original[..40]).unwrap();
+        assert_eq!(report.recovered_chunks, 1);
+        assert_eq!(report.lost_offset, Some(33));
+        assert_eq!(report.lost_bytes, 7);
+        assert!(report.iend_added);
+        let types: Vec<String> = png.chunks().iter().map(|c| c.chunk_type().to_string()).collect();
+        assert_eq!(types, ["IHDR", "IEND"]);
+    }
+
+    #[test]
+    fn test_recover_stops_at_bad_crc() {
+        let mut bytes = testing_bytes();
+        bytes[33 + 12 + 3 - 1] ^= 0xff;
+        let (png, report) = Png::from_bytes_partial(&bytes).unwrap();
+        assert_eq!(png.chunks().len(), 2);
+        assert!(report.reason.is_some());
+    }
+
+    #[test]
+    fn test_recover_intact_file() {
+        let original = testing_bytes();
+        let (png, report) = Png::from_bytes_partial(&original).unwrap();
+        assert_eq!(png.as_bytes(), original);
+        assert_eq!(report.lost_offset, None);
+        assert!(!report.iend_added);
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use crate::chunk_ref::ChunkRef;
+use crate::chunk_type::ChunkType;
+use crate::png::Png;
+use crate::tr;
+
+// Largo a partir del cual un chunk de texto se considera anormalmente grande
+pub const DEFAULT_MAX_TEXT: usize = 4096;
+
+// Lo que scan busca. Los códigos son estables porque los usan los pipelines que leen el JSON
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum FindingKind {
+    UnknownPrivate,
+    DuplicateAncillary,
+    DataAfterIend,
+    LargeText,
+    Malformed,
+}
+
+impl FindingKind {
+    pub const ALL: [FindingKind; 5] = [
+        FindingKind::UnknownPrivate,
+        FindingKind::DuplicateAncillary,
+        FindingKind::DataAfterIend,
+        FindingKind::LargeText,
+        FindingKind::Malformed,
+    ];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            FindingKind::UnknownPrivate => "unknown-private",
+            FindingKind::DuplicateAncillary => "duplicate-ancillary",
+            FindingKind::DataAfterIend => "data-after-iend",
+            FindingKind::LargeText => "large-text",
+            FindingKind::Malformed => "malformed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub kind: FindingKind,
+    pub offset: usize,
+    pub chunk_type: Option<ChunkType>,
+    pub message: String,
+}
+
+impl Display for Finding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} [offset {}]: {}", self.kind.code(), self.offset, self.message)
+    }
+}
+
+// Recorre los bytes sin armar el PNG, para ver también lo que hay después de IEND y seguir
+// hasta donde se pueda en archivos dañados
+pub fn scan_bytes(bytes: &[u8], max_text: usize) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    if bytes.len() < 8 || bytes[..8] != Png::STANDARD_HEADER {
+        findings.push(Finding {
+            kind: FindingKind::Malformed,
+            offset: 0,
+            chunk_type: None,
+            message: tr!("la firma no corresponde a un PNG", "the signature is not a PNG signature"),
+        });
+        return findings;
+    }
+
+    let mut counts: HashMap<ChunkType, usize> = HashMap::new();
+    let mut offset = 8;
+    while offset < bytes.len() {
+        let chunk = match ChunkRef::parse_at(&bytes[offset..], offset) {
+            Ok(chunk) => chunk,
+            Err(err) => {
+                findings.push(Finding { kind: FindingKind::Malformed, offset, chunk_type: None, message: err.to_string() });
+                break;
+            }
+        };
+        let ct = chunk.chunk_type();
+        let finding = |kind, message| Finding { kind, offset, chunk_type: Some(ct), message };
+
+        if !ct.is_public() && !ct.is_registered() {
+            findings.push(finding(FindingKind::UnknownPrivate, tr!("tipo privado {} ({} bytes)", "private type {} ({} bytes)", ct, chunk.length())));
+        }
+        let count = counts.entry(ct).or_default();
+        *count += 1;
+        let single = ct.constraints().is_some_and(|constraints| !constraints.multiple);
+        if *count == 2 && !ct.is_critical() && single {
+            findings.push(finding(FindingKind::DuplicateAncillary, tr!("{} aparece más de una vez", "{} appears more than once", ct)));
+        }
+        let is_text = [ChunkType::tEXt, ChunkType::zTXt, ChunkType::iTXt].contains(&ct);
+        if is_text && chunk.data().len() > max_text {
+            findings.push(finding(FindingKind::LargeText, tr!("{} de {} bytes", "{} of {} bytes", ct, chunk.length())));
+        }
+
+        offset += chunk.total_len();
+        if ct == ChunkType::IEND {
+            break;
+        }
+    }
+    if offset < bytes.len() && !findings.iter().any(|finding| finding.kind == FindingKind::Malformed) {
+        findings.push(Finding {
+            kind: FindingKind::DataAfterIend,
+            offset,
+            chunk_type: None,
+            message: tr!("hay {} bytes después de IEND", "there are {} bytes after IEND", bytes.len() - offset),
+        });
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::Chunk;
+    use std::str::FromStr;
+
+    fn testing_bytes(extra: Vec<Chunk>) -> Vec<u8> {
+        let ihdr = Chunk::new(ChunkType::IHDR, vec![0, 0, 0, 2, 0, 0, 0, 2, 8, 2, 0, 0, 0]).unwrap();
+        let idat = Chunk::new(ChunkType::IDAT, vec![1, 2, 3]).unwrap();
+        let iend = Chunk::new(ChunkType::IEND, Vec::new()).unwrap();
+        Png::from_chunks([vec![ihdr, idat], extra, vec![iend]].into_iter().flatten().collect()).as_bytes()
+    }
+
+    #[test]
+    fn test_clean_file() {
+        let text = Chunk::new(ChunkType::tEXt, b"Comment\0hola".to_vec()).unwrap();
+        assert!(scan_bytes(&testing_bytes(vec![text]), DEFAULT_MAX_TEXT).is_empty());
+    }
+
+    #[test]
+    fn test_finds_everything() {
+        let private = Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"hola".to_vec()).unwrap();
+        let gama = || Chunk::new(ChunkType::gAMA, vec![0, 0, 0xb1, 0x8f]).unwrap();
+        let text = Chunk::new(ChunkType::tEXt, [&b"Comment\0"[..], &[b'x'; 100]].concat()).unwrap();
+        let mut bytes = testing_bytes(vec![private, gama(), gama(), text]);
+        bytes.extend_from_slice(b"escondido");
+
+        let kinds: Vec<FindingKind> = scan_bytes(&bytes, 50).iter().map(|finding| finding.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                FindingKind::UnknownPrivate,
+                FindingKind::DuplicateAncillary,
+                FindingKind::LargeText,
+                FindingKind::DataAfterIend
+            ]
+        );
+    }
+}
@@ -0,0 +1,77 @@
+use std::fmt::Debug;
+use zeroize::Zeroize;
+
+// Bytes sensibles: contraseñas, claves derivadas y mensajes descifrados. Se sobrescriben con ceros
+// al liberarse y nunca aparecen en la salida de depuración
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret<T: Zeroize>(T);
+
+impl<T: Zeroize> Secret<T> {
+    pub fn new(value: T) -> Secret<T> {
+        Secret(value)
+    }
+
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+
+    pub fn expose_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Zeroize + Default> Secret<T> {
+    // Saca el valor sin copiarlo; desde ahí quien lo recibe se hace cargo de borrarlo
+    pub fn into_inner(mut self) -> T {
+        std::mem::take(&mut self.0)
+    }
+}
+
+impl<T: Zeroize> Drop for Secret<T> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T: Zeroize> Debug for Secret<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Secret([oculto])")
+    }
+}
+
+impl<T: Zeroize> From<T> for Secret<T> {
+    fn from(value: T) -> Secret<T> {
+        Secret(value)
+    }
+}
+
+impl From<String> for Secret<Vec<u8>> {
+    fn from(value: String) -> Secret<Vec<u8>> {
+        Secret(value.into_bytes())
+    }
+}
+
+impl From<&str> for Secret<Vec<u8>> {
+    fn from(value: &str) -> Secret<Vec<u8>> {
+        Secret(value.as_bytes().to_vec())
+    }
+}
+
+impl From<&[u8]> for Secret<Vec<u8>> {
+    fn from(value: &[u8]) -> Secret<Vec<u8>> {
+        Secret(value.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_is_redacted() {
+        let secret = Secret::from("contraseña");
+        assert_eq!(secret.expose(), "contraseña".as_bytes());
+        assert!(!format!("{:?}", secret).contains("contraseña"));
+        assert_eq!(secret.into_inner(), "contraseña".as_bytes());
+    }
+}
@@ -0,0 +1,121 @@
+use std::str::FromStr;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::chunk::Chunk;
+use crate::chunk_type::ChunkType;
+use crate::png::Png;
+use crate::tr;
+
+// Tipo de chunk como string de 4 caracteres
+impl Serialize for ChunkType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for ChunkType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        if s.len() != 4 {
+            return Err(D::Error::custom(tr!(
+                "el tipo de chunk debe tener 4 caracteres: {:?}",
+                "the chunk type must have 4 characters: {:?}",
+                s
+            )));
+        }
+        ChunkType::from_str(&s).map_err(D::Error::custom)
+    }
+}
+
+// Los datos van en base64. El CRC se exporta como referencia pero al leer se recalcula,
+// así un chunk editado a mano sigue siendo válido
+#[derive(Serialize, Deserialize)]
+struct ChunkRepr {
+    #[serde(rename = "type")]
+    chunk_type: ChunkType,
+    data: String,
+    #[serde(default, skip_deserializing)]
+    crc: u32,
+}
+
+impl Serialize for Chunk {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ChunkRepr {
+            chunk_type: *self.chunk_type(),
+            data: BASE64.encode(self.data()),
+            crc: self.crc(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Chunk {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = ChunkRepr::deserialize(deserializer)?;
+        let data = BASE64.decode(repr.data).map_err(D::Error::custom)?;
+        Chunk::new(repr.chunk_type, data).map_err(D::Error::custom)
+    }
+}
+
+#[derive(Deserialize)]
+struct PngRepr {
+    chunks: Vec<Chunk>,
+}
+
+impl Serialize for Png {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("Png", 1)?;
+        state.serialize_field("chunks", self.chunks())?;
+        state.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Png {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = PngRepr::deserialize(deserializer)?;
+        Ok(Png::from_chunks(repr.chunks))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_json_round_trip() {
+        let chunk = Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"hola".to_vec()).unwrap();
+        let json = serde_json::to_string(&chunk).unwrap();
+        let expected = format!(r#"{{"type":"ruSt","data":"aG9sYQ==","crc":{}}}"#, chunk.crc());
+        assert_eq!(json, expected);
+
+        let back: Chunk = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.as_bytes(), chunk.as_bytes());
+    }
+
+    #[test]
+    fn test_chunk_crc_is_recomputed() {
+        let json = r#"{"type":"ruSt","data":"aG9sYQ==","crc":1}"#;
+        let chunk: Chunk = serde_json::from_str(json).unwrap();
+        assert_ne!(chunk.crc(), 1);
+    }
+
+    #[test]
+    fn test_invalid_chunk_type() {
+        assert!(serde_json::from_str::<ChunkType>(r#""ru1t""#).is_err());
+        assert!(serde_json::from_str::<ChunkType>(r#""ru""#).is_err());
+    }
+
+    #[test]
+    fn test_png_round_trip() {
+        let png = Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("FrSt").unwrap(), b"primero".to_vec()).unwrap(),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), Vec::new()).unwrap(),
+        ]);
+        let json = serde_json::to_string(&png).unwrap();
+        let back: Png = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.as_bytes(), png.as_bytes());
+    }
+}
@@ -0,0 +1,250 @@
+use std::collections::HashSet;
+use crate::chunk::Chunk;
+use crate::chunk_type::ChunkType;
+use crate::crypto::{fill_random, random_bytes};
+use crate::fragment::{split, MAX_FRAGMENT_DATA};
+use crate::png::Png;
+use crate::secret::Secret;
+use crate::{tr, PngmeError, Result};
+use crate::locale::text;
+
+// Encabezado de cada parte de un mensaje repartido con el esquema de Shamir, en chunks spRd:
+// firma "PNGt" + versión + id (u32) + x de la parte + partes necesarias + total de partes.
+// Cada parte mide lo mismo que el mensaje y, con menos de `threshold`, no dice nada de él
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShareHeader {
+    pub id: u32,
+    pub x: u8,
+    pub threshold: u8,
+    pub total: u8,
+}
+
+impl ShareHeader {
+    pub const MAGIC: [u8; 4] = *b"PNGt";
+    pub const VERSION: u8 = 1;
+    pub const LEN: usize = 12;
+
+    pub fn parse(data: &[u8]) -> Option<(ShareHeader, &[u8])> {
+        if data.len() < ShareHeader::LEN || data[..4] != ShareHeader::MAGIC || data[4] != ShareHeader::VERSION {
+            return None;
+        }
+        let header = ShareHeader {
+            id: u32::from_be_bytes([data[5], data[6], data[7], data[8]]),
+            x: data[9],
+            threshold: data[10],
+            total: data[11],
+        };
+        if header.x == 0 || header.threshold < 2 || header.threshold > header.total {
+            return None;
+        }
+        Some((header, &data[ShareHeader::LEN..]))
+    }
+
+    pub fn to_bytes(&self) -> [u8; ShareHeader::LEN] {
+        let mut bytes = [0; ShareHeader::LEN];
+        bytes[..4].copy_from_slice(&ShareHeader::MAGIC);
+        bytes[4] = ShareHeader::VERSION;
+        bytes[5..9].copy_from_slice(&self.id.to_be_bytes());
+        bytes[9] = self.x;
+        bytes[10] = self.threshold;
+        bytes[11] = self.total;
+        bytes
+    }
+}
+
+// Producto en GF(2^8) con el polinomio de AES (x^8 + x^4 + x^3 + x + 1), sin ramas que dependan de los datos
+fn mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    for _ in 0..8 {
+        product ^= a & (b & 1).wrapping_neg();
+        let carry = (a >> 7).wrapping_neg();
+        a = (a << 1) ^ (carry & 0x1b);
+        b >>= 1;
+    }
+    product
+}
+
+// Inverso multiplicativo: a^254, porque a^255 = 1 para todo a distinto de 0
+fn inverse(a: u8) -> u8 {
+    let mut result = 1;
+    for _ in 0..254 {
+        result = mul(result, a);
+    }
+    result
+}
+
+// Parte el secreto en `total` partes con x = 1..=total; cualquier `threshold` de ellas lo reconstruye.
+// Cada byte es el término independiente de un polinomio de grado threshold - 1 con el resto al azar
+pub fn split_secret(secret: &[u8], threshold: u8, total: u8) -> Result<Vec<(u8, Vec<u8>)>> {
+    if threshold < 2 || threshold > total {
+        return Err(PngmeError::InvalidPayload(tr!(
+            "hacen falta entre 2 y {} partes para reconstruir el mensaje, no {}",
+            "between 2 and {} shares are needed to rebuild the message, not {}",
+            total, threshold
+        )));
+    }
+    let degree = threshold as usize - 1;
+    let mut coefficients = Secret::new(vec![0; secret.len() * degree]);
+    fill_random(coefficients.expose_mut())?;
+
+    let shares = (1..=total)
+        .map(|x| {
+            let y = secret
+                .iter()
+                .zip(coefficients.expose().chunks(degree))
+                .map(|(byte, coefficients)| {
+                    // Horner desde el coeficiente de mayor grado
+                    coefficients.iter().rev().fold(0, |acc, coefficient| mul(acc ^ coefficient, x)) ^ byte
+                })
+                .collect();
+            (x, y)
+        })
+        .collect();
+    Ok(shares)
+}
+
+// Interpola en x = 0 con todas las partes recibidas; tienen que tener x distintos y el mismo largo
+pub fn combine(shares: &[(u8, Vec<u8>)]) -> Result<Secret<Vec<u8>>> {
+    let len = shares.first().map_or(0, |(_, y)| y.len());
+    if shares.iter().any(|(_, y)| y.len() != len) {
+        let reason = text("las partes no tienen el mismo largo", "the shares do not have the same length");
+        return Err(PngmeError::InvalidPayload(reason.to_string()));
+    }
+    let mut secret = Secret::new(vec![0; len]);
+    for (i, (xi, yi)) in shares.iter().enumerate() {
+        // coeficiente de Lagrange en 0: producto de xj / (xj - xi); en GF(2^8) restar es xor
+        let weight = shares
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .fold(1, |acc, (_, (xj, _))| mul(acc, mul(*xj, inverse(xj ^ xi))));
+        for (byte, y) in secret.expose_mut().iter_mut().zip(yi) {
+            *byte ^= mul(weight, *y);
+        }
+    }
+    Ok(secret)
+}
+
+// Una parte de Shamir por archivo, cada una en los chunks spRd que hagan falta
+pub fn share_chunks(data: &[u8], threshold: u8, total: usize) -> Result<Vec<Vec<Chunk>>> {
+    let total = u8::try_from(total)
+        .map_err(|_| PngmeError::InvalidPayload(tr!(
+            "no se puede repartir un mensaje en {} archivos",
+            "a message cannot be split across {} files",
+            total
+        )))?;
+    // el id es al azar: uno derivado del mensaje diría algo de él en cada parte
+    let id = u32::from_be_bytes(random_bytes()?);
+    split_secret(data, threshold, total)?
+        .into_iter()
+        .map(|(x, y)| {
+            let header = ShareHeader { id, x, threshold, total };
+            split(ChunkType::spRd, &[&header.to_bytes()[..], &y].concat(), MAX_FRAGMENT_DATA)
+        })
+        .collect()
+}
+
+impl Png {
+    // Partes de Shamir que tiene este archivo, ya sin fragmentar
+    pub fn shamir_shares(&self) -> Result<Vec<(ShareHeader, Vec<u8>)>> {
+        let parts = self.spread_parts()?;
+        let shares = parts
+            .iter()
+            .filter_map(|data| ShareHeader::parse(data))
+            .map(|(header, y)| (header, y.to_vec()))
+            .collect();
+        Ok(shares)
+    }
+}
+
+// Reconstruye el mensaje con las partes de distintos archivos; alcanza con `threshold` de ellas
+pub fn gather_shares(shares: Vec<(ShareHeader, Vec<u8>)>) -> Result<Secret<Vec<u8>>> {
+    let ids: HashSet<u32> = shares.iter().map(|(header, _)| header.id).collect();
+    match ids.len() {
+        0 => {
+            let reason = text(
+                "los archivos no tienen partes de ningún mensaje",
+                "the files do not have parts of any message"
+            );
+            return Err(PngmeError::InvalidPayload(reason.to_string()));
+        }
+        1 => {}
+        n => return Err(PngmeError::InvalidPayload(tr!(
+            "los archivos tienen partes de {} mensajes distintos",
+            "the files have parts of {} different messages",
+            n
+        ))),
+    }
+
+    let first = shares[0].0;
+    let mut seen = HashSet::new();
+    let mut points = Vec::new();
+    for (header, y) in shares {
+        if (header.threshold, header.total) != (first.threshold, first.total) {
+            let reason = text(
+                "las partes no coinciden en la cantidad necesaria",
+                "the shares do not agree on the required count"
+            );
+            return Err(PngmeError::InvalidPayload(reason.to_string()));
+        }
+        if !seen.insert(header.x) {
+            return Err(PngmeError::InvalidPayload(tr!("la parte {} está repetida", "part {} is repeated", header.x)));
+        }
+        points.push((header.x, y));
+    }
+    if points.len() < first.threshold as usize {
+        return Err(PngmeError::InvalidPayload(tr!(
+            "hacen falta {} partes de {} para reconstruir el mensaje y hay {}",
+            "{} of {} shares are needed to rebuild the message and there are {}",
+            first.threshold,
+            first.total,
+            points.len()
+        )));
+    }
+    points.truncate(first.threshold as usize);
+    combine(&points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cover(chunks: Vec<Chunk>) -> Png {
+        let ihdr = Chunk::new(ChunkType::IHDR, vec![0; 13]).unwrap();
+        let iend = Chunk::new(ChunkType::IEND, Vec::new()).unwrap();
+        Png::from_chunks([vec![ihdr], chunks, vec![iend]].into_iter().flatten().collect())
+    }
+
+    #[test]
+    fn test_field_inverse() {
+        for a in 1..=255 {
+            assert_eq!(mul(a, inverse(a)), 1);
+        }
+    }
+
+    #[test]
+    fn test_any_threshold_shares_recover() {
+        let secret = b"nos vemos a las cinco";
+        let shares = split_secret(secret, 3, 5).unwrap();
+        assert!(shares.iter().all(|(_, y)| y.len() == secret.len() && y != secret));
+        for skip in 0..5 {
+            let subset: Vec<_> = shares.iter().filter(|(x, _)| *x != skip + 1).take(3).cloned().collect();
+            assert_eq!(combine(&subset).unwrap().expose(), secret);
+        }
+        assert_ne!(combine(&shares[..2]).unwrap().expose(), secret);
+    }
+
+    #[test]
+    fn test_gather_shares_from_covers() {
+        let data: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let covers: Vec<Png> = share_chunks(&data, 3, 5).unwrap().into_iter().map(cover).collect();
+        let mut shares: Vec<_> = covers.iter().flat_map(|png| png.shamir_shares().unwrap()).collect();
+        assert!(covers.iter().all(|png| png.shards().unwrap().is_empty()));
+
+        shares.retain(|(header, _)| header.x % 2 == 1);
+        assert_eq!(gather_shares(shares.clone()).unwrap().expose(), &data);
+        shares.pop();
+        let error = gather_shares(shares).unwrap_err();
+        assert!(error.to_string().contains("hacen falta 3 partes de 5"));
+    }
+}
@@ -0,0 +1,227 @@
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+use pngme::chunk_type::ChunkType;
+use pngme::fragment::{split, FragmentHeader, MAX_FRAGMENT_DATA};
+use pngme::payload::Payload;
+use pngme::png::Png;
+use pngme::locale::text;
+use pngme::{tr, PngmeError, Result};
+use crate::args::ShellArgs;
+use crate::color;
+use crate::commands::{read_png, text_message, write_png};
+
+const HELP: &str = "\
+list                     lista los chunks
+show N [BYTES]           muestra el chunk N con sus datos
+decode TIPO              muestra el mensaje guardado en el primer chunk del tipo
+encode TIPO MENSAJE      agrega un mensaje en un chunk nuevo
+remove TIPO              elimina el primer chunk del tipo (y sus fragmentos)
+save [ARCHIVO]           guarda los cambios, en el archivo abierto o en otro
+quit                     sale; con cambios sin guardar hay que repetirlo";
+
+const HELP_EN: &str = "\
+list                     lists the chunks
+show N [BYTES]           shows chunk N with its data
+decode TYPE              shows the message stored in the first chunk of the type
+encode TYPE MESSAGE      adds a message in a new chunk
+remove TYPE              removes the first chunk of the type (and its fragments)
+save [FILE]              saves the changes, to the open file or another one
+quit                     exits; with unsaved changes it has to be repeated";
+
+// El PNG se lee una sola vez y los comandos trabajan sobre la copia en memoria hasta `save`
+struct Session {
+    path: PathBuf,
+    png: Png,
+    modified: bool,
+    // el primer quit con cambios sin guardar sólo avisa
+    warned: bool,
+}
+
+enum Flow {
+    Continue,
+    Quit,
+}
+
+// Sin terminal (con los comandos en un archivo o un pipe) no hay prompt y el primer error corta la
+// sesión, para que un script no siga con el archivo a medio editar
+pub fn shell(args: ShellArgs) -> Result<()> {
+    let png = read_png(&args.file_path)?;
+    let mut session = Session { path: args.file_path, png, modified: false, warned: false };
+    let interactive = std::io::stdin().is_terminal();
+    if interactive {
+        let chunks = session.png.chunks().len();
+        println!("{}", tr!(
+            "{}: {} chunks; help muestra los comandos",
+            "{}: {} chunks; help shows the commands",
+            session.path.display(),
+            chunks
+        ));
+    }
+    let mut lines = std::io::stdin().lock().lines();
+    loop {
+        if interactive {
+            print!("pngme> ");
+            std::io::stdout().flush()?;
+        }
+        let Some(line) = lines.next() else {
+            break;
+        };
+        let result = split_words(&line?).and_then(|words| match words.split_first() {
+            Some((command, rest)) => session.run(command, rest),
+            None => Ok(Flow::Continue),
+        });
+        match result {
+            Ok(Flow::Continue) => {}
+            Ok(Flow::Quit) => return Ok(()),
+            Err(err) if interactive => eprintln!("Error: {}", err),
+            Err(err) => return Err(err),
+        }
+    }
+    if session.modified {
+        eprintln!("{}", text("Los cambios sin guardar se descartaron", "The unsaved changes were discarded"));
+    }
+    Ok(())
+}
+
+impl Session {
+    fn run(&mut self, command: &str, args: &[String]) -> Result<Flow> {
+        if command != "quit" && command != "exit" {
+            self.warned = false;
+        }
+        match (command, args) {
+            ("help" | "?", []) => println!("{}", text(HELP, HELP_EN)),
+            ("list", []) => self.list(),
+            ("show", [index]) => self.show(index, None)?,
+            ("show", [index, bytes]) => self.show(index, Some(bytes))?,
+            ("decode", [chunk_type]) => self.decode(chunk_type)?,
+            ("encode", [chunk_type, message]) => self.encode(chunk_type, message)?,
+            ("remove", [chunk_type]) => self.remove(chunk_type)?,
+            ("save", []) => self.save(None)?,
+            ("save", [path]) => self.save(Some(PathBuf::from(path)))?,
+            ("quit" | "exit", []) if self.modified && !self.warned => {
+                self.warned = true;
+                eprintln!("{}", text(
+                    "Hay cambios sin guardar: save para guardarlos, o quit otra vez para salir igual",
+                    "There are unsaved changes: save to keep them, or quit again to exit anyway"
+                ));
+            }
+            ("quit" | "exit", []) => return Ok(Flow::Quit),
+            _ => {
+                return Err(PngmeError::Usage(tr!(
+                    "comando inválido: {}; help muestra los comandos",
+                    "invalid command: {}; help shows the commands",
+                    command
+                )));
+            }
+        }
+        Ok(Flow::Continue)
+    }
+
+    fn list(&self) {
+        println!("{:>5}  {:<4}  {:>10}  {:<8}", "#", text("tipo", "type"), "length", "crc");
+        for (index, chunk) in self.png.chunks().iter().enumerate() {
+            println!("{:>5}  {}  {:>10}  {:08x}", index, color::chunk_type(chunk.chunk_type()), chunk.length(), chunk.crc());
+        }
+    }
+
+    fn show(&self, index: &str, bytes: Option<&String>) -> Result<()> {
+        let chunks = self.png.chunks();
+        let chunk = index
+            .parse::<usize>()
+            .ok()
+            .and_then(|index| chunks.get(index))
+            .ok_or_else(|| PngmeError::Usage(tr!(
+                "no hay ningún chunk #{} (hay {})",
+                "there is no chunk #{} (there are {})",
+                index,
+                chunks.len()
+            )))?;
+        let bytes = match bytes {
+            Some(bytes) => bytes.parse().map_err(|_| PngmeError::Usage(tr!(
+                "cantidad inválida: {}",
+                "invalid count: {}",
+                bytes
+            )))?,
+            None => pngme::chunk::Chunk::DEFAULT_PREVIEW_BYTES,
+        };
+        print!("{:.*}", bytes, chunk);
+        Ok(())
+    }
+
+    fn decode(&self, chunk_type: &str) -> Result<()> {
+        let chunk = self.png.chunk_by_type(chunk_type).ok_or_else(|| self.png.chunk_not_found(chunk_type))?;
+        if let Some(text) = text_message(chunk)? {
+            println!("{}", text);
+            return Ok(());
+        }
+        // los mensajes cifrados se leen con pngme decode, que sabe pedir la contraseña
+        let payload = Payload::open(&self.png.reassemble(chunk)?, None)?;
+        match std::str::from_utf8(payload.data()) {
+            Ok(text) => println!("{}", text),
+            Err(_) => println!("{}", tr!("({} bytes binarios)", "({} binary bytes)", payload.data().len())),
+        }
+        Ok(())
+    }
+
+    fn encode(&mut self, chunk_type: &str, message: &str) -> Result<()> {
+        let chunk_type = ChunkType::from_str(chunk_type)?;
+        for chunk in split(chunk_type, &Payload::new(message.as_bytes()).to_bytes()?, MAX_FRAGMENT_DATA)? {
+            self.png.append_chunk(chunk)?;
+        }
+        self.modified = true;
+        Ok(())
+    }
+
+    fn remove(&mut self, chunk_type: &str) -> Result<()> {
+        let chunk = self.png.remove_chunk(chunk_type)?;
+        if let Some((header, _)) = FragmentHeader::parse(chunk.data()) {
+            self.png.remove_fragments(chunk.chunk_type(), header.id);
+        }
+        println!("{}", tr!("Se eliminó {} ({} bytes)", "Removed {} ({} bytes)", chunk.chunk_type(), chunk.length()));
+        self.modified = true;
+        Ok(())
+    }
+
+    fn save(&mut self, path: Option<PathBuf>) -> Result<()> {
+        let path = path.unwrap_or_else(|| self.path.clone());
+        write_png(&path, &self.png)?;
+        println!("{}", tr!("Guardado en {}", "Saved to {}", path.display()));
+        self.modified = false;
+        Ok(())
+    }
+}
+
+// Separa la línea en palabras como una shell sencilla: comillas simples o dobles para incluir
+// espacios y \ para escapar el carácter siguiente
+fn split_words(line: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quote = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match (c, quote) {
+            ('\\', Some('\'')) => word.get_or_insert_with(String::new).push(c),
+            ('\\', _) => {
+                let next = chars.next().ok_or_else(|| {
+                    let reason = text("\\ al final de la línea", "\\ at the end of the line");
+                    PngmeError::Usage(reason.to_string())
+                })?;
+                word.get_or_insert_with(String::new).push(next);
+            }
+            (c, Some(q)) if c == q => quote = None,
+            (c, Some(_)) => word.get_or_insert_with(String::new).push(c),
+            ('"' | '\'', None) => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            (c, None) if c.is_whitespace() => words.extend(word.take()),
+            (c, None) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quote.is_some() {
+        return Err(PngmeError::Usage(text("falta cerrar las comillas", "unclosed quotes").to_string()));
+    }
+    words.extend(word);
+    Ok(words)
+}
@@ -0,0 +1,206 @@
+use std::path::Path;
+use ed25519_dalek::pkcs8::{DecodePrivateKey, DecodePublicKey};
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey, SIGNATURE_LENGTH};
+use crate::chunk::Chunk;
+use crate::chunk_type::ChunkType;
+use crate::crypto;
+use crate::secret::Secret;
+use crate::png::Png;
+use crate::{tr, PngmeError, Result};
+use crate::locale::text;
+
+const VERSION: u8 = 1;
+const DOMAIN: &[u8] = b"pngme-sig-v1\0";
+
+// Datos del chunk siGN: versión + cantidad de tipos cubiertos (u8) + tipos + firma (64).
+// Sin tipos, la firma cubre todos los chunks del archivo salvo el propio siGN
+fn signed_message(png: &Png, covered: &[ChunkType]) -> Vec<u8> {
+    let mut message = DOMAIN.to_vec();
+    message.extend_from_slice(png.header());
+    message.push(covered.len() as u8);
+    for chunk_type in covered {
+        message.extend_from_slice(&chunk_type.bytes());
+    }
+    let included = |chunk: &&Chunk| {
+        let chunk_type = chunk.chunk_type();
+        *chunk_type != ChunkType::siGN && (covered.is_empty() || covered.contains(chunk_type))
+    };
+    for chunk in png.chunks().iter().filter(included) {
+        message.extend_from_slice(&chunk.length().to_be_bytes());
+        message.extend_from_slice(&chunk.chunk_type().bytes());
+        message.extend_from_slice(chunk.data());
+    }
+    message
+}
+
+const PEM_PREFIX: &[u8] = b"-----BEGIN";
+
+// Las claves se leen en PEM o, como con --keyfile, en crudo o base64 (32 bytes)
+fn read_key_bytes(path: &Path) -> Result<Option<Secret<[u8; 32]>>> {
+    if std::fs::read(path)?.trim_ascii_start().starts_with(PEM_PREFIX) {
+        return Ok(None);
+    }
+    let bytes = crypto::read_keyfile(path)?;
+    let key = bytes.expose().as_slice().try_into().map_err(|_| {
+        let len = bytes.expose().len();
+        PngmeError::InvalidKey(tr!(
+            "{}: una clave Ed25519 tiene 32 bytes y el archivo tiene {}",
+            "{}: an Ed25519 key has 32 bytes and the file has {}",
+            path.display(),
+            len
+        ))
+    })?;
+    Ok(Some(Secret::new(key)))
+}
+
+pub fn read_signing_key(path: &Path) -> Result<SigningKey> {
+    if let Some(seed) = read_key_bytes(path)? {
+        return Ok(SigningKey::from_bytes(seed.expose()));
+    }
+    let pem = std::fs::read_to_string(path)?;
+    SigningKey::from_pkcs8_pem(&pem).map_err(|err| PngmeError::InvalidKey(format!("{}: {}", path.display(), err)))
+}
+
+pub fn read_verifying_key(path: &Path) -> Result<VerifyingKey> {
+    let invalid = |err: String| PngmeError::InvalidKey(format!("{}: {}", path.display(), err));
+    if let Some(bytes) = read_key_bytes(path)? {
+        return VerifyingKey::from_bytes(bytes.expose()).map_err(|err| invalid(err.to_string()));
+    }
+    let pem = std::fs::read_to_string(path)?;
+    VerifyingKey::from_public_key_pem(&pem).map_err(|err| invalid(err.to_string()))
+}
+
+impl Png {
+    // Firma los chunks de los tipos indicados (todos si está vacío) y agrega un chunk siGN antes de IEND.
+    // Una firma anterior se reemplaza
+    pub fn sign(&mut self, key: &SigningKey, covered: &[ChunkType]) -> Result<()> {
+        if covered.len() > u8::MAX as usize {
+            let reason = text(
+                "demasiados tipos para cubrir con la firma",
+                "too many types to cover with the signature"
+            );
+            return Err(PngmeError::InvalidKey(reason.to_string()));
+        }
+        self.chunks_mut().retain(|chunk| *chunk.chunk_type() != ChunkType::siGN);
+        let signature = key.sign(&signed_message(self, covered));
+
+        let mut data = vec![VERSION, covered.len() as u8];
+        for chunk_type in covered {
+            data.extend_from_slice(&chunk_type.bytes());
+        }
+        data.extend_from_slice(&signature.to_bytes());
+        let index = self.legal_insert_index(&ChunkType::siGN);
+        self.chunks_mut().insert(index, Chunk::new(ChunkType::siGN, data)?);
+        Ok(())
+    }
+
+    // Verifica el chunk siGN y devuelve los tipos que cubre (vacío significa todos)
+    pub fn verify_signature(&self, key: &VerifyingKey) -> Result<Vec<ChunkType>> {
+        let bad = |reason: &str| PngmeError::BadSignature(reason.to_string());
+        let mut signatures = self.chunks().iter().filter(|chunk| *chunk.chunk_type() == ChunkType::siGN);
+        let chunk = signatures.next().ok_or_else(|| self.chunk_not_found("siGN"))?;
+        if signatures.next().is_some() {
+            return Err(bad(text("hay más de un chunk siGN", "there is more than one siGN chunk")));
+        }
+
+        let data = chunk.data();
+        match data.first() {
+            Some(&VERSION) => {}
+            Some(version) => return Err(bad(&tr!(
+                "versión de firma no soportada: {}",
+                "unsupported signature version: {}",
+                version
+            ))),
+            None => return Err(bad(text("el chunk siGN está vacío", "the siGN chunk is empty"))),
+        }
+        let count = *data.get(1).ok_or_else(|| bad(text(
+            "el chunk siGN está truncado",
+            "the siGN chunk is truncated"
+        )))? as usize;
+        let types_end = 2 + count * 4;
+        if data.len() != types_end + SIGNATURE_LENGTH {
+            return Err(bad(text("el chunk siGN tiene un largo inválido", "the siGN chunk has an invalid length")));
+        }
+        let covered = data[2..types_end]
+            .chunks_exact(4)
+            .map(|code| ChunkType::try_from([code[0], code[1], code[2], code[3]]))
+            .collect::<Result<Vec<_>>>()?;
+        let signature = Signature::from_bytes(data[types_end..].try_into().expect("largo verificado"));
+
+        key.verify_strict(&signed_message(self, &covered), &signature)
+            .map_err(|_| bad(text(
+                "el contenido firmado fue modificado o la clave no corresponde",
+                "the signed contents were modified or the key does not match"
+            )))?;
+        Ok(covered)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn chunk(code: &str, data: &str) -> Chunk {
+        Chunk::new(ChunkType::from_str(code).unwrap(), data.as_bytes().to_vec()).unwrap()
+    }
+
+    fn testing_png() -> Png {
+        Png::from_chunks(vec![chunk("IHDR", "cabecera"), chunk("IDAT", "pixeles"), chunk("IEND", "")])
+    }
+
+    fn key() -> SigningKey {
+        SigningKey::from_bytes(&[7; 32])
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let mut png = testing_png();
+        png.sign(&key(), &[]).unwrap();
+        assert_eq!(*png.chunks()[2].chunk_type(), ChunkType::siGN);
+        assert!(png.verify_signature(&key().verifying_key()).unwrap().is_empty());
+
+        let other = SigningKey::from_bytes(&[8; 32]);
+        assert!(matches!(png.verify_signature(&other.verifying_key()), Err(PngmeError::BadSignature(_))));
+    }
+
+    #[test]
+    fn test_tampering_is_detected() {
+        let mut png = testing_png();
+        png.sign(&key(), &[]).unwrap();
+        png.append_chunk(chunk("tEXt", "agregado")).unwrap();
+        assert!(png.verify_signature(&key().verifying_key()).is_err());
+    }
+
+    #[test]
+    fn test_selected_types() {
+        let mut png = testing_png();
+        png.sign(&key(), &[ChunkType::IDAT]).unwrap();
+        png.chunks_mut().insert(1, chunk("tEXt", "no cubierto"));
+        assert_eq!(png.verify_signature(&key().verifying_key()).unwrap(), [ChunkType::IDAT]);
+
+        png.chunks_mut().insert(2, chunk("IDAT", "mas pixeles"));
+        assert!(png.verify_signature(&key().verifying_key()).is_err());
+    }
+
+    #[test]
+    fn test_raw_and_base64_keys() {
+        let path = std::env::temp_dir().join(format!("pngme-sign-key-{}", std::process::id()));
+        std::fs::write(&path, [7; 32]).unwrap();
+        assert_eq!(read_signing_key(&path).unwrap(), key());
+        std::fs::write(&path, "BwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwc=\n").unwrap();
+        assert_eq!(read_signing_key(&path).unwrap(), key());
+        std::fs::write(&path, [7; 16]).unwrap();
+        assert!(matches!(read_signing_key(&path), Err(PngmeError::InvalidKey(_))));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_resign_replaces_signature() {
+        let mut png = testing_png();
+        png.sign(&key(), &[]).unwrap();
+        png.sign(&key(), &[]).unwrap();
+        assert_eq!(png.chunks().iter().filter(|c| *c.chunk_type() == ChunkType::siGN).count(), 1);
+        assert!(png.verify_signature(&key().verifying_key()).is_ok());
+    }
+}
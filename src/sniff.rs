@@ -0,0 +1,53 @@
+// Tipos de contenido que se reconocen por la firma del principio: (firma, MIME, extensión)
+const SIGNATURES: [(&[u8], &str, &str); 9] = [
+    (b"\x89PNG\r\n\x1a\n", "image/png", "png"),
+    (b"\xff\xd8\xff", "image/jpeg", "jpg"),
+    (b"GIF8", "image/gif", "gif"),
+    (b"%PDF-", "application/pdf", "pdf"),
+    (b"PK\x03\x04", "application/zip", "zip"),
+    (b"\x1f\x8b", "application/gzip", "gz"),
+    (b"\x28\xb5\x2f\xfd", "application/zstd", "zst"),
+    (b"7z\xbc\xaf\x27\x1c", "application/x-7z-compressed", "7z"),
+    (b"{\"", "application/json", "json"),
+];
+
+// Tipo MIME según los primeros bytes del contenido; lo que no se reconoce es binario genérico
+pub fn detect_mime(data: &[u8]) -> &'static str {
+    if let Some((_, mime, _)) = SIGNATURES.iter().find(|(magic, _, _)| data.starts_with(magic)) {
+        return mime;
+    }
+    if is_text(data) {
+        "text/plain"
+    } else {
+        "application/octet-stream"
+    }
+}
+
+// Extensión habitual para un tipo MIME, sin el punto
+pub fn extension(mime: &str) -> &'static str {
+    match mime {
+        "text/plain" => "txt",
+        mime => SIGNATURES.iter().find(|(_, other, _)| *other == mime).map_or("bin", |(_, _, extension)| extension),
+    }
+}
+
+// Texto que se puede mostrar en la terminal: UTF-8 sin caracteres de control salvo espacios y saltos
+pub fn is_text(data: &[u8]) -> bool {
+    std::str::from_utf8(data).is_ok_and(|text| text.chars().all(|c| !c.is_control() || matches!(c, '\n' | '\r' | '\t')))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_mime() {
+        assert_eq!(detect_mime(b"%PDF-1.7 ..."), "application/pdf");
+        assert_eq!(detect_mime(b"\x89PNG\r\n\x1a\n...."), "image/png");
+        assert_eq!(detect_mime("hola, ¿qué tal?\n".as_bytes()), "text/plain");
+        assert_eq!(detect_mime(b"hola\x00"), "application/octet-stream");
+        assert_eq!(detect_mime(&[0xff, 0x00]), "application/octet-stream");
+        assert_eq!(extension("application/pdf"), "pdf");
+        assert_eq!(extension("application/octet-stream"), "bin");
+    }
+}
@@ -0,0 +1,189 @@
+use std::collections::{BTreeMap, HashSet};
+use crate::chunk::Chunk;
+use crate::chunk_type::ChunkType;
+use crate::fragment::{split, FragmentHeader, MAX_FRAGMENT_DATA};
+use crate::hash::sha256;
+use crate::png::Png;
+use crate::{tr, PngmeError, Result};
+use crate::locale::text;
+
+// Encabezado de cada parte de un mensaje repartido entre varios PNG, en chunks spRd:
+// firma "PNGs" + versión + id (u32) + índice (u16) + total de partes (u16), seguido de los datos.
+// Una parte que no entra en un chunk se fragmenta como cualquier otro mensaje
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShardHeader {
+    pub id: u32,
+    pub index: u16,
+    pub total: u16,
+}
+
+impl ShardHeader {
+    pub const MAGIC: [u8; 4] = *b"PNGs";
+    pub const VERSION: u8 = 1;
+    pub const LEN: usize = 13;
+
+    pub fn parse(data: &[u8]) -> Option<(ShardHeader, &[u8])> {
+        if data.len() < ShardHeader::LEN || data[..4] != ShardHeader::MAGIC || data[4] != ShardHeader::VERSION {
+            return None;
+        }
+        let header = ShardHeader {
+            id: u32::from_be_bytes([data[5], data[6], data[7], data[8]]),
+            index: u16::from_be_bytes([data[9], data[10]]),
+            total: u16::from_be_bytes([data[11], data[12]]),
+        };
+        if header.total == 0 || header.index >= header.total {
+            return None;
+        }
+        Some((header, &data[ShardHeader::LEN..]))
+    }
+
+    pub fn to_bytes(&self) -> [u8; ShardHeader::LEN] {
+        let mut bytes = [0; ShardHeader::LEN];
+        bytes[..4].copy_from_slice(&ShardHeader::MAGIC);
+        bytes[4] = ShardHeader::VERSION;
+        bytes[5..9].copy_from_slice(&self.id.to_be_bytes());
+        bytes[9..11].copy_from_slice(&self.index.to_be_bytes());
+        bytes[11..13].copy_from_slice(&self.total.to_be_bytes());
+        bytes
+    }
+}
+
+// Reparte los datos en `count` partes de tamaño parecido; cada una son los chunks de un archivo
+pub fn shard(data: &[u8], count: usize) -> Result<Vec<Vec<Chunk>>> {
+    let total = u16::try_from(count)
+        .ok()
+        .filter(|total| *total > 0)
+        .ok_or_else(|| PngmeError::InvalidPayload(tr!(
+            "no se puede repartir un mensaje en {} archivos",
+            "a message cannot be split across {} files",
+            count
+        )))?;
+    let hash = sha256(data);
+    let id = u32::from_be_bytes([hash[0], hash[1], hash[2], hash[3]]);
+    let size = data.len().div_ceil(count).max(1);
+
+    (0..total)
+        .map(|index| {
+            let start = (index as usize * size).min(data.len());
+            let end = (start + size).min(data.len());
+            let header = ShardHeader { id, index, total };
+            split(ChunkType::spRd, &[&header.to_bytes()[..], &data[start..end]].concat(), MAX_FRAGMENT_DATA)
+        })
+        .collect()
+}
+
+impl Png {
+    // Datos de cada chunk spRd, con los fragmentos ya juntos
+    pub(crate) fn spread_parts(&self) -> Result<Vec<Vec<u8>>> {
+        let mut seen_fragments = HashSet::new();
+        let mut parts = Vec::new();
+        for chunk in self.chunks().iter().filter(|chunk| *chunk.chunk_type() == ChunkType::spRd) {
+            if let Some((fragment, _)) = FragmentHeader::parse(chunk.data()) {
+                if !seen_fragments.insert(fragment.id) {
+                    continue;
+                }
+            }
+            parts.push(self.reassemble(chunk)?.into_owned());
+        }
+        Ok(parts)
+    }
+
+    // Partes de mensajes repartidos que tiene este archivo, ya sin fragmentar
+    pub fn shards(&self) -> Result<Vec<(ShardHeader, Vec<u8>)>> {
+        let parts = self.spread_parts()?;
+        let shards = parts
+            .iter()
+            .filter_map(|data| ShardHeader::parse(data))
+            .map(|(header, piece)| (header, piece.to_vec()))
+            .collect();
+        Ok(shards)
+    }
+
+    // Borra las partes de mensajes repartidos, incluidos sus fragmentos
+    pub fn remove_shards(&mut self) -> usize {
+        let before = self.chunks().len();
+        self.chunks_mut().retain(|chunk| *chunk.chunk_type() != ChunkType::spRd);
+        before - self.chunks().len()
+    }
+}
+
+// Junta las partes de un mismo mensaje, vengan de los archivos que vengan y en cualquier orden
+pub fn gather(shards: Vec<(ShardHeader, Vec<u8>)>) -> Result<Vec<u8>> {
+    let ids: HashSet<u32> = shards.iter().map(|(header, _)| header.id).collect();
+    match ids.len() {
+        0 => {
+            let reason = text(
+                "los archivos no tienen partes de ningún mensaje",
+                "the files do not have parts of any message"
+            );
+            return Err(PngmeError::InvalidPayload(reason.to_string()));
+        }
+        1 => {}
+        n => return Err(PngmeError::InvalidPayload(tr!(
+            "los archivos tienen partes de {} mensajes distintos",
+            "the files have parts of {} different messages",
+            n
+        ))),
+    }
+
+    let total = shards[0].0.total;
+    let mut pieces = BTreeMap::new();
+    for (header, data) in shards {
+        if header.total != total {
+            let reason = text(
+                "las partes no coinciden en la cantidad total",
+                "the parts do not agree on the total count"
+            );
+            return Err(PngmeError::InvalidPayload(reason.to_string()));
+        }
+        if pieces.insert(header.index, data).is_some() {
+            return Err(PngmeError::InvalidPayload(tr!(
+                "la parte {} está repetida",
+                "part {} is repeated",
+                header.index + 1
+            )));
+        }
+    }
+    if let Some(missing) = (0..total).find(|index| !pieces.contains_key(index)) {
+        return Err(PngmeError::InvalidPayload(tr!(
+            "falta la parte {} de {}",
+            "part {} of {} is missing",
+            missing + 1,
+            total
+        )));
+    }
+    Ok(pieces.into_values().flatten().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cover(chunks: Vec<Chunk>) -> Png {
+        let ihdr = Chunk::new(ChunkType::IHDR, vec![0; 13]).unwrap();
+        let iend = Chunk::new(ChunkType::IEND, Vec::new()).unwrap();
+        Png::from_chunks([vec![ihdr], chunks, vec![iend]].into_iter().flatten().collect())
+    }
+
+    #[test]
+    fn test_shard_and_gather() {
+        let data: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let covers: Vec<Png> = shard(&data, 3).unwrap().into_iter().map(cover).collect();
+        let mut shards: Vec<_> = covers.iter().flat_map(|png| png.shards().unwrap()).collect();
+        assert_eq!(shards.len(), 3);
+        assert!(shards.iter().all(|(_, piece)| piece.len() <= 334));
+
+        shards.reverse();
+        assert_eq!(gather(shards.clone()).unwrap(), data);
+        shards.remove(1);
+        assert!(gather(shards).unwrap_err().to_string().contains("falta la parte 2 de 3"));
+    }
+
+    #[test]
+    fn test_gather_rejects_mixed_messages() {
+        let first = shard(b"uno", 2).unwrap().into_iter().map(cover).next().unwrap();
+        let second = shard(b"otro", 2).unwrap().into_iter().map(cover).next().unwrap();
+        let shards = [first.shards().unwrap(), second.shards().unwrap()].concat();
+        assert!(gather(shards).is_err());
+    }
+}
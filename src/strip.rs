@@ -0,0 +1,213 @@
+use crate::chunk::Chunk;
+use crate::chunk_type::ChunkType;
+use crate::png::Png;
+
+// Qué chunks borra Png::strip. Los chunks críticos nunca se tocan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StripFilter {
+    // tipos a borrar; vacío significa todos los auxiliares
+    include: Vec<ChunkType>,
+    // tipos que se conservan aunque estén incluidos
+    exclude: Vec<ChunkType>,
+}
+
+impl StripFilter {
+    // Texto, fecha y EXIF: los chunks que suelen llevar datos personales
+    pub const METADATA: [ChunkType; 5] = [
+        ChunkType::tEXt,
+        ChunkType::zTXt,
+        ChunkType::iTXt,
+        ChunkType::tIME,
+        ChunkType::eXIf,
+    ];
+
+    pub fn metadata() -> StripFilter {
+        StripFilter::only(&StripFilter::METADATA)
+    }
+
+    pub fn all_ancillary() -> StripFilter {
+        StripFilter { include: Vec::new(), exclude: Vec::new() }
+    }
+
+    pub fn only(types: &[ChunkType]) -> StripFilter {
+        StripFilter { include: types.to_vec(), exclude: Vec::new() }
+    }
+
+    pub fn include(mut self, types: &[ChunkType]) -> StripFilter {
+        self.include.extend_from_slice(types);
+        self
+    }
+
+    pub fn exclude(mut self, types: &[ChunkType]) -> StripFilter {
+        self.exclude.extend_from_slice(types);
+        self
+    }
+
+    pub fn matches(&self, chunk_type: &ChunkType) -> bool {
+        !chunk_type.is_critical()
+            && (self.include.is_empty() || self.include.contains(chunk_type))
+            && !self.exclude.contains(chunk_type)
+    }
+}
+
+// Familias de chunks que los presets conservan
+const COLOR_MANAGEMENT: [ChunkType; 8] = [
+    ChunkType::iCCP,
+    ChunkType::sRGB,
+    ChunkType::gAMA,
+    ChunkType::cHRM,
+    ChunkType::cICP,
+    ChunkType::mDCV,
+    ChunkType::cLLI,
+    ChunkType::sBIT,
+];
+const RENDERING: [ChunkType; 6] = [
+    ChunkType::tRNS,
+    ChunkType::bKGD,
+    ChunkType::pHYs,
+    ChunkType::acTL,
+    ChunkType::fcTL,
+    ChunkType::fdAT,
+];
+
+// Presets para quien no quiere elegir tipo por tipo:
+// social borra todo salvo color y presentación, archive sólo lo identificable (EXIF y fecha)
+// y minimal deja únicamente la transparencia
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StripPreset {
+    Social,
+    Archive,
+    Minimal,
+}
+
+impl StripPreset {
+    pub const ALL: [StripPreset; 3] = [StripPreset::Social, StripPreset::Archive, StripPreset::Minimal];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StripPreset::Social => "social",
+            StripPreset::Archive => "archive",
+            StripPreset::Minimal => "minimal",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<StripPreset> {
+        StripPreset::ALL.into_iter().find(|preset| preset.name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn filter(&self) -> StripFilter {
+        match self {
+            StripPreset::Social => StripFilter::all_ancillary().exclude(&COLOR_MANAGEMENT).exclude(&RENDERING),
+            StripPreset::Archive => StripFilter::only(&[ChunkType::eXIf, ChunkType::tIME]),
+            StripPreset::Minimal => StripFilter::all_ancillary().exclude(&[ChunkType::tRNS]),
+        }
+    }
+}
+
+impl Png {
+    // Borra los chunks que coinciden con el filtro y los devuelve en orden
+    pub fn strip(&mut self, filter: &StripFilter) -> Vec<Chunk> {
+        let (removed, kept) = std::mem::take(self.chunks_mut())
+            .into_iter()
+            .partition(|chunk| filter.matches(chunk.chunk_type()));
+        *self.chunks_mut() = kept;
+        removed
+    }
+
+    // Lo mismo, pero sólo si lo que cambió es crítico y no se pidió conservarlos
+    pub fn critical_changed(&mut self, changed: &ChunkType, keep_unsafe_to_copy: bool) -> Vec<Chunk> {
+        if !changed.is_critical() || keep_unsafe_to_copy {
+            return Vec::new();
+        }
+        self.strip_unsafe_to_copy()
+    }
+
+    // Después de cambiar un chunk crítico: la especificación pide no conservar los auxiliares con el bit de
+    // copia segura en 0 (tRNS, bKGD, hIST o uno desconocido), porque pueden depender de la imagen anterior
+    pub fn strip_unsafe_to_copy(&mut self) -> Vec<Chunk> {
+        let (removed, kept) = std::mem::take(self.chunks_mut()).into_iter().partition(|chunk| {
+            let chunk_type = chunk.chunk_type();
+            !chunk_type.is_critical() && !chunk_type.is_safe_to_copy()
+        });
+        *self.chunks_mut() = kept;
+        removed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn chunk(code: &str) -> Chunk {
+        Chunk::new(ChunkType::from_str(code).unwrap(), Vec::new()).unwrap()
+    }
+
+    fn testing_png() -> Png {
+        Png::from_chunks(["IHDR", "iCCP", "tEXt", "eXIf", "IDAT", "tIME", "ruSt", "IEND"].into_iter().map(chunk).collect())
+    }
+
+    fn types(png: &Png) -> Vec<String> {
+        png.chunks().iter().map(|chunk| chunk.chunk_type().to_string()).collect()
+    }
+
+    #[test]
+    fn test_strip_metadata() {
+        let mut png = testing_png();
+        let removed = png.strip(&StripFilter::metadata());
+        assert_eq!(removed.len(), 3);
+        assert_eq!(types(&png), ["IHDR", "iCCP", "IDAT", "ruSt", "IEND"]);
+    }
+
+    #[test]
+    fn test_strip_all_with_exclude() {
+        let mut png = testing_png();
+        png.strip(&StripFilter::all_ancillary().exclude(&[ChunkType::iCCP]));
+        assert_eq!(types(&png), ["IHDR", "iCCP", "IDAT", "IEND"]);
+    }
+
+    #[test]
+    fn test_strip_presets() {
+        let stripped = |preset: StripPreset| {
+            let mut png = testing_png();
+            png.strip(&preset.filter());
+            types(&png)
+        };
+        assert_eq!(stripped(StripPreset::Social), ["IHDR", "iCCP", "IDAT", "IEND"]);
+        assert_eq!(stripped(StripPreset::Archive), ["IHDR", "iCCP", "tEXt", "IDAT", "ruSt", "IEND"]);
+        assert_eq!(stripped(StripPreset::Minimal), ["IHDR", "IDAT", "IEND"]);
+        assert_eq!(StripPreset::from_name("Social"), Some(StripPreset::Social));
+    }
+
+    #[test]
+    fn test_strip_unsafe_to_copy() {
+        let types_in = ["IHDR", "PLTE", "tRNS", "IDAT", "tEXt", "ruST", "IEND"];
+        let mut png = Png::from_chunks(types_in.into_iter().map(chunk).collect());
+        let removed = png.strip_unsafe_to_copy();
+        assert_eq!(removed.iter().map(|chunk| chunk.chunk_type().to_string()).collect::<Vec<_>>(), ["tRNS", "ruST"]);
+        assert_eq!(types(&png), ["IHDR", "PLTE", "IDAT", "tEXt", "IEND"]);
+    }
+
+    #[test]
+    fn test_patch_idat_drops_unsafe_to_copy() {
+        let patched = |index: usize, keep: bool| {
+            let types_in = ["IHDR", "PLTE", "tRNS", "IDAT", "tEXt", "IEND"];
+            let mut png = Png::from_chunks(types_in.into_iter().map(chunk).collect());
+            assert_eq!(png.patch_chunk_at(index, 0, &[0xde, 0xad]).unwrap(), Vec::<u8>::new());
+            assert_eq!(png.chunks()[index].data(), [0xde, 0xad]);
+            let changed = *png.chunks()[index].chunk_type();
+            png.critical_changed(&changed, keep);
+            types(&png)
+        };
+        assert_eq!(patched(3, false), ["IHDR", "PLTE", "IDAT", "tEXt", "IEND"]);
+        assert_eq!(patched(3, true), ["IHDR", "PLTE", "tRNS", "IDAT", "tEXt", "IEND"]);
+        assert_eq!(patched(4, false), ["IHDR", "PLTE", "tRNS", "IDAT", "tEXt", "IEND"]);
+    }
+
+    #[test]
+    fn test_strip_never_removes_critical() {
+        let mut png = testing_png();
+        png.strip(&StripFilter::only(&[ChunkType::IDAT, ChunkType::tIME]));
+        assert_eq!(types(&png), ["IHDR", "iCCP", "tEXt", "eXIf", "IDAT", "ruSt", "IEND"]);
+    }
+}
@@ -0,0 +1,57 @@
+// Cantidad mínima de inserciones, borrados y reemplazos de caracteres para pasar de `a` a `b`
+// (distancia de Levenshtein)
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let replace = previous[j] + usize::from(ca != *cb);
+            current.push(replace.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+// Los candidatos más parecidos a `wanted`, del más cercano al más lejano y sin repetir. Una
+// diferencia sólo de mayúsculas cuenta como la más cercana, porque en un tipo de chunk es lo más
+// fácil de errar
+pub fn similar<'a>(wanted: &str, candidates: impl IntoIterator<Item = &'a str>) -> Vec<&'a str> {
+    let max = (wanted.chars().count() / 2).max(1);
+    let mut found: Vec<(usize, &str)> = Vec::new();
+    for candidate in candidates {
+        if candidate == wanted || found.iter().any(|(_, seen)| *seen == candidate) {
+            continue;
+        }
+        let distance = if candidate.eq_ignore_ascii_case(wanted) { 0 } else { edit_distance(wanted, candidate) };
+        if distance <= max {
+            found.push((distance, candidate));
+        }
+    }
+    found.sort_by_key(|(distance, _)| *distance);
+    found.into_iter().map(|(_, candidate)| candidate).take(3).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("rust", "rust"), 0);
+        assert_eq!(edit_distance("rust", "ruSt"), 1);
+        assert_eq!(edit_distance("tEXt", "zTXt"), 2);
+        assert_eq!(edit_distance("", "IEND"), 4);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn test_similar() {
+        let types = ["IHDR", "ruSt", "tEXt", "IDAT", "ruSt", "IEND"];
+        assert_eq!(similar("rust", types), vec!["ruSt"]);
+        assert_eq!(similar("tEXT", types), vec!["tEXt"]);
+        assert_eq!(similar("IDAX", types), vec!["IDAT"]);
+        assert!(similar("zzzz", types).is_empty());
+    }
+}
@@ -0,0 +1,212 @@
+use std::path::PathBuf;
+use pngme::chunk::Chunk;
+use pngme::chunk_type::ChunkType;
+use pngme::hexdump::hexdump;
+use pngme::png::Png;
+use pngme::locale::text;
+use pngme::{tr, Result};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use crate::args::TuiArgs;
+use crate::commands::{default_dir, read_png, write_png};
+
+const KEYS: &str = "↑↓ elegir  PgUp/PgDn datos  d borrar  e exportar  s guardar  q salir";
+const KEYS_EN: &str = "↑↓ select  PgUp/PgDn data  d delete  e export  s save  q quit";
+
+struct Browser {
+    png: Png,
+    // dónde guarda `s`: -o o el archivo abierto
+    output: PathBuf,
+    list: ListState,
+    // primera línea visible del volcado, de a 16 bytes
+    scroll: usize,
+    modified: bool,
+    // el primer q con cambios sin guardar sólo avisa
+    warned: bool,
+    status: String,
+}
+
+pub fn tui(args: TuiArgs) -> Result<()> {
+    let png = read_png(&args.file_path)?;
+    let output = args.output.unwrap_or(args.file_path);
+    let mut browser = Browser {
+        png,
+        output,
+        list: ListState::default().with_selected(Some(0)),
+        scroll: 0,
+        modified: false,
+        warned: false,
+        status: String::new(),
+    };
+    let mut terminal = ratatui::try_init()?;
+    let result = browser.run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+impl Browser {
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            if key.code != KeyCode::Char('q') {
+                self.warned = false;
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc if self.modified && !self.warned => {
+                    self.warned = true;
+                    self.status = tr!(
+                        "Hay cambios sin guardar: s para guardarlos, q otra vez para salir igual",
+                        "There are unsaved changes: s to save them, q again to exit anyway"
+                    );
+                }
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Up | KeyCode::Char('k') => self.select(-1),
+                KeyCode::Down | KeyCode::Char('j') => self.select(1),
+                KeyCode::Home => self.select(isize::MIN),
+                KeyCode::End => self.select(isize::MAX),
+                KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(16),
+                KeyCode::PageDown => self.scroll_down(16),
+                KeyCode::Char('d') | KeyCode::Delete => self.delete(),
+                KeyCode::Char('e') => self.status = self.export().unwrap_or_else(|err| format!("Error: {}", err)),
+                KeyCode::Char('s') => self.status = self.save().unwrap_or_else(|err| format!("Error: {}", err)),
+                _ => {}
+            }
+        }
+    }
+
+    fn selected(&self) -> Option<(usize, &Chunk)> {
+        let index = self.list.selected()?;
+        self.png.get(index).map(|chunk| (index, chunk))
+    }
+
+    fn select(&mut self, delta: isize) {
+        let last = self.png.len().saturating_sub(1);
+        let current = self.list.selected().unwrap_or(0);
+        let next = current.saturating_add_signed(delta).min(last);
+        self.list.select(Some(next));
+        self.scroll = 0;
+    }
+
+    fn scroll_down(&mut self, lines: usize) {
+        let total = self.selected().map_or(0, |(_, chunk)| chunk.data().len().div_ceil(16));
+        self.scroll = (self.scroll + lines).min(total.saturating_sub(1));
+    }
+
+    fn delete(&mut self) {
+        let Some(index) = self.list.selected() else {
+            return;
+        };
+        if let Some(chunk) = self.png.remove_chunk_at(index) {
+            self.status = tr!("Se eliminó {} ({} bytes)", "Removed {} ({} bytes)", chunk.chunk_type(), chunk.length());
+            self.modified = true;
+            self.select(0);
+        }
+    }
+
+    // Los datos del chunk, tal cual, en TIPO-N.bin
+    fn export(&self) -> Result<String> {
+        let Some((index, chunk)) = self.selected() else {
+            return Ok(String::new());
+        };
+        let path = default_dir().join(format!("{}-{}.bin", chunk.chunk_type(), index));
+        std::fs::File::options().write(true).create_new(true).open(&path)?;
+        std::fs::write(&path, chunk.data())?;
+        Ok(tr!("{} bytes guardados en {}", "{} bytes saved to {}", chunk.length(), path.display()))
+    }
+
+    fn save(&mut self) -> Result<String> {
+        write_png(&self.output, &self.png)?;
+        self.modified = false;
+        Ok(tr!("Guardado en {}", "Saved to {}", self.output.display()))
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, footer] = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [left, right] = Layout::horizontal([Constraint::Length(28), Constraint::Min(0)]).areas(main);
+        let [properties, data] = Layout::vertical([Constraint::Length(7), Constraint::Min(0)]).areas(right);
+
+        let items: Vec<ListItem> = self
+            .png
+            .chunks()
+            .iter()
+            .enumerate()
+            .map(|(index, chunk)| {
+                let ct = chunk.chunk_type();
+                let line = Line::from(vec![
+                    Span::raw(format!("{:>4}  ", index)),
+                    Span::styled(ct.to_string(), type_style(ct)),
+                    Span::raw(format!("  {:>10}", chunk.length())),
+                ]);
+                ListItem::new(line)
+            })
+            .collect();
+        let title = format!(" {} chunks{} ", self.png.len(), if self.modified { " *" } else { "" });
+        let list = List::new(items)
+            .block(Block::bordered().title(title))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, left, &mut self.list);
+
+        if let Some((_, chunk)) = self.selected() {
+            let block = Block::bordered().title(text(" propiedades ", " properties "));
+            frame.render_widget(Paragraph::new(describe(chunk)).block(block), properties);
+            frame.render_widget(self.dump(chunk, data), data);
+        }
+        let status = if self.status.is_empty() { text(KEYS, KEYS_EN) } else { &self.status };
+        frame.render_widget(Paragraph::new(status), footer);
+    }
+
+    // Sólo se arma el volcado de las líneas que entran en pantalla, porque un IDAT puede ser enorme
+    fn dump(&self, chunk: &Chunk, area: Rect) -> Paragraph<'static> {
+        let height = area.height.saturating_sub(2) as usize;
+        let start = (self.scroll * 16).min(chunk.data().len());
+        let end = (start + height * 16).min(chunk.data().len());
+        let title = tr!(" datos: {}-{} de {} bytes ", " data: {}-{} of {} bytes ", start, end, chunk.data().len());
+        Paragraph::new(hexdump(&chunk.data()[start..end], start)).block(Block::bordered().title(title))
+    }
+}
+
+// Cada letra del tipo con el bit 5 que la hace mayúscula o minúscula y lo que significa
+fn describe(chunk: &Chunk) -> Vec<Line<'static>> {
+    let ct = chunk.chunk_type();
+    let bytes = ct.bytes();
+    let properties = [
+        if ct.is_critical() { text("crítico", "critical") } else { text("auxiliar", "ancillary") },
+        if ct.is_public() { text("público", "public") } else { text("privado", "private") },
+        if ct.is_reserved_bit_valid() {
+            text("reservado válido", "valid reserved")
+        } else {
+            text("reservado inválido", "invalid reserved")
+        },
+        if ct.is_safe_to_copy() {
+            text("copia segura", "safe to copy")
+        } else {
+            text("copia insegura", "unsafe to copy")
+        },
+    ];
+    let mut lines = vec![Line::from(format!("length {}  crc {:08x}", chunk.length(), chunk.crc()))];
+    for (i, (byte, property)) in bytes.iter().zip(properties).enumerate() {
+        lines.push(Line::from(format!("{}  byte {}, bit 5 = {}  {}", *byte as char, i, (byte >> 5) & 1, property)));
+    }
+    lines
+}
+
+// Los mismos colores que list: críticos, auxiliares públicos y privados
+fn type_style(chunk_type: &ChunkType) -> Style {
+    if chunk_type.is_critical() {
+        Style::new().fg(Color::Blue).add_modifier(Modifier::BOLD)
+    } else if chunk_type.is_public() {
+        Style::new().fg(Color::Green)
+    } else {
+        Style::new().fg(Color::Magenta)
+    }
+}
@@ -0,0 +1,786 @@
+use crate::chunk::Chunk;
+use crate::chunk_type::ChunkType;
+use crate::compression::{deflate, inflate_limited};
+use crate::png::Png;
+use crate::{tr, PngmeError, Result};
+use crate::locale;
+
+// Interpretación estructurada de los chunks estándar más comunes
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypedChunk {
+    Ihdr(Ihdr),
+    Text(TextChunk),
+    Ztxt(Ztxt),
+    Itxt(Itxt),
+    Iccp(Iccp),
+    Time(Time),
+    Phys(Phys),
+    Gama(Gama),
+    Iend,
+    // tipos que todavía no se interpretan
+    Other(ChunkType),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ihdr {
+    pub width: u32,
+    pub height: u32,
+    pub bit_depth: u8,
+    pub color_type: u8,
+    pub compression_method: u8,
+    pub filter_method: u8,
+    pub interlace_method: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorType {
+    Grayscale,
+    Rgb,
+    Indexed,
+    GrayscaleAlpha,
+    Rgba,
+}
+
+impl ColorType {
+    pub fn from_code(code: u8) -> Option<ColorType> {
+        match code {
+            0 => Some(ColorType::Grayscale),
+            2 => Some(ColorType::Rgb),
+            3 => Some(ColorType::Indexed),
+            4 => Some(ColorType::GrayscaleAlpha),
+            6 => Some(ColorType::Rgba),
+            _ => None,
+        }
+    }
+
+    pub fn allowed_bit_depths(&self) -> &'static [u8] {
+        match self {
+            ColorType::Grayscale => &[1, 2, 4, 8, 16],
+            ColorType::Indexed => &[1, 2, 4, 8],
+            ColorType::Rgb | ColorType::GrayscaleAlpha | ColorType::Rgba => &[8, 16],
+        }
+    }
+
+    pub fn channels(&self) -> u8 {
+        match self {
+            ColorType::Grayscale | ColorType::Indexed => 1,
+            ColorType::GrayscaleAlpha => 2,
+            ColorType::Rgb => 3,
+            ColorType::Rgba => 4,
+        }
+    }
+}
+
+impl std::fmt::Display for ColorType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ColorType::Grayscale => locale::text("escala de grises", "grayscale"),
+            ColorType::Rgb => "RGB",
+            ColorType::Indexed => locale::text("paleta indexada", "indexed color"),
+            ColorType::GrayscaleAlpha => locale::text("escala de grises con alfa", "grayscale with alpha"),
+            ColorType::Rgba => "RGBA",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Ihdr {
+    pub fn from_chunk(chunk: &Chunk) -> Result<Ihdr> {
+        if *chunk.chunk_type() != ChunkType::IHDR {
+            return Err(invalid(chunk.chunk_type(), locale::text("no es un chunk IHDR", "it is not an IHDR chunk")));
+        }
+        let data = expect_len(chunk, 13)?;
+        let ihdr = Ihdr {
+            width: be_u32(&data[0..4]),
+            height: be_u32(&data[4..8]),
+            bit_depth: data[8],
+            color_type: data[9],
+            compression_method: data[10],
+            filter_method: data[11],
+            interlace_method: data[12],
+        };
+        ihdr.validate()?;
+        Ok(ihdr)
+    }
+
+    pub fn color(&self) -> Option<ColorType> {
+        ColorType::from_code(self.color_type)
+    }
+
+    pub fn is_interlaced(&self) -> bool {
+        self.interlace_method == 1
+    }
+
+    // Verifica las combinaciones permitidas por la especificación
+    pub fn validate(&self) -> Result<()> {
+        let ct = &ChunkType::IHDR;
+        if self.width == 0 || self.height == 0 {
+            return Err(invalid(ct, locale::text(
+                "el ancho y el alto deben ser mayores a cero",
+                "width and height must be greater than zero"
+            )));
+        }
+        if self.width > Chunk::MAX_LENGTH || self.height > Chunk::MAX_LENGTH {
+            return Err(invalid(ct, locale::text(
+                "el ancho y el alto no pueden superar 2^31-1",
+                "width and height cannot exceed 2^31-1"
+            )));
+        }
+        let color = self
+            .color()
+            .ok_or_else(|| invalid(ct, &tr!(
+                "tipo de color desconocido: {}",
+                "unknown color type: {}",
+                self.color_type
+            )))?;
+        if !color.allowed_bit_depths().contains(&self.bit_depth) {
+            let reason = tr!(
+                "profundidad de {} bits no permitida para {}",
+                "bit depth {} not allowed for {}",
+                self.bit_depth,
+                color
+            );
+            return Err(invalid(ct, &reason));
+        }
+        if self.compression_method != 0 {
+            return Err(invalid(ct, locale::text("método de compresión desconocido", "unknown compression method")));
+        }
+        if self.filter_method != 0 {
+            return Err(invalid(ct, locale::text("método de filtrado desconocido", "unknown filter method")));
+        }
+        if self.interlace_method > 1 {
+            return Err(invalid(ct, locale::text("método de entrelazado desconocido", "unknown interlace method")));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextChunk {
+    pub keyword: String,
+    pub text: String,
+}
+
+// El texto queda comprimido tal como viene en el archivo
+impl TextChunk {
+    pub fn new(keyword: &str, text: &str) -> Result<TextChunk> {
+        validate_keyword(&ChunkType::tEXt, keyword)?;
+        to_latin1(&ChunkType::tEXt, text, locale::text("el texto", "the text"))?;
+        Ok(TextChunk { keyword: keyword.to_string(), text: text.to_string() })
+    }
+
+    // keyword \0 texto, ambos en Latin-1
+    pub fn to_chunk(&self) -> Result<Chunk> {
+        let mut data = validate_keyword(&ChunkType::tEXt, &self.keyword)?;
+        data.push(0);
+        data.extend(to_latin1(&ChunkType::tEXt, &self.text, locale::text("el texto", "the text"))?);
+        Chunk::new(ChunkType::tEXt, data)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ztxt {
+    pub keyword: String,
+    pub compression_method: u8,
+    pub compressed_text: Vec<u8>,
+}
+
+impl Ztxt {
+    // Comprime el texto (Latin-1) con zlib
+    pub fn new(keyword: &str, text: &str) -> Result<Ztxt> {
+        validate_keyword(&ChunkType::zTXt, keyword)?;
+        let text = to_latin1(&ChunkType::zTXt, text, locale::text("el texto", "the text"))?;
+        Ok(Ztxt {
+            keyword: keyword.to_string(),
+            compression_method: 0,
+            compressed_text: deflate(&text)?,
+        })
+    }
+
+    pub fn text(&self) -> Result<String> {
+        self.text_limited(DEFAULT_INFLATE_LIMIT)
+    }
+
+    // Falla con DecompressionLimit si el texto descomprimido pasa de `limit` bytes
+    pub fn text_limited(&self, limit: usize) -> Result<String> {
+        if self.compression_method != 0 {
+            let reason = tr!(
+                "método de compresión desconocido: {}",
+                "unknown compression method: {}",
+                self.compression_method
+            );
+            return Err(invalid(&ChunkType::zTXt, &reason));
+        }
+        let text = inflate_chunk(&ChunkType::zTXt, &self.compressed_text, limit, locale::text("el texto", "the text"))?;
+        Ok(latin1(&text))
+    }
+
+    // keyword \0 método texto_comprimido
+    pub fn to_chunk(&self) -> Result<Chunk> {
+        let mut data = validate_keyword(&ChunkType::zTXt, &self.keyword)?;
+        data.push(0);
+        data.push(self.compression_method);
+        data.extend_from_slice(&self.compressed_text);
+        Chunk::new(ChunkType::zTXt, data)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Itxt {
+    pub keyword: String,
+    pub compressed: bool,
+    pub compression_method: u8,
+    pub language_tag: String,
+    pub translated_keyword: String,
+    // UTF-8, o zlib si `compressed`
+    pub text: Vec<u8>,
+}
+
+impl Itxt {
+    pub fn new(
+        keyword: &str,
+        language_tag: &str,
+        translated_keyword: &str,
+        text: &str,
+        compressed: bool,
+    ) -> Result<Itxt> {
+        validate_keyword(&ChunkType::iTXt, keyword)?;
+        // etiqueta estilo RFC 3066: letras, dígitos y guiones; vacía si no se conoce
+        if !language_tag.bytes().all(|byte| byte.is_ascii_alphanumeric() || byte == b'-') {
+            return Err(invalid(&ChunkType::iTXt, locale::text(
+                "la etiqueta de idioma sólo admite letras, dígitos y '-'",
+                "the language tag only allows letters, digits and '-'"
+            )));
+        }
+        if translated_keyword.contains('\0') || text.contains('\0') {
+            return Err(invalid(&ChunkType::iTXt, locale::text(
+                "el texto no puede contener bytes nulos",
+                "the text cannot contain null bytes"
+            )));
+        }
+        let text = if compressed { deflate(text.as_bytes())? } else { text.as_bytes().to_vec() };
+        Ok(Itxt {
+            keyword: keyword.to_string(),
+            compressed,
+            compression_method: 0,
+            language_tag: language_tag.to_string(),
+            translated_keyword: translated_keyword.to_string(),
+            text,
+        })
+    }
+
+    pub fn text(&self) -> Result<String> {
+        self.text_limited(DEFAULT_INFLATE_LIMIT)
+    }
+
+    // Falla con DecompressionLimit si el texto comprimido pasa de `limit` bytes al descomprimirse
+    pub fn text_limited(&self, limit: usize) -> Result<String> {
+        let text = if self.compressed {
+            if self.compression_method != 0 {
+                let reason = tr!(
+                    "método de compresión desconocido: {}",
+                    "unknown compression method: {}",
+                    self.compression_method
+                );
+                return Err(invalid(&ChunkType::iTXt, &reason));
+            }
+            inflate_chunk(&ChunkType::iTXt, &self.text, limit, locale::text("el texto", "the text"))?
+        } else {
+            self.text.clone()
+        };
+        String::from_utf8(text).map_err(|_| invalid(&ChunkType::iTXt, locale::text(
+            "el texto no es UTF-8",
+            "the text is not UTF-8"
+        )))
+    }
+
+    // keyword \0 flag método idioma \0 keyword_traducido \0 texto
+    pub fn to_chunk(&self) -> Result<Chunk> {
+        let mut data = validate_keyword(&ChunkType::iTXt, &self.keyword)?;
+        data.push(0);
+        data.push(u8::from(self.compressed));
+        data.push(self.compression_method);
+        data.extend_from_slice(self.language_tag.as_bytes());
+        data.push(0);
+        data.extend_from_slice(self.translated_keyword.as_bytes());
+        data.push(0);
+        data.extend_from_slice(&self.text);
+        Chunk::new(ChunkType::iTXt, data)
+    }
+}
+
+// Perfil ICC embebido; el perfil queda comprimido tal como viene en el archivo
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Iccp {
+    pub profile_name: String,
+    pub compression_method: u8,
+    pub compressed_profile: Vec<u8>,
+}
+
+impl Iccp {
+    pub fn profile(&self) -> Result<Vec<u8>> {
+        self.profile_limited(DEFAULT_INFLATE_LIMIT)
+    }
+
+    // Falla con DecompressionLimit si el perfil descomprimido pasa de `limit` bytes
+    pub fn profile_limited(&self, limit: usize) -> Result<Vec<u8>> {
+        if self.compression_method != 0 {
+            let reason = tr!(
+                "método de compresión desconocido: {}",
+                "unknown compression method: {}",
+                self.compression_method
+            );
+            return Err(invalid(&ChunkType::iCCP, &reason));
+        }
+        inflate_chunk(&ChunkType::iCCP, &self.compressed_profile, limit, locale::text("el perfil", "the profile"))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Time {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Phys {
+    pub pixels_per_unit_x: u32,
+    pub pixels_per_unit_y: u32,
+    // 0 = desconocida (sólo relación de aspecto), 1 = metro
+    pub unit: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gama {
+    // gamma multiplicado por 100000
+    pub gamma: u32,
+}
+
+impl Gama {
+    pub fn value(&self) -> f64 {
+        self.gamma as f64 / 100_000.0
+    }
+}
+
+// Lo más que se descomprime de un zTXt, iTXt o iCCP cuando no se pide otro límite: alcanza para cualquier
+// texto o perfil real y evita que un archivo de pocos KB agote la memoria
+pub const DEFAULT_INFLATE_LIMIT: usize = 16 << 20;
+
+fn inflate_chunk(chunk_type: &ChunkType, data: &[u8], limit: usize, field: &str) -> Result<Vec<u8>> {
+    match inflate_limited(data, limit) {
+        Ok(Some(out)) => Ok(out),
+        Ok(None) => Err(PngmeError::DecompressionLimit { chunk_type: Some(chunk_type.to_string()), limit }),
+        Err(_) => Err(invalid(chunk_type, &tr!("{} comprimido está dañado", "compressed {} is damaged", field))),
+    }
+}
+
+fn invalid(chunk_type: &ChunkType, reason: &str) -> PngmeError {
+    PngmeError::InvalidChunkData { chunk_type: chunk_type.to_string(), reason: reason.to_string() }
+}
+
+fn expect_len(chunk: &Chunk, len: usize) -> Result<&[u8]> {
+    let data = chunk.data();
+    if data.len() != len {
+        let reason = tr!("se esperaban {} bytes y hay {}", "{} bytes expected, {} available", len, data.len());
+        return Err(invalid(chunk.chunk_type(), &reason));
+    }
+    Ok(data)
+}
+
+fn be_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+// tEXt, zTXt e iTXt usan Latin-1 para el keyword
+pub(crate) fn latin1(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| *byte as char).collect()
+}
+
+// Separa el primer campo terminado en \0 del resto
+fn split_null<'a>(chunk_type: &ChunkType, data: &'a [u8], field: &str) -> Result<(&'a [u8], &'a [u8])> {
+    match data.iter().position(|byte| *byte == 0) {
+        Some(index) => Ok((&data[..index], &data[index + 1..])),
+        None => Err(invalid(chunk_type, &tr!(
+            "falta el separador nulo después de {}",
+            "the null separator after {} is missing",
+            field
+        ))),
+    }
+}
+
+// Reglas de la especificación: 1 a 79 caracteres Latin-1 imprimibles, sin espacios al
+// principio, al final ni repetidos
+pub(crate) fn validate_keyword(chunk_type: &ChunkType, keyword: &str) -> Result<Vec<u8>> {
+    let bytes = to_latin1(chunk_type, keyword, locale::text("el keyword", "the keyword"))?;
+    if bytes.is_empty() || bytes.len() > 79 {
+        return Err(invalid(chunk_type, locale::text(
+            "el keyword debe tener entre 1 y 79 caracteres",
+            "the keyword must have between 1 and 79 characters"
+        )));
+    }
+    if bytes.iter().any(|byte| !matches!(byte, 32..=126 | 161..=255)) {
+        return Err(invalid(chunk_type, locale::text(
+            "el keyword sólo admite caracteres Latin-1 imprimibles",
+            "the keyword only allows printable Latin-1 characters"
+        )));
+    }
+    if keyword.starts_with(' ') || keyword.ends_with(' ') || keyword.contains("  ") {
+        return Err(invalid(chunk_type, locale::text(
+            "el keyword no puede tener espacios al principio, al final ni repetidos",
+            "the keyword cannot have leading, trailing or repeated spaces"
+        )));
+    }
+    Ok(bytes)
+}
+
+pub(crate) fn to_latin1(chunk_type: &ChunkType, text: &str, field: &str) -> Result<Vec<u8>> {
+    text.chars()
+        .map(|c| match u8::try_from(u32::from(c)) {
+            Ok(0) => Err(invalid(chunk_type, &tr!(
+                "{} no puede contener bytes nulos",
+                "{} cannot contain null bytes",
+                field
+            ))),
+            Ok(byte) => Ok(byte),
+            Err(_) => Err(invalid(chunk_type, &tr!(
+                "{} tiene caracteres fuera de Latin-1 (usar iTXt)",
+                "{} has characters outside Latin-1 (use iTXt)",
+                field
+            ))),
+        })
+        .collect()
+}
+
+fn keyword(chunk_type: &ChunkType, bytes: &[u8]) -> Result<String> {
+    if bytes.is_empty() || bytes.len() > 79 {
+        return Err(invalid(chunk_type, locale::text(
+            "el keyword debe tener entre 1 y 79 bytes",
+            "the keyword must have between 1 and 79 bytes"
+        )));
+    }
+    Ok(latin1(bytes))
+}
+
+impl TryFrom<&Chunk> for TypedChunk {
+    type Error = PngmeError;
+    fn try_from(chunk: &Chunk) -> Result<TypedChunk> {
+        let ct = chunk.chunk_type();
+        let typed = match ct.bytes() {
+            [b'I', b'H', b'D', b'R'] => TypedChunk::Ihdr(Ihdr::from_chunk(chunk)?),
+            [b't', b'E', b'X', b't'] => {
+                let (key, text) = split_null(ct, chunk.data(), locale::text("el keyword", "the keyword"))?;
+                TypedChunk::Text(TextChunk { keyword: keyword(ct, key)?, text: latin1(text) })
+            }
+            [b'z', b'T', b'X', b't'] => {
+                let (key, rest) = split_null(ct, chunk.data(), locale::text("el keyword", "the keyword"))?;
+                let (method, compressed) = rest
+                    .split_first()
+                    .ok_or_else(|| invalid(ct, locale::text(
+                        "falta el método de compresión",
+                        "the compression method is missing"
+                    )))?;
+                TypedChunk::Ztxt(Ztxt {
+                    keyword: keyword(ct, key)?,
+                    compression_method: *method,
+                    compressed_text: compressed.to_vec(),
+                })
+            }
+            [b'i', b'T', b'X', b't'] => {
+                let (key, rest) = split_null(ct, chunk.data(), locale::text("el keyword", "the keyword"))?;
+                if rest.len() < 2 {
+                    return Err(invalid(ct, locale::text(
+                        "faltan los campos de compresión",
+                        "the compression fields are missing"
+                    )));
+                }
+                let (flag, method, rest) = (rest[0], rest[1], &rest[2..]);
+                let (language, rest) = split_null(ct, rest, locale::text("la etiqueta de idioma", "the language tag"))?;
+                let (translated, text) = split_null(ct, rest, locale::text(
+                    "el keyword traducido",
+                    "the translated keyword"
+                ))?;
+                let translated = std::str::from_utf8(translated)
+                    .map_err(|_| invalid(ct, locale::text(
+                        "el keyword traducido no es UTF-8",
+                        "the translated keyword is not UTF-8"
+                    )))?;
+                TypedChunk::Itxt(Itxt {
+                    keyword: keyword(ct, key)?,
+                    compressed: flag != 0,
+                    compression_method: method,
+                    language_tag: latin1(language),
+                    translated_keyword: translated.to_string(),
+                    text: text.to_vec(),
+                })
+            }
+            [b'i', b'C', b'C', b'P'] => {
+                let (name, rest) = split_null(ct, chunk.data(), locale::text(
+                    "el nombre del perfil",
+                    "the profile name"
+                ))?;
+                let (method, compressed) = rest
+                    .split_first()
+                    .ok_or_else(|| invalid(ct, locale::text(
+                        "falta el método de compresión",
+                        "the compression method is missing"
+                    )))?;
+                TypedChunk::Iccp(Iccp {
+                    profile_name: keyword(ct, name)?,
+                    compression_method: *method,
+                    compressed_profile: compressed.to_vec(),
+                })
+            }
+            [b't', b'I', b'M', b'E'] => {
+                let data = expect_len(chunk, 7)?;
+                TypedChunk::Time(Time {
+                    year: u16::from_be_bytes([data[0], data[1]]),
+                    month: data[2],
+                    day: data[3],
+                    hour: data[4],
+                    minute: data[5],
+                    second: data[6],
+                })
+            }
+            [b'p', b'H', b'Y', b's'] => {
+                let data = expect_len(chunk, 9)?;
+                TypedChunk::Phys(Phys {
+                    pixels_per_unit_x: be_u32(&data[0..4]),
+                    pixels_per_unit_y: be_u32(&data[4..8]),
+                    unit: data[8],
+                })
+            }
+            [b'g', b'A', b'M', b'A'] => {
+                let data = expect_len(chunk, 4)?;
+                TypedChunk::Gama(Gama { gamma: be_u32(data) })
+            }
+            [b'I', b'E', b'N', b'D'] => {
+                expect_len(chunk, 0)?;
+                TypedChunk::Iend
+            }
+            _ => TypedChunk::Other(*ct),
+        };
+        Ok(typed)
+    }
+}
+
+impl Chunk {
+    pub fn decode_typed(&self) -> Result<TypedChunk> {
+        TypedChunk::try_from(self)
+    }
+}
+
+impl TypedChunk {
+    // Keyword de tEXt, zTXt e iTXt; None para los demás tipos
+    pub fn keyword(&self) -> Option<&str> {
+        match self {
+            TypedChunk::Text(text) => Some(&text.keyword),
+            TypedChunk::Ztxt(ztxt) => Some(&ztxt.keyword),
+            TypedChunk::Itxt(itxt) => Some(&itxt.keyword),
+            _ => None,
+        }
+    }
+}
+
+fn has_keyword(chunk: &Chunk, keyword: &str) -> bool {
+    chunk.decode_typed().is_ok_and(|typed| typed.keyword() == Some(keyword))
+}
+
+impl Png {
+    pub fn has_text(&self, keyword: &str) -> bool {
+        self.chunks().iter().any(|chunk| has_keyword(chunk, keyword))
+    }
+
+    // Borra los chunks de texto con ese keyword; devuelve cuántos había
+    pub fn remove_text(&mut self, keyword: &str) -> usize {
+        let before = self.chunks().len();
+        self.chunks_mut().retain(|chunk| !has_keyword(chunk, keyword));
+        before - self.chunks().len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn chunk(chunk_type: &str, data: &[u8]) -> Chunk {
+        Chunk::new(ChunkType::from_str(chunk_type).unwrap(), data.to_vec()).unwrap()
+    }
+
+    #[test]
+    fn test_decode_ihdr() {
+        let data = [0, 0, 0, 4, 0, 0, 0, 2, 8, 2, 0, 0, 0];
+        let typed = chunk("IHDR", &data).decode_typed().unwrap();
+        match typed {
+            TypedChunk::Ihdr(ihdr) => {
+                assert_eq!((ihdr.width, ihdr.height, ihdr.bit_depth, ihdr.color_type), (4, 2, 8, 2));
+            }
+            other => panic!("se esperaba IHDR: {:?}", other),
+        }
+        assert!(chunk("IHDR", &data[..12]).decode_typed().is_err());
+    }
+
+    #[test]
+    fn test_ihdr_validation() {
+        // RGB con 4 bits no está permitido
+        assert!(chunk("IHDR", &[0, 0, 0, 4, 0, 0, 0, 2, 4, 2, 0, 0, 0]).decode_typed().is_err());
+        // tipo de color 5 no existe
+        assert!(chunk("IHDR", &[0, 0, 0, 4, 0, 0, 0, 2, 8, 5, 0, 0, 0]).decode_typed().is_err());
+        // ancho cero
+        assert!(chunk("IHDR", &[0, 0, 0, 0, 0, 0, 0, 2, 8, 2, 0, 0, 0]).decode_typed().is_err());
+        // paleta de 4 bits entrelazada
+        let typed = chunk("IHDR", &[0, 0, 0, 4, 0, 0, 0, 2, 4, 3, 0, 0, 1]).decode_typed().unwrap();
+        match typed {
+            TypedChunk::Ihdr(ihdr) => {
+                assert_eq!(ihdr.color(), Some(ColorType::Indexed));
+                assert!(ihdr.is_interlaced());
+            }
+            other => panic!("se esperaba IHDR: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_text() {
+        let typed = chunk("tEXt", b"Author\0Iv\xe1n").decode_typed().unwrap();
+        assert_eq!(
+            typed,
+            TypedChunk::Text(TextChunk { keyword: "Author".to_string(), text: "Iván".to_string() })
+        );
+        assert!(chunk("tEXt", b"sin separador").decode_typed().is_err());
+    }
+
+    #[test]
+    fn test_text_chunk_round_trip() {
+        let chunk = TextChunk::new("Comment", "¡Hola!").unwrap().to_chunk().unwrap();
+        assert_eq!(chunk.data(), b"Comment\0\xa1Hola!");
+        let typed = chunk.decode_typed().unwrap();
+        assert_eq!(
+            typed,
+            TypedChunk::Text(TextChunk { keyword: "Comment".to_string(), text: "¡Hola!".to_string() })
+        );
+    }
+
+    #[test]
+    fn test_text_chunk_keyword_validation() {
+        assert!(TextChunk::new("", "x").is_err());
+        assert!(TextChunk::new(&"k".repeat(80), "x").is_err());
+        assert!(TextChunk::new(" Title", "x").is_err());
+        assert!(TextChunk::new("Two  spaces", "x").is_err());
+        assert!(TextChunk::new("Tab\there", "x").is_err());
+        assert!(TextChunk::new("Title", "日本語").is_err());
+        assert!(TextChunk::new("Title", "con\0nulo").is_err());
+    }
+
+    #[test]
+    fn test_ztxt_round_trip() {
+        let text = "Un comentario largo, largo, largo, largo, largo";
+        let chunk = Ztxt::new("Comment", text).unwrap().to_chunk().unwrap();
+        assert!(chunk.data().starts_with(b"Comment\0\0"));
+        match chunk.decode_typed().unwrap() {
+            TypedChunk::Ztxt(ztxt) => assert_eq!(ztxt.text().unwrap(), text),
+            other => panic!("se esperaba zTXt: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_ztxt_bad_method() {
+        let mut ztxt = Ztxt::new("Comment", "hola").unwrap();
+        ztxt.compression_method = 1;
+        assert!(ztxt.text().is_err());
+    }
+
+    #[test]
+    fn test_decompression_limit() {
+        let ztxt = Ztxt::new("Comment", &"a".repeat(100)).unwrap();
+        assert_eq!(ztxt.text_limited(100).unwrap().len(), 100);
+        assert!(matches!(ztxt.text_limited(99), Err(PngmeError::DecompressionLimit { limit: 99, .. })));
+        let itxt = Itxt::new("Comment", "", "", &"a".repeat(100), true).unwrap();
+        assert!(matches!(itxt.text_limited(99), Err(PngmeError::DecompressionLimit { .. })));
+
+        let mut data = b"perfil\0\0".to_vec();
+        data.extend(deflate(&[0; 1000]).unwrap());
+        let TypedChunk::Iccp(iccp) = chunk("iCCP", &data).decode_typed().unwrap() else {
+            panic!("se esperaba iCCP");
+        };
+        assert_eq!(iccp.profile_name, "perfil");
+        assert_eq!(iccp.profile().unwrap().len(), 1000);
+        assert!(matches!(iccp.profile_limited(999), Err(PngmeError::DecompressionLimit { .. })));
+    }
+
+    #[test]
+    fn test_decode_itxt() {
+        let typed = chunk("iTXt", b"Title\0\0\0es\0T\xc3\xadtulo\0hola").decode_typed().unwrap();
+        match typed {
+            TypedChunk::Itxt(itxt) => {
+                assert_eq!(itxt.keyword, "Title");
+                assert!(!itxt.compressed);
+                assert_eq!(itxt.language_tag, "es");
+                assert_eq!(itxt.translated_keyword, "Título");
+                assert_eq!(itxt.text, b"hola");
+            }
+            other => panic!("se esperaba iTXt: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_itxt_round_trip() {
+        for compressed in [false, true] {
+            let itxt = Itxt::new("Title", "es", "Título", "Un mensaje en español: ñ", compressed).unwrap();
+            let chunk = itxt.to_chunk().unwrap();
+            match chunk.decode_typed().unwrap() {
+                TypedChunk::Itxt(decoded) => {
+                    assert_eq!(decoded, itxt);
+                    assert_eq!(decoded.text().unwrap(), "Un mensaje en español: ñ");
+                }
+                other => panic!("se esperaba iTXt: {:?}", other),
+            }
+        }
+        assert!(Itxt::new("Title", "es_AR", "", "hola", false).is_err());
+    }
+
+    #[test]
+    fn test_remove_text_by_keyword() {
+        let mut png = Png::from_chunks(vec![
+            TextChunk::new("Comment", "uno").unwrap().to_chunk().unwrap(),
+            Ztxt::new("Author", "dos").unwrap().to_chunk().unwrap(),
+            Itxt::new("Comment", "", "", "tres", true).unwrap().to_chunk().unwrap(),
+            chunk("ruSt", b"Comment\0cuatro"),
+        ]);
+        assert!(png.has_text("Comment"));
+        assert_eq!(png.remove_text("Comment"), 2);
+        assert!(!png.has_text("Comment"));
+        assert!(png.has_text("Author"));
+        assert_eq!(png.chunks().len(), 2);
+    }
+
+    #[test]
+    fn test_decode_time_phys_gama() {
+        let time = chunk("tIME", &[7, 234, 10, 16, 12, 30, 0]).decode_typed().unwrap();
+        assert_eq!(
+            time,
+            TypedChunk::Time(Time { year: 2026, month: 10, day: 16, hour: 12, minute: 30, second: 0 })
+        );
+
+        let phys = chunk("pHYs", &[0, 0, 11, 19, 0, 0, 11, 19, 1]).decode_typed().unwrap();
+        assert_eq!(
+            phys,
+            TypedChunk::Phys(Phys { pixels_per_unit_x: 2835, pixels_per_unit_y: 2835, unit: 1 })
+        );
+
+        let gama = chunk("gAMA", &[0, 0, 177, 143]).decode_typed().unwrap();
+        match gama {
+            TypedChunk::Gama(gama) => assert!((gama.value() - 0.45455).abs() < 1e-9),
+            other => panic!("se esperaba gAMA: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_other() {
+        let typed = chunk("ruSt", b"hola").decode_typed().unwrap();
+        assert_eq!(typed, TypedChunk::Other(ChunkType::from_str("ruSt").unwrap()));
+    }
+}
@@ -0,0 +1,178 @@
+use std::fmt::Display;
+use crate::chunk_ref::ChunkRef;
+use crate::locale::text;
+use crate::tr;
+use crate::chunk_type::ChunkType;
+use crate::png::Png;
+use crate::typed::Ihdr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    pub severity: Severity,
+    // offset absoluto del chunk (o del byte) en el que se encontró el problema
+    pub offset: Option<usize>,
+    pub message: String,
+}
+
+impl Problem {
+    fn error(offset: Option<usize>, message: String) -> Problem {
+        Problem { severity: Severity::Error, offset, message }
+    }
+
+    fn warning(offset: Option<usize>, message: String) -> Problem {
+        Problem { severity: Severity::Warning, offset, message }
+    }
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => text("aviso", "warning"),
+            Severity::Error => text("error", "error"),
+        };
+        match self.offset {
+            Some(offset) => write!(f, "{} [offset {}]: {}", severity, offset, self.message),
+            None => write!(f, "{}: {}", severity, self.message),
+        }
+    }
+}
+
+// Revisa firma, CRC y estructura básica, juntando todos los problemas en lugar de
+// cortar en el primero
+pub fn verify_bytes(bytes: &[u8]) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    if bytes.len() < 8 || bytes[..8] != Png::STANDARD_HEADER {
+        problems.push(Problem::error(Some(0), tr!("la firma no corresponde a un PNG", "the signature is not a PNG signature")));
+        return problems;
+    }
+
+    let mut offset = 8;
+    let mut index = 0;
+    let mut seen_iend = false;
+    let mut ihdr_count = 0;
+    let mut idat_count = 0;
+    while offset < bytes.len() {
+        if seen_iend {
+            let extra = bytes.len() - offset;
+            problems.push(Problem::warning(Some(offset), tr!("hay {} bytes después de IEND", "there are {} bytes after IEND", extra)));
+            break;
+        }
+        let chunk = match ChunkRef::parse_at(&bytes[offset..], offset) {
+            Ok(chunk) => chunk,
+            Err(err) => {
+                problems.push(Problem::error(err.offset().or(Some(offset)), err.to_string()));
+                return problems;
+            }
+        };
+        let ct = chunk.chunk_type();
+
+        if !chunk.is_crc_valid() {
+            problems.push(Problem::error(
+                Some(offset),
+                tr!(
+                    "CRC inválido en {}: se esperaba {:08x} y se leyó {:08x}",
+                    "invalid CRC in {}: expected {:08x}, read {:08x}",
+                    ct,
+                    chunk.computed_crc(),
+                    chunk.crc()
+                ),
+            ));
+        }
+        if !ct.is_reserved_bit_valid() {
+            problems.push(Problem::warning(Some(offset), tr!("{} tiene el bit reservado en uso", "{} has the reserved bit set", ct)));
+        }
+        if ct.is_critical() && !ct.is_standard() {
+            problems.push(Problem::error(Some(offset), tr!("chunk crítico desconocido {}", "unknown critical chunk {}", ct)));
+        }
+
+        if ct == ChunkType::IHDR {
+            ihdr_count += 1;
+            if index != 0 {
+                problems.push(Problem::error(Some(offset), tr!("IHDR no es el primer chunk", "IHDR is not the first chunk")));
+            }
+            if let Err(err) = Ihdr::from_chunk(&chunk.to_chunk()) {
+                problems.push(Problem::error(Some(offset), err.to_string()));
+            }
+        } else if index == 0 {
+            problems.push(Problem::error(Some(offset), tr!("el primer chunk es {} y no IHDR", "the first chunk is {}, not IHDR", ct)));
+        }
+        if ct == ChunkType::IDAT {
+            idat_count += 1;
+        }
+        if ct == ChunkType::IEND {
+            seen_iend = true;
+        }
+
+        offset += chunk.total_len();
+        index += 1;
+    }
+
+    if ihdr_count > 1 {
+        problems.push(Problem::error(None, tr!("hay {} chunks IHDR", "there are {} IHDR chunks", ihdr_count)));
+    }
+    if idat_count == 0 {
+        problems.push(Problem::error(None, tr!("no hay ningún chunk IDAT", "there is no IDAT chunk")));
+    }
+    if !seen_iend {
+        problems.push(Problem::error(None, tr!("falta el chunk IEND", "the IEND chunk is missing")));
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::Chunk;
+    use std::str::FromStr;
+
+    fn testing_bytes() -> Vec<u8> {
+        let ihdr = Chunk::new(ChunkType::IHDR, vec![0, 0, 0, 2, 0, 0, 0, 2, 8, 2, 0, 0, 0]).unwrap();
+        let idat = Chunk::new(ChunkType::IDAT, vec![1, 2, 3]).unwrap();
+        let text = Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"hola".to_vec()).unwrap();
+        let iend = Chunk::new(ChunkType::IEND, Vec::new()).unwrap();
+        Png::from_chunks(vec![ihdr, idat, text, iend]).as_bytes()
+    }
+
+    #[test]
+    fn test_clean_file() {
+        assert!(verify_bytes(&testing_bytes()).is_empty());
+    }
+
+    #[test]
+    fn test_reports_every_bad_crc() {
+        let mut bytes = testing_bytes();
+        // último byte del CRC de IHDR y del de IDAT
+        bytes[8 + 12 + 13 - 1] ^= 1;
+        bytes[33 + 12 + 3 - 1] ^= 1;
+        let problems = verify_bytes(&bytes);
+        assert_eq!(problems.len(), 2);
+        assert!(problems.iter().all(|p| p.severity == Severity::Error));
+        assert_eq!(problems[1].offset, Some(33));
+    }
+
+    #[test]
+    fn test_missing_iend_and_trailing_data() {
+        let bytes = testing_bytes();
+        let problems = verify_bytes(&bytes[..bytes.len() - 12]);
+        assert_eq!(problems, vec![Problem::error(None, "falta el chunk IEND".to_string())]);
+
+        let mut bytes = testing_bytes();
+        bytes.extend_from_slice(b"xx");
+        let problems = verify_bytes(&bytes);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn test_bad_signature() {
+        let mut bytes = testing_bytes();
+        bytes[0] = 0;
+        assert_eq!(verify_bytes(&bytes).len(), 1);
+    }
+}